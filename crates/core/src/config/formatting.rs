@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormattingConfig {
+  /// Spaces per indentation level inside `live_design!` blocks.
+  pub indent_width: usize,
+  pub trailing_commas: TrailingCommas,
+}
+
+/// Whether properties and children written one per line end with a `,`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrailingCommas {
+  Always,
+  Never,
+  /// Commas after every entry of a body that has any, none in the others.
+  #[default]
+  Preserve,
+}
+
+impl Default for FormattingConfig {
+  fn default() -> Self {
+    Self {
+      indent_width: 4,
+      trailing_commas: TrailingCommas::default(),
+    }
+  }
+}
@@ -1,5 +1,7 @@
+mod formatting;
 mod logging;
 
+pub use formatting::{FormattingConfig, TrailingCommas};
 use logging::LoggingConfig;
 use serde::{Deserialize, Serialize};
 
@@ -19,4 +21,6 @@ pub struct Config {
   pub client: LSPClient,
   #[serde(default)]
   pub logging: LoggingConfig,
+  #[serde(default)]
+  pub formatting: FormattingConfig,
 }
@@ -7,6 +7,8 @@ pub enum DocumentError {
   DocumentNotFound { path: String },
   #[error("Missing Cargo.toml in {:?}", dir)]
   ManifestFileNotFound { dir: String },
+  #[error("`cargo metadata` failed for the manifest at {:?} : {:?}", path, err)]
+  CargoMetadataFailed { path: String, err: String },
   #[error("Cannot get member manifest files for the manifest at {:?}", dir)]
   MemberManifestsFailed { dir: String },
   #[error("Document is already stored at {:?}", path)]
@@ -1,10 +1,14 @@
 mod document_error;
 mod directory_error;
 mod sync_error;
+mod rename_error;
+mod plugin_error;
 
 pub use document_error::DocumentError;
 pub use sync_error::SyncError;
 pub use directory_error::DirectoryError;
+pub use rename_error::RenameError;
+pub use plugin_error::PluginError;
 
 use thiserror::Error;
 
@@ -16,4 +20,8 @@ pub enum MakepadAnalyzerError {
   SyncError(#[from] SyncError),
   #[error(transparent)]
   DirectoryError(#[from] DirectoryError),
+  #[error(transparent)]
+  RenameError(#[from] RenameError),
+  #[error(transparent)]
+  PluginError(#[from] PluginError),
 }
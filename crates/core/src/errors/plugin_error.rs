@@ -0,0 +1,9 @@
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PluginError {
+  #[error("Plugin `{}` was built for plugin API {}, but the server speaks {}", name, found, expected)]
+  ApiVersionMismatch { name: String, expected: u32, found: u32 },
+  #[error("A plugin named `{}` is already registered", name)]
+  AlreadyRegistered { name: String },
+}
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RenameError {
+  #[error("`{}` is provided by `{}` and can't be renamed", name, module)]
+  FrameworkSymbol { name: String, module: String },
+  #[error("`{}` isn't declared in this package", name)]
+  SymbolNotDeclared { name: String },
+  #[error("No renamable symbol at the cursor")]
+  NoSymbol,
+  #[error("`{}` isn't a valid name for a {}", name, kind)]
+  InvalidName { name: String, kind: String },
+}
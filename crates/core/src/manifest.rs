@@ -1,4 +1,4 @@
-use std::path::{Path, PathBuf};
+use std::{collections::HashMap, env, path::{Path, PathBuf}, process::Command};
 use serde::{Deserialize, Serialize};
 use crate::errors::{DocumentError, MakepadAnalyzerError};
 
@@ -32,6 +32,31 @@ impl MakepadManifestFile {
     &self.path
   }
 
+  /// The package directory, which `crate://self/` dependencies are relative to.
+  pub fn dir(&self) -> &Path {
+    self.path.parent().unwrap_or(&self.path)
+  }
+
+  /// The source directories of every package in the dependency graph, by package name, as
+  /// reported by `cargo metadata`. Runs offline, so it fails rather than fetch anything.
+  pub fn dependency_dirs(&self) -> Result<HashMap<String, PathBuf>, DocumentError> {
+    let metadata_failed = |err: String| DocumentError::CargoMetadataFailed {
+      path: self.path.to_string_lossy().to_string(),
+      err,
+    };
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let output = Command::new(cargo)
+      .args(["metadata", "--offline", "--format-version", "1", "--manifest-path"])
+      .arg(&self.path)
+      .output()
+      .map_err(|err| metadata_failed(err.to_string()))?;
+    if !output.status.success() {
+      return Err(metadata_failed(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    parse_dependency_dirs(&String::from_utf8_lossy(&output.stdout))
+      .ok_or_else(|| metadata_failed("unexpected output".to_string()))
+  }
+
   fn find_cargo_toml(start_dir: &Path) -> Option<PathBuf> {
     let mut current_dir = start_dir.to_path_buf();
     while current_dir.exists() {
@@ -45,6 +70,22 @@ impl MakepadManifestFile {
   }
 }
 
+/// Reads the `packages` of `cargo metadata` output into a map from package name to the
+/// directory holding its `Cargo.toml`.
+fn parse_dependency_dirs(metadata: &str) -> Option<HashMap<String, PathBuf>> {
+  let metadata: serde_json::Value = serde_json::from_str(metadata).ok()?;
+  let dirs = metadata
+    .get("packages")?
+    .as_array()?
+    .iter()
+    .filter_map(|package| {
+      let name = package.get("name")?.as_str()?;
+      let manifest_path = Path::new(package.get("manifest_path")?.as_str()?);
+      Some((name.to_string(), manifest_path.parent()?.to_path_buf()))
+    })
+    .collect();
+  Some(dirs)
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "kebab-case")]
@@ -73,4 +114,19 @@ mod tests {
     assert_eq!(cargo_toml, Some(PathBuf::from("D:\\projects\\project-robius\\robrix\\Cargo.toml")));
     assert_eq!(cargo_toml2, Some(PathBuf::from("E:\\makepad\\examples\\simple\\Cargo.toml")));
   }
+
+  #[test]
+  fn test_parse_dependency_dirs() {
+    let metadata = r#"{
+      "packages": [
+        {"name": "robrix", "manifest_path": "/work/robrix/Cargo.toml"},
+        {"name": "makepad-widgets", "manifest_path": "/cargo/makepad-widgets-0.6.0/Cargo.toml"}
+      ],
+      "version": 1
+    }"#;
+    let dirs = parse_dependency_dirs(metadata).unwrap();
+    assert_eq!(dirs.len(), 2);
+    assert_eq!(dirs["makepad-widgets"], PathBuf::from("/cargo/makepad-widgets-0.6.0"));
+    assert!(parse_dependency_dirs("{}").is_none());
+  }
 }
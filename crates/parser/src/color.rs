@@ -0,0 +1,115 @@
+/// An 8-bit RGBA color, as written by a `#` literal in a `live_design!` block.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgba {
+  pub r: u8,
+  pub g: u8,
+  pub b: u8,
+  pub a: u8,
+}
+
+impl Rgba {
+  pub fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+    Rgba { r, g, b, a }
+  }
+
+  /// Parses the hex digits of a color literal, without the `#` or `#x` prefix.
+  ///
+  /// Makepad accepts the short forms `#g` and `#gg` (gray), `#rgb` and `#rgba` besides
+  /// `#rrggbb` and `#rrggbbaa`; colors without an alpha are opaque.
+  pub fn from_hex(digits: &str) -> Option<Rgba> {
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+      return None;
+    }
+    let nibble = |i: usize| u8::from_str_radix(&digits[i..i + 1], 16).ok().map(|n| n * 17);
+    let byte = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    match digits.len() {
+      1 => nibble(0).map(|v| Rgba::new(v, v, v, 255)),
+      2 => byte(0).map(|v| Rgba::new(v, v, v, 255)),
+      3 => Some(Rgba::new(nibble(0)?, nibble(1)?, nibble(2)?, 255)),
+      4 => Some(Rgba::new(nibble(0)?, nibble(1)?, nibble(2)?, nibble(3)?)),
+      6 => Some(Rgba::new(byte(0)?, byte(2)?, byte(4)?, 255)),
+      8 => Some(Rgba::new(byte(0)?, byte(2)?, byte(4)?, byte(6)?)),
+      _ => None,
+    }
+  }
+
+  /// Converts from normalized `0.0..=1.0` channels, as used by `vec4` and LSP colors.
+  pub fn from_f32(r: f32, g: f32, b: f32, a: f32) -> Self {
+    let byte = |channel: f32| (channel.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Rgba::new(byte(r), byte(g), byte(b), byte(a))
+  }
+
+  pub fn to_f32(&self) -> [f32; 4] {
+    [self.r, self.g, self.b, self.a].map(|channel| channel as f32 / 255.0)
+  }
+
+  /// The shortest literal Makepad expands back to this color, e.g. `#091` or `#2a`.
+  pub fn to_short_hex(&self) -> String {
+    let doubled = |channel: u8| channel % 17 == 0;
+    let nibble = |channel: u8| format!("{:x}", channel / 17);
+    let is_gray = self.r == self.g && self.g == self.b;
+    let rgb_doubled = doubled(self.r) && doubled(self.g) && doubled(self.b);
+    match (self.a == 255, is_gray) {
+      (true, true) if doubled(self.r) => format!("#{}", nibble(self.r)),
+      (true, true) => format!("#{:02x}", self.r),
+      (true, false) if rgb_doubled => {
+        format!("#{}{}{}", nibble(self.r), nibble(self.g), nibble(self.b))
+      }
+      (false, _) if rgb_doubled && doubled(self.a) => format!(
+        "#{}{}{}{}",
+        nibble(self.r),
+        nibble(self.g),
+        nibble(self.b),
+        nibble(self.a)
+      ),
+      _ => self.to_hex(),
+    }
+  }
+
+  /// `#rrggbb` for opaque colors, `#rrggbbaa` otherwise.
+  pub fn to_hex(&self) -> String {
+    if self.a == 255 {
+      format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    } else {
+      format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_color_short_forms() {
+    assert_eq!(Rgba::from_hex("344054"), Some(Rgba::new(0x34, 0x40, 0x54, 255)));
+    assert_eq!(Rgba::from_hex("091"), Some(Rgba::new(0x00, 0x99, 0x11, 255)));
+    assert_eq!(Rgba::from_hex("0000"), Some(Rgba::new(0, 0, 0, 0)));
+    assert_eq!(Rgba::from_hex("2A"), Some(Rgba::new(0x2a, 0x2a, 0x2a, 255)));
+    assert_eq!(Rgba::from_hex("f"), Some(Rgba::new(255, 255, 255, 255)));
+    assert_eq!(Rgba::from_hex("ff000080"), Some(Rgba::new(255, 0, 0, 0x80)));
+    assert_eq!(Rgba::from_hex("12345"), None);
+    assert_eq!(Rgba::from_hex("12g"), None);
+
+    assert_eq!(Rgba::from_hex("091").unwrap().to_hex(), "#009911");
+    assert_eq!(Rgba::from_hex("0000").unwrap().to_hex(), "#00000000");
+  }
+
+  #[test]
+  fn test_color_short_hex_round_trips() {
+    for digits in ["f", "2a", "091", "0000", "344054", "ff000080", "1234"] {
+      let color = Rgba::from_hex(digits).unwrap();
+      let short = color.to_short_hex();
+      assert_eq!(Rgba::from_hex(&short[1..]), Some(color), "{} -> {}", digits, short);
+    }
+    assert_eq!(Rgba::from_hex("ffffff").unwrap().to_short_hex(), "#f");
+    assert_eq!(Rgba::from_hex("2a2a2a").unwrap().to_short_hex(), "#2a");
+    assert_eq!(Rgba::from_hex("009911").unwrap().to_short_hex(), "#091");
+    assert_eq!(Rgba::from_hex("00000000").unwrap().to_short_hex(), "#0000");
+    assert_eq!(Rgba::from_hex("344054").unwrap().to_short_hex(), "#344054");
+
+    let color = Rgba::from_f32(1.0, 0.0, 0.5, 1.0);
+    assert_eq!(color, Rgba::new(255, 0, 128, 255));
+    assert_eq!(color.to_f32()[0], 1.0);
+  }
+}
@@ -0,0 +1,840 @@
+use crate::span::{Span, SyntaxError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Punct {
+  LBrace,
+  RBrace,
+  LParen,
+  RParen,
+  LBracket,
+  RBracket,
+  Lt,
+  Gt,
+  LtEq,
+  GtEq,
+  Colon,
+  PathSep,
+  Semi,
+  Comma,
+  Dot,
+  Eq,
+  EqEq,
+  NotEq,
+  Arrow,
+  Plus,
+  Minus,
+  Star,
+  Slash,
+  Percent,
+  PlusEq,
+  MinusEq,
+  StarEq,
+  SlashEq,
+  Bang,
+  AndAnd,
+  OrOr,
+  Amp,
+  Pipe,
+  Caret,
+  Question,
+}
+
+impl Punct {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Punct::LBrace => "{",
+      Punct::RBrace => "}",
+      Punct::LParen => "(",
+      Punct::RParen => ")",
+      Punct::LBracket => "[",
+      Punct::RBracket => "]",
+      Punct::Lt => "<",
+      Punct::Gt => ">",
+      Punct::LtEq => "<=",
+      Punct::GtEq => ">=",
+      Punct::Colon => ":",
+      Punct::PathSep => "::",
+      Punct::Semi => ";",
+      Punct::Comma => ",",
+      Punct::Dot => ".",
+      Punct::Eq => "=",
+      Punct::EqEq => "==",
+      Punct::NotEq => "!=",
+      Punct::Arrow => "->",
+      Punct::Plus => "+",
+      Punct::Minus => "-",
+      Punct::Star => "*",
+      Punct::Slash => "/",
+      Punct::Percent => "%",
+      Punct::PlusEq => "+=",
+      Punct::MinusEq => "-=",
+      Punct::StarEq => "*=",
+      Punct::SlashEq => "/=",
+      Punct::Bang => "!",
+      Punct::AndAnd => "&&",
+      Punct::OrOr => "||",
+      Punct::Amp => "&",
+      Punct::Pipe => "|",
+      Punct::Caret => "^",
+      Punct::Question => "?",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+  /// `width`, `Fill`, `fn`, `self`, ...
+  Ident(String),
+  /// `<View>`, holding the widget name.
+  WidgetRef(String),
+  /// `{{App}}`, holding the Rust type name.
+  RustBinding(String),
+  /// `#344054`, holding the hex digits without the leading `#` (or `#x`).
+  Color(String),
+  /// `dep("crate://self/resources/icons/chat.svg")`, holding the resource path.
+  Dep(String),
+  /// `20`, `0.5`, `1.5e3`, with an optional unit suffix such as `20px`.
+  Number { value: f64, unit: Option<String> },
+  String(String),
+  LineComment(String),
+  BlockComment(String),
+  Punct(Punct),
+  /// A character that can't start any token; an error is reported for it.
+  Unknown(char),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+  pub kind: TokenKind,
+  pub span: Span,
+}
+
+impl Token {
+  pub fn is_trivia(&self) -> bool {
+    matches!(self.kind, TokenKind::LineComment(_) | TokenKind::BlockComment(_))
+  }
+
+  pub fn is_punct(&self, punct: Punct) -> bool {
+    self.kind == TokenKind::Punct(punct)
+  }
+
+  pub fn is_ident(&self, name: &str) -> bool {
+    matches!(&self.kind, TokenKind::Ident(ident) if ident == name)
+  }
+
+  pub fn ident(&self) -> Option<&str> {
+    match &self.kind {
+      TokenKind::Ident(ident) => Some(ident),
+      _ => None,
+    }
+  }
+
+  /// The span of the name inside a `<Widget>` or `{{RustType}}` token.
+  pub fn name_span(&self, source: &str) -> Span {
+    match &self.kind {
+      TokenKind::WidgetRef(name) | TokenKind::RustBinding(name) => {
+        // Whitespace is allowed around the name, e.g. `< View >`.
+        let offset = self.span.text(source).find(name.as_str()).unwrap_or_default();
+        let start = self.span.start + offset;
+        Span::new(start, start + name.len())
+      }
+      _ => self.span,
+    }
+  }
+}
+
+/// The tokens of a single `live_design! { ... }` invocation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexedBlock {
+  /// The whole invocation, from `live_design` to the closing delimiter.
+  pub span: Span,
+  /// The contents between the opening and closing delimiters.
+  pub body_span: Span,
+  pub tokens: Vec<Token>,
+  pub errors: Vec<SyntaxError>,
+}
+
+/// Finds every `live_design!` invocation in a Rust source file and tokenizes its body.
+pub fn lex_live_design_blocks(source: &str) -> Vec<LexedBlock> {
+  let mut blocks = vec![];
+  let mut pos = 0;
+  while let Some((span_start, open, body_start)) = find_next_invocation(source, pos) {
+    let block = lex_block(source, span_start, open, body_start);
+    pos = block.span.end.max(body_start);
+    blocks.push(block);
+  }
+  blocks
+}
+
+/// Tokenizes `source` as live DSL from start to end, without looking for `live_design!`.
+pub fn tokenize(source: &str) -> (Vec<Token>, Vec<SyntaxError>) {
+  let mut lexer = Lexer::new(source, 0);
+  let mut tokens = vec![];
+  while let Some(token) = lexer.next_token() {
+    tokens.push(token);
+  }
+  (tokens, lexer.errors)
+}
+
+fn lex_block(source: &str, span_start: usize, open: Punct, body_start: usize) -> LexedBlock {
+  let close = match open {
+    Punct::LParen => Punct::RParen,
+    Punct::LBracket => Punct::RBracket,
+    _ => Punct::RBrace,
+  };
+
+  let mut lexer = Lexer::new(source, body_start);
+  let mut tokens = vec![];
+  let mut depth = 0usize;
+  loop {
+    let token_start = lexer.pos;
+    let Some(token) = lexer.next_token() else {
+      lexer.errors.push(SyntaxError::new(
+        format!("unclosed `live_design!` block, expected `{}`", close.as_str()),
+        Span::new(span_start, body_start),
+      ));
+      return LexedBlock {
+        span: Span::new(span_start, source.len()),
+        body_span: Span::new(body_start, source.len()),
+        tokens,
+        errors: lexer.errors,
+      };
+    };
+
+    if token.is_punct(open) {
+      depth += 1;
+    } else if token.is_punct(close) {
+      if depth == 0 {
+        return LexedBlock {
+          span: Span::new(span_start, token.span.end),
+          body_span: Span::new(body_start, token_start.min(token.span.start)),
+          tokens,
+          errors: lexer.errors,
+        };
+      }
+      depth -= 1;
+    }
+    tokens.push(token);
+  }
+}
+
+/// Scans plain Rust source for the next `live_design!` invocation, skipping comments, strings
+/// and char literals. Returns the start of the invocation, its opening delimiter and the
+/// offset right after that delimiter.
+fn find_next_invocation(source: &str, from: usize) -> Option<(usize, Punct, usize)> {
+  const MACRO_NAME: &str = "live_design";
+
+  let bytes = source.as_bytes();
+  let mut i = from;
+  while i < bytes.len() {
+    let b = bytes[i];
+    match b {
+      b'/' if bytes.get(i + 1) == Some(&b'/') => {
+        i = skip_line(bytes, i);
+      }
+      b'/' if bytes.get(i + 1) == Some(&b'*') => {
+        i = skip_block_comment(bytes, i);
+      }
+      b'"' => {
+        i = skip_string(bytes, i + 1);
+      }
+      b'r' | b'b' if is_raw_string_start(bytes, i) => {
+        i = skip_raw_string(bytes, i);
+      }
+      b'\'' => {
+        i = skip_char_literal(source, i);
+      }
+      _ if b.is_ascii_alphabetic() || b == b'_' => {
+        let start = i;
+        while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+          i += 1;
+        }
+        if &bytes[start..i] != MACRO_NAME.as_bytes() {
+          continue;
+        }
+        let mut j = skip_whitespace(bytes, i);
+        if bytes.get(j) != Some(&b'!') {
+          continue;
+        }
+        j = skip_whitespace(bytes, j + 1);
+        let open = match bytes.get(j) {
+          Some(b'{') => Punct::LBrace,
+          Some(b'(') => Punct::LParen,
+          Some(b'[') => Punct::LBracket,
+          _ => continue,
+        };
+        return Some((start, open, j + 1));
+      }
+      _ => i += 1,
+    }
+  }
+  None
+}
+
+fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+  while i < bytes.len() && (bytes[i] as char).is_ascii_whitespace() {
+    i += 1;
+  }
+  i
+}
+
+fn skip_line(bytes: &[u8], mut i: usize) -> usize {
+  while i < bytes.len() && bytes[i] != b'\n' {
+    i += 1;
+  }
+  i
+}
+
+fn skip_block_comment(bytes: &[u8], mut i: usize) -> usize {
+  let mut depth = 0;
+  while i < bytes.len() {
+    if bytes[i] == b'/' && bytes.get(i + 1) == Some(&b'*') {
+      depth += 1;
+      i += 2;
+    } else if bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/') {
+      depth -= 1;
+      i += 2;
+      if depth == 0 {
+        return i;
+      }
+    } else {
+      i += 1;
+    }
+  }
+  i
+}
+
+/// Skips a string body, `i` being the offset right after the opening quote.
+fn skip_string(bytes: &[u8], mut i: usize) -> usize {
+  while i < bytes.len() {
+    match bytes[i] {
+      b'\\' => i += 2,
+      b'"' => return i + 1,
+      _ => i += 1,
+    }
+  }
+  i
+}
+
+fn is_raw_string_start(bytes: &[u8], i: usize) -> bool {
+  if i > 0 && is_ident_continue(bytes[i - 1] as char) {
+    return false;
+  }
+  let mut j = i;
+  if bytes[j] == b'b' {
+    j += 1;
+  }
+  if bytes.get(j) != Some(&b'r') {
+    return false;
+  }
+  j += 1;
+  while bytes.get(j) == Some(&b'#') {
+    j += 1;
+  }
+  bytes.get(j) == Some(&b'"')
+}
+
+fn skip_raw_string(bytes: &[u8], mut i: usize) -> usize {
+  while bytes[i] != b'#' && bytes[i] != b'"' {
+    i += 1;
+  }
+  let mut hashes = 0;
+  while bytes[i] == b'#' {
+    hashes += 1;
+    i += 1;
+  }
+  // Skip the opening quote.
+  i += 1;
+  while i < bytes.len() {
+    if bytes[i] == b'"' && bytes[i + 1..].iter().take(hashes).filter(|b| **b == b'#').count() == hashes {
+      return i + 1 + hashes;
+    }
+    i += 1;
+  }
+  i
+}
+
+/// Skips a char literal such as `'a'` or `'\n'`, or just the quote of a lifetime like `'a`.
+fn skip_char_literal(source: &str, i: usize) -> usize {
+  let bytes = source.as_bytes();
+  match bytes.get(i + 1) {
+    Some(b'\\') => {
+      let mut j = i + 2;
+      while j < bytes.len() && bytes[j] != b'\'' && bytes[j] != b'\n' {
+        j += 1;
+      }
+      j + 1
+    }
+    Some(_) => {
+      // A char literal is one (possibly multi-byte) char followed by a closing quote.
+      let len = source[i + 1..].chars().next().map_or(1, char::len_utf8);
+      if bytes.get(i + 1 + len) == Some(&b'\'') {
+        i + 2 + len
+      } else {
+        i + 1
+      }
+    }
+    None => i + 1,
+  }
+}
+
+pub(crate) fn is_ident_start(c: char) -> bool {
+  c == '_' || c.is_alphabetic()
+}
+
+pub(crate) fn is_ident_continue(c: char) -> bool {
+  c == '_' || c.is_alphanumeric()
+}
+
+/// Valid digit counts for a Makepad color literal: `#f`, `#2A`, `#091`, `#0000`, `#344054`
+/// and `#34405480`.
+pub fn is_valid_color_len(len: usize) -> bool {
+  matches!(len, 1 | 2 | 3 | 4 | 6 | 8)
+}
+
+/// A lexer for the body of a `live_design!` block.
+pub struct Lexer<'a> {
+  source: &'a str,
+  pos: usize,
+  pub errors: Vec<SyntaxError>,
+}
+
+impl<'a> Lexer<'a> {
+  /// Creates a lexer reading `source` from byte offset `start`. Spans are absolute offsets
+  /// into `source`.
+  pub fn new(source: &'a str, start: usize) -> Self {
+    Lexer {
+      source,
+      pos: start,
+      errors: vec![],
+    }
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.source[self.pos..].chars().next()
+  }
+
+  fn peek_nth(&self, n: usize) -> Option<char> {
+    self.source[self.pos..].chars().nth(n)
+  }
+
+  fn bump(&mut self) -> Option<char> {
+    let c = self.peek()?;
+    self.pos += c.len_utf8();
+    Some(c)
+  }
+
+  fn eat_while(&mut self, predicate: impl Fn(char) -> bool) {
+    while self.peek().is_some_and(&predicate) {
+      self.bump();
+    }
+  }
+
+  fn error(&mut self, message: impl Into<String>, span: Span) {
+    self.errors.push(SyntaxError::new(message, span));
+  }
+
+  pub fn next_token(&mut self) -> Option<Token> {
+    self.eat_while(char::is_whitespace);
+    let start = self.pos;
+    let c = self.peek()?;
+
+    let kind = match c {
+      '/' if self.peek_nth(1) == Some('/') => {
+        self.eat_while(|c| c != '\n');
+        TokenKind::LineComment(self.source[start..self.pos].to_string())
+      }
+      '/' if self.peek_nth(1) == Some('*') => self.block_comment(start),
+      '"' => {
+        self.bump();
+        TokenKind::String(self.string_body(start, "unterminated string literal"))
+      }
+      '#' => self.color(start),
+      '<' | '{' if self.widget_ref_or_binding().is_some() => {
+        let (kind, end) = self.widget_ref_or_binding().unwrap_or((TokenKind::Unknown(c), start));
+        self.pos = end;
+        kind
+      }
+      c if c.is_ascii_digit() => self.number(start),
+      c if is_ident_start(c) => {
+        self.eat_while(is_ident_continue);
+        let ident = &self.source[start..self.pos];
+        if ident == "dep" {
+          if let Some(kind) = self.dep(start) {
+            return Some(Token {
+              kind,
+              span: Span::new(start, self.pos),
+            });
+          }
+        }
+        TokenKind::Ident(ident.to_string())
+      }
+      _ => match self.punct() {
+        Some(punct) => TokenKind::Punct(punct),
+        None => {
+          self.bump();
+          self.error(format!("unexpected character `{}`", c), Span::new(start, self.pos));
+          TokenKind::Unknown(c)
+        }
+      },
+    };
+
+    Some(Token {
+      kind,
+      span: Span::new(start, self.pos),
+    })
+  }
+
+  fn block_comment(&mut self, start: usize) -> TokenKind {
+    let end = skip_block_comment(self.source.as_bytes(), self.pos);
+    let closed = self.source[..end].ends_with("*/") && end >= start + 4;
+    self.pos = end;
+    if !closed {
+      self.error("unterminated block comment", Span::new(start, start + 2));
+    }
+    TokenKind::BlockComment(self.source[start..self.pos].to_string())
+  }
+
+  /// Reads a string body up to and including the closing quote, the opening quote having
+  /// already been consumed. An unterminated string stops at the end of the line.
+  fn string_body(&mut self, start: usize, message: &str) -> String {
+    let mut value = String::new();
+    loop {
+      match self.peek() {
+        Some('"') => {
+          self.bump();
+          return value;
+        }
+        Some('\\') => {
+          self.bump();
+          match self.bump() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('r') => value.push('\r'),
+            Some('0') => value.push('\0'),
+            Some(c) => value.push(c),
+            None => {}
+          }
+        }
+        Some('\n') | None => {
+          self.error(message, Span::new(start, self.pos));
+          return value;
+        }
+        Some(c) => {
+          self.bump();
+          value.push(c);
+        }
+      }
+    }
+  }
+
+  fn color(&mut self, start: usize) -> TokenKind {
+    self.bump();
+    let word_start = self.pos;
+    self.eat_while(is_ident_continue);
+    let mut digits = &self.source[word_start..self.pos];
+
+    // `#x` lets a color start with digits that would otherwise read as a float exponent,
+    // e.g. `#x1e3`.
+    if digits.len() > 1 && digits.starts_with('x') && digits[1..].chars().all(|c| c.is_ascii_hexdigit()) {
+      digits = &digits[1..];
+    }
+
+    let span = Span::new(start, self.pos);
+    if digits.is_empty() {
+      self.error("expected hex digits after `#`", span);
+    } else if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+      self.error(format!("malformed color literal `#{}`, expected hex digits", digits), span);
+    } else if !is_valid_color_len(digits.len()) {
+      self.error(
+        format!("malformed color literal `#{}`, expected 1, 2, 3, 4, 6 or 8 hex digits", digits),
+        span,
+      );
+    }
+    TokenKind::Color(digits.to_string())
+  }
+
+  fn widget_ref_or_binding(&self) -> Option<(TokenKind, usize)> {
+    match self.peek()? {
+      '<' => self.widget_ref().map(|(name, end)| (TokenKind::WidgetRef(name), end)),
+      '{' => self.rust_binding().map(|(name, end)| (TokenKind::RustBinding(name), end)),
+      _ => None,
+    }
+  }
+
+  /// Matches `<Name>` at the current position, returning the name and the end offset.
+  fn widget_ref(&self) -> Option<(String, usize)> {
+    let rest = &self.source[self.pos + 1..];
+    let trimmed = rest.trim_start_matches([' ', '\t']);
+    let name_start = self.pos + 1 + (rest.len() - trimmed.len());
+    let name_len = trimmed.find(|c| !is_ident_continue(c)).unwrap_or(trimmed.len());
+    let name = &trimmed[..name_len];
+    if !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+      return None;
+    }
+    let after = &self.source[name_start + name_len..];
+    let after_trimmed = after.trim_start_matches([' ', '\t']);
+    if !after_trimmed.starts_with('>') {
+      return None;
+    }
+    let end = name_start + name_len + (after.len() - after_trimmed.len()) + 1;
+    Some((name.to_string(), end))
+  }
+
+  /// Matches `{{Name}}` at the current position, returning the name and the end offset.
+  fn rust_binding(&self) -> Option<(String, usize)> {
+    let rest = self.source[self.pos..].strip_prefix("{{")?;
+    let trimmed = rest.trim_start();
+    let name_start = self.pos + 2 + (rest.len() - trimmed.len());
+    let name_len = trimmed.find(|c| !is_ident_continue(c)).unwrap_or(trimmed.len());
+    let name = &trimmed[..name_len];
+    if !name.starts_with(is_ident_start) {
+      return None;
+    }
+    let after = &self.source[name_start + name_len..];
+    let after_trimmed = after.trim_start();
+    if !after_trimmed.starts_with("}}") {
+      return None;
+    }
+    let end = name_start + name_len + (after.len() - after_trimmed.len()) + 2;
+    Some((name.to_string(), end))
+  }
+
+  fn number(&mut self, start: usize) -> TokenKind {
+    if self.peek() == Some('0') && matches!(self.peek_nth(1), Some('x') | Some('X')) {
+      self.bump();
+      self.bump();
+      let digits_start = self.pos;
+      self.eat_while(|c| c.is_ascii_hexdigit() || c == '_');
+      let digits = self.source[digits_start..self.pos].replace('_', "");
+      let value = u64::from_str_radix(&digits, 16).unwrap_or_else(|_| {
+        self.error("malformed hex number", Span::new(start, self.pos));
+        0
+      });
+      return TokenKind::Number { value: value as f64, unit: None };
+    }
+
+    self.eat_while(|c| c.is_ascii_digit() || c == '_');
+    if self.peek() == Some('.') && self.peek_nth(1).is_some_and(|c| c.is_ascii_digit()) {
+      self.bump();
+      self.eat_while(|c| c.is_ascii_digit() || c == '_');
+    } else if self.peek() == Some('.')
+      && !self.peek_nth(1).is_some_and(|c| c == '.' || is_ident_start(c))
+    {
+      // `1.` is a valid float literal.
+      self.bump();
+    }
+    if matches!(self.peek(), Some('e') | Some('E')) {
+      let has_exponent = match self.peek_nth(1) {
+        Some('+') | Some('-') => self.peek_nth(2).is_some_and(|c| c.is_ascii_digit()),
+        Some(c) => c.is_ascii_digit(),
+        None => false,
+      };
+      if has_exponent {
+        self.bump();
+        if matches!(self.peek(), Some('+') | Some('-')) {
+          self.bump();
+        }
+        self.eat_while(|c| c.is_ascii_digit());
+      }
+    }
+    let number_end = self.pos;
+    let value = self.source[start..number_end]
+      .replace('_', "")
+      .parse::<f64>()
+      .unwrap_or_else(|_| {
+        self.error("malformed number", Span::new(start, number_end));
+        0.0
+      });
+
+    let unit = if self.peek().is_some_and(is_ident_start) {
+      self.eat_while(is_ident_continue);
+      Some(self.source[number_end..self.pos].to_string())
+    } else {
+      None
+    };
+
+    TokenKind::Number { value, unit }
+  }
+
+  /// Lexes the rest of `dep("...")` after the `dep` ident. Returns `None` and leaves the
+  /// position untouched when `dep` isn't followed by `(`.
+  fn dep(&mut self, start: usize) -> Option<TokenKind> {
+    let ident_end = self.pos;
+    self.eat_while(char::is_whitespace);
+    if self.peek() != Some('(') {
+      self.pos = ident_end;
+      return None;
+    }
+    self.bump();
+    self.eat_while(char::is_whitespace);
+    if self.peek() != Some('"') {
+      self.error("expected a string in `dep(...)`", Span::new(start, self.pos));
+      return Some(TokenKind::Dep(String::new()));
+    }
+    self.bump();
+    let path = self.string_body(start, "unterminated `dep(` string");
+    let path_end = self.pos;
+    self.eat_while(char::is_whitespace);
+    if self.peek() == Some(')') {
+      self.bump();
+    } else {
+      self.pos = path_end;
+      self.error("expected `)` to close `dep(`", Span::new(start, path_end));
+    }
+    Some(TokenKind::Dep(path))
+  }
+
+  fn punct(&mut self) -> Option<Punct> {
+    let c = self.peek()?;
+    let next = self.peek_nth(1);
+    let (punct, len) = match (c, next) {
+      (':', Some(':')) => (Punct::PathSep, 2),
+      ('-', Some('>')) => (Punct::Arrow, 2),
+      ('=', Some('=')) => (Punct::EqEq, 2),
+      ('!', Some('=')) => (Punct::NotEq, 2),
+      ('<', Some('=')) => (Punct::LtEq, 2),
+      ('>', Some('=')) => (Punct::GtEq, 2),
+      ('+', Some('=')) => (Punct::PlusEq, 2),
+      ('-', Some('=')) => (Punct::MinusEq, 2),
+      ('*', Some('=')) => (Punct::StarEq, 2),
+      ('/', Some('=')) => (Punct::SlashEq, 2),
+      ('&', Some('&')) => (Punct::AndAnd, 2),
+      ('|', Some('|')) => (Punct::OrOr, 2),
+      ('{', _) => (Punct::LBrace, 1),
+      ('}', _) => (Punct::RBrace, 1),
+      ('(', _) => (Punct::LParen, 1),
+      (')', _) => (Punct::RParen, 1),
+      ('[', _) => (Punct::LBracket, 1),
+      (']', _) => (Punct::RBracket, 1),
+      ('<', _) => (Punct::Lt, 1),
+      ('>', _) => (Punct::Gt, 1),
+      (':', _) => (Punct::Colon, 1),
+      (';', _) => (Punct::Semi, 1),
+      (',', _) => (Punct::Comma, 1),
+      ('.', _) => (Punct::Dot, 1),
+      ('=', _) => (Punct::Eq, 1),
+      ('+', _) => (Punct::Plus, 1),
+      ('-', _) => (Punct::Minus, 1),
+      ('*', _) => (Punct::Star, 1),
+      ('/', _) => (Punct::Slash, 1),
+      ('%', _) => (Punct::Percent, 1),
+      ('!', _) => (Punct::Bang, 1),
+      ('&', _) => (Punct::Amp, 1),
+      ('|', _) => (Punct::Pipe, 1),
+      ('^', _) => (Punct::Caret, 1),
+      ('?', _) => (Punct::Question, 1),
+      _ => return None,
+    };
+    self.pos += len;
+    Some(punct)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn kinds(source: &str) -> Vec<TokenKind> {
+    let (tokens, errors) = tokenize(source);
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+    tokens.into_iter().map(|token| token.kind).collect()
+  }
+
+  fn ident(name: &str) -> TokenKind {
+    TokenKind::Ident(name.to_string())
+  }
+
+  #[test]
+  fn test_lex_widget_definition() {
+    let kinds = kinds("pub AppTab = <RadioButton> { width: Fit, height: 20.5px }");
+    assert_eq!(kinds, vec![
+      ident("pub"),
+      ident("AppTab"),
+      TokenKind::Punct(Punct::Eq),
+      TokenKind::WidgetRef("RadioButton".to_string()),
+      TokenKind::Punct(Punct::LBrace),
+      ident("width"),
+      TokenKind::Punct(Punct::Colon),
+      ident("Fit"),
+      TokenKind::Punct(Punct::Comma),
+      ident("height"),
+      TokenKind::Punct(Punct::Colon),
+      TokenKind::Number { value: 20.5, unit: Some("px".to_string()) },
+      TokenKind::Punct(Punct::RBrace),
+    ]);
+  }
+
+  #[test]
+  fn test_lex_literals_and_bindings() {
+    let kinds = kinds(r#"App = {{App}} { c: #344054, d: #x1e3, i: dep("crate://self/a.svg") } // end"#);
+    assert_eq!(kinds[2], TokenKind::RustBinding("App".to_string()));
+    assert_eq!(kinds[6], TokenKind::Color("344054".to_string()));
+    assert_eq!(kinds[10], TokenKind::Color("1e3".to_string()));
+    assert_eq!(kinds[14], TokenKind::Dep("crate://self/a.svg".to_string()));
+    assert_eq!(kinds[16], TokenKind::LineComment("// end".to_string()));
+  }
+
+  #[test]
+  fn test_lex_shader_fn_body() {
+    let kinds = kinds(
+      "fn pixel(self) -> vec4 { let a = self.pos * 2.0; if a.x <= 1e-3 { return #0000; } }",
+    );
+    assert!(kinds.contains(&TokenKind::Punct(Punct::Arrow)));
+    assert!(kinds.contains(&TokenKind::Punct(Punct::LtEq)));
+    assert!(kinds.contains(&TokenKind::Number { value: 1e-3, unit: None }));
+    assert!(kinds.contains(&TokenKind::Color("0000".to_string())));
+    assert!(!kinds.iter().any(|kind| matches!(kind, TokenKind::WidgetRef(_))));
+  }
+
+  #[test]
+  fn test_lex_reports_errors() {
+    let (_, errors) = tokenize("a: #12345, b: #12g4, c: dep(\"crate://self/x.svg\n d: \"open");
+    let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages, vec![
+      "malformed color literal `#12345`, expected 1, 2, 3, 4, 6 or 8 hex digits",
+      "malformed color literal `#12g4`, expected hex digits",
+      "unterminated `dep(` string",
+      "expected `)` to close `dep(`",
+      "unterminated string literal",
+    ]);
+  }
+
+  #[test]
+  fn test_find_live_design_blocks() {
+    let source = r##"
+      use makepad_widgets::*;
+      // live_design! { Commented = <View> {} }
+      const S: &str = "live_design! { InString = <View> {} }";
+      const R: &str = r#"live_design!{ InRawString = <View> {} }"#;
+      fn lifetime<'a>(c: char) -> bool { c == '{' }
+      live_design! {
+        App = {{App}} { ui: <Window> { body = { <View> {} } } }
+      }
+      makepad_widgets::live_design!(Second = <View> {});
+    "##;
+
+    let blocks = lex_live_design_blocks(source);
+    assert_eq!(blocks.len(), 2);
+    assert!(blocks.iter().all(|block| block.errors.is_empty()));
+
+    assert_eq!(blocks[0].tokens[0].kind, ident("App"));
+    assert!(blocks[0].span.text(source).starts_with("live_design! {"));
+    assert!(blocks[0].span.text(source).ends_with('}'));
+    assert_eq!(blocks[0].tokens.last().map(|t| &t.kind), Some(&TokenKind::Punct(Punct::RBrace)));
+
+    assert_eq!(blocks[1].tokens[0].kind, ident("Second"));
+    assert_eq!(blocks[1].body_span.text(source), "Second = <View> {}");
+
+    let app = &blocks[0].tokens[2];
+    assert_eq!(app.name_span(source).text(source), "App");
+  }
+
+  #[test]
+  fn test_unclosed_live_design_block() {
+    let blocks = lex_live_design_blocks("live_design! { App = <View> { ");
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].errors.len(), 1);
+    assert!(blocks[0].errors[0].message.starts_with("unclosed `live_design!` block"));
+  }
+}
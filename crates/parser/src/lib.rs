@@ -1,2 +1,13 @@
 mod token;
-mod token_map;
+pub mod token_map;
+pub mod lexer;
+pub mod parser;
+pub mod span;
+pub mod color;
+
+pub use token::*;
+pub use lexer::{lex_live_design_blocks, tokenize, LexedBlock, Punct, Token, TokenKind};
+pub use parser::{parse_document, LiveDesignBlock, ParsedDocument};
+pub use token_map::{ReferenceIdent, TokenAstNode, TokenIdent, TokenMap};
+pub use span::{LineIndex, Span, SyntaxError};
+pub use color::Rgba;
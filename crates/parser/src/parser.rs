@@ -0,0 +1,871 @@
+use crate::{
+  lexer::{lex_live_design_blocks, Punct, Token, TokenKind},
+  span::{Span, SyntaxError},
+  token::*,
+};
+
+/// A parsed `live_design!` invocation.
+#[derive(Debug, Clone)]
+pub struct LiveDesignBlock {
+  pub span: Span,
+  pub body_span: Span,
+  pub nodes: Vec<LiveDSLASTNode>,
+  pub references: Vec<Reference>,
+  /// All tokens of the body, comments included.
+  pub tokens: Vec<Token>,
+}
+
+impl LiveDesignBlock {
+  pub fn imports(&self) -> impl Iterator<Item = &ImportNode> {
+    self.nodes.iter().filter_map(|node| match node {
+      LiveDSLASTNode::Import(import) => Some(import),
+      _ => None,
+    })
+  }
+
+  pub fn constants(&self) -> impl Iterator<Item = &ConstantNode> {
+    self.nodes.iter().filter_map(|node| match node {
+      LiveDSLASTNode::Constant(constant) => Some(constant),
+      _ => None,
+    })
+  }
+
+  pub fn widgets(&self) -> impl Iterator<Item = &WidgetNode> {
+    self.nodes.iter().filter_map(|node| match node {
+      LiveDSLASTNode::Widget(widget) => Some(widget),
+      _ => None,
+    })
+  }
+}
+
+/// Every `live_design!` block of a Rust file, along with their syntax errors.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedDocument {
+  pub blocks: Vec<LiveDesignBlock>,
+  pub errors: Vec<SyntaxError>,
+}
+
+impl ParsedDocument {
+  pub fn imports(&self) -> impl Iterator<Item = &ImportNode> {
+    self.blocks.iter().flat_map(LiveDesignBlock::imports)
+  }
+
+  pub fn constants(&self) -> impl Iterator<Item = &ConstantNode> {
+    self.blocks.iter().flat_map(LiveDesignBlock::constants)
+  }
+
+  pub fn widgets(&self) -> impl Iterator<Item = &WidgetNode> {
+    self.blocks.iter().flat_map(LiveDesignBlock::widgets)
+  }
+
+  pub fn references(&self) -> impl Iterator<Item = &Reference> {
+    self.blocks.iter().flat_map(|block| block.references.iter())
+  }
+
+  /// The block containing the byte `offset`, if any.
+  pub fn block_at(&self, offset: usize) -> Option<&LiveDesignBlock> {
+    self.blocks.iter().find(|block| block.body_span.contains(offset))
+  }
+}
+
+/// Parses every `live_design!` block in a Rust source file.
+pub fn parse_document(source: &str) -> ParsedDocument {
+  let mut document = ParsedDocument::default();
+  for lexed in lex_live_design_blocks(source) {
+    let mut parser = Parser::new(source, &lexed.tokens, lexed.body_span.end);
+    let nodes = parser.parse_items();
+
+    document.errors.extend(lexed.errors);
+    document.errors.extend(parser.errors);
+    document.blocks.push(LiveDesignBlock {
+      span: lexed.span,
+      body_span: lexed.body_span,
+      nodes,
+      references: parser.references,
+      tokens: lexed.tokens,
+    });
+  }
+  document
+}
+
+/// Shader declaration keywords that may prefix a property, e.g. `instance hover: float`.
+pub const SHADER_DECL_KEYWORDS: &[&str] = &["instance", "uniform", "varying", "texture", "const"];
+
+struct Parser<'a> {
+  source: &'a str,
+  tokens: Vec<&'a Token>,
+  pos: usize,
+  /// Offset used for errors at the end of the block.
+  end: usize,
+  errors: Vec<SyntaxError>,
+  references: Vec<Reference>,
+}
+
+impl<'a> Parser<'a> {
+  fn new(source: &'a str, tokens: &'a [Token], end: usize) -> Self {
+    Parser {
+      source,
+      tokens: tokens.iter().filter(|token| !token.is_trivia()).collect(),
+      pos: 0,
+      end,
+      errors: vec![],
+      references: vec![],
+    }
+  }
+
+  fn peek(&self) -> Option<&'a Token> {
+    self.tokens.get(self.pos).copied()
+  }
+
+  fn peek_nth(&self, n: usize) -> Option<&'a Token> {
+    self.tokens.get(self.pos + n).copied()
+  }
+
+  fn bump(&mut self) -> Option<&'a Token> {
+    let token = self.peek()?;
+    self.pos += 1;
+    Some(token)
+  }
+
+  fn at_punct(&self, punct: Punct) -> bool {
+    self.peek().is_some_and(|token| token.is_punct(punct))
+  }
+
+  fn eat_punct(&mut self, punct: Punct) -> Option<&'a Token> {
+    if self.at_punct(punct) {
+      self.bump()
+    } else {
+      None
+    }
+  }
+
+  /// The end of the last consumed token.
+  fn prev_end(&self) -> usize {
+    self
+      .pos
+      .checked_sub(1)
+      .and_then(|pos| self.tokens.get(pos))
+      .map_or(self.end, |token| token.span.end)
+  }
+
+  /// The span of the current token, or an empty span at the end of the block.
+  fn current_span(&self) -> Span {
+    self.peek().map_or(Span::new(self.end, self.end), |token| token.span)
+  }
+
+  fn error(&mut self, message: impl Into<String>, span: Span) {
+    self.errors.push(SyntaxError::new(message, span));
+  }
+
+  fn describe(&self, token: Option<&Token>) -> String {
+    match token {
+      Some(token) => format!("`{}`", token.span.text(self.source)),
+      None => "end of block".to_string(),
+    }
+  }
+
+  fn reference(&mut self, token: &Token, kind: ReferenceKind) {
+    if let TokenKind::WidgetRef(name) | TokenKind::RustBinding(name) | TokenKind::Ident(name) = &token.kind {
+      self.references.push(Reference {
+        name: name.clone(),
+        kind,
+        span: token.name_span(self.source),
+      });
+    }
+  }
+
+  /// Skips to the next token that could start an item at the current nesting level, without
+  /// consuming a closing brace.
+  fn recover(&mut self) {
+    let mut depth = 0usize;
+    while let Some(token) = self.peek() {
+      match &token.kind {
+        TokenKind::Punct(Punct::LBrace | Punct::LParen | Punct::LBracket) => depth += 1,
+        TokenKind::Punct(Punct::RBrace | Punct::RParen | Punct::RBracket) => {
+          if depth == 0 {
+            return;
+          }
+          depth -= 1;
+        }
+        TokenKind::Punct(Punct::Comma | Punct::Semi) if depth == 0 => {
+          self.bump();
+          return;
+        }
+        TokenKind::Ident(_) | TokenKind::WidgetRef(_) if depth == 0 && self.pos > 0 => {
+          let prev = self.tokens[self.pos - 1];
+          if self.at_member_start() || self.source[prev.span.end..token.span.start].contains('\n') {
+            return;
+          }
+        }
+        _ => {}
+      }
+      self.bump();
+    }
+  }
+
+  fn parse_items(&mut self) -> Vec<LiveDSLASTNode> {
+    let mut nodes = vec![];
+    while let Some(token) = self.peek() {
+      if token.is_punct(Punct::Comma) || token.is_punct(Punct::Semi) {
+        self.bump();
+        continue;
+      }
+      if token.is_ident("use") {
+        nodes.push(LiveDSLASTNode::Import(self.parse_import()));
+        continue;
+      }
+      if token.is_ident("link") && self.peek_nth(1).and_then(Token::ident).is_some() {
+        // `link widgets;` declares the link this block belongs to.
+        self.bump();
+        self.bump();
+        continue;
+      }
+
+      let start = token.span.start;
+      let is_pub = token.is_ident("pub");
+      if is_pub {
+        self.bump();
+      }
+
+      match (self.peek(), self.peek_nth(1)) {
+        (Some(name @ Token { kind: TokenKind::Ident(_), .. }), Some(eq)) if eq.is_punct(Punct::Eq) => {
+          self.bump();
+          self.bump();
+          nodes.push(self.parse_definition(name, is_pub, start));
+        }
+        (token, _) => {
+          let message = format!("expected an item, found {}", self.describe(token));
+          self.error(message, self.current_span());
+          self.bump();
+          self.recover();
+        }
+      }
+    }
+    nodes
+  }
+
+  fn parse_import(&mut self) -> ImportNode {
+    let start = self.bump().map_or(self.end, |token| token.span.start);
+    let mut segments = vec![];
+    let mut is_glob = false;
+
+    loop {
+      match self.peek() {
+        Some(token @ Token { kind: TokenKind::Ident(name), .. }) => {
+          segments.push(PathSegment {
+            name: name.clone(),
+            span: token.span,
+          });
+          self.bump();
+        }
+        Some(token) if token.is_punct(Punct::Star) => {
+          is_glob = true;
+          self.bump();
+          break;
+        }
+        token => {
+          let message = format!("expected a path segment, found {}", self.describe(token));
+          self.error(message, self.current_span());
+          break;
+        }
+      }
+      if self.eat_punct(Punct::PathSep).is_none() {
+        break;
+      }
+    }
+
+    if self.eat_punct(Punct::Semi).is_none() {
+      self.error("expected `;` after `use` path", Span::new(self.prev_end(), self.prev_end()));
+      if !self.peek().is_some_and(|token| token.is_ident("use") || token.is_punct(Punct::RBrace)) {
+        self.recover();
+      }
+    }
+
+    ImportNode {
+      segments,
+      is_glob,
+      span: Span::new(start, self.prev_end()),
+    }
+  }
+
+  /// Parses the value of a top-level `Name = ...` item.
+  fn parse_definition(&mut self, name: &Token, is_pub: bool, start: usize) -> LiveDSLASTNode {
+    let name_text = name.ident().unwrap_or_default().to_string();
+    let value_start = self.current_span();
+
+    if let Some(mut widget) = self.parse_widget_value() {
+      widget.name = name_text;
+      widget.name_span = Some(name.span);
+      widget.is_pub = is_pub;
+      widget.span = Span::new(start, widget.span.end);
+      return LiveDSLASTNode::Widget(widget);
+    }
+
+    let value = self.parse_expression("=");
+    let value_span = Span::new(value_start.start, self.prev_end().max(value_start.start));
+    LiveDSLASTNode::Constant(ConstantNode {
+      name: name_text,
+      value,
+      is_pub,
+      name_span: name.span,
+      value_span,
+      span: Span::new(start, self.prev_end()),
+    })
+  }
+
+  /// Parses `<Type> { ... }`, `{{Type}} { ... }`, `{ ... }` or `Type { ... }` if the next
+  /// tokens start one of those.
+  fn parse_widget_value(&mut self) -> Option<WidgetNode> {
+    let token = self.peek()?;
+    let (kind, widget_type, type_span) = match &token.kind {
+      TokenKind::WidgetRef(name) => {
+        self.reference(token, ReferenceKind::Widget);
+        (WidgetKind::Instance, name.clone(), Some(token.name_span(self.source)))
+      }
+      TokenKind::RustBinding(name) => {
+        self.reference(token, ReferenceKind::RustType);
+        (WidgetKind::RustBinding, name.clone(), Some(token.name_span(self.source)))
+      }
+      TokenKind::Punct(Punct::LBrace) => (WidgetKind::Object, String::new(), None),
+      TokenKind::Ident(name) if self.peek_nth(1).is_some_and(|next| next.is_punct(Punct::LBrace)) => {
+        (WidgetKind::Object, name.clone(), Some(token.span))
+      }
+      _ => return None,
+    };
+    if kind != WidgetKind::Object || !widget_type.is_empty() {
+      self.bump();
+    }
+
+    let start = token.span.start;
+    let mut widget = WidgetNode {
+      name: String::new(),
+      widget_type,
+      kind,
+      is_pub: false,
+      properties: vec![],
+      children: vec![],
+      shader_fns: vec![],
+      name_span: None,
+      type_span,
+      body_span: None,
+      span: Span::new(start, start),
+    };
+
+    match self.eat_punct(Punct::LBrace) {
+      Some(open) => self.parse_body(&mut widget, open),
+      None => {
+        let message = format!("expected `{{` after {}", self.describe(Some(token)));
+        self.error(message, token.span);
+      }
+    }
+    widget.span = Span::new(start, self.prev_end());
+    Some(widget)
+  }
+
+  /// Parses the items of a `{ ... }` body, the opening brace having been consumed.
+  fn parse_body(&mut self, widget: &mut WidgetNode, open: &Token) {
+    loop {
+      let Some(token) = self.peek() else {
+        self.error("unclosed `{`, expected `}`", open.span);
+        widget.body_span = Some(Span::new(open.span.start, self.end));
+        return;
+      };
+
+      match &token.kind {
+        TokenKind::Punct(Punct::RBrace) => {
+          self.bump();
+          widget.body_span = Some(open.span.join(token.span));
+          return;
+        }
+        TokenKind::Punct(Punct::Comma | Punct::Semi) => {
+          self.bump();
+        }
+        TokenKind::WidgetRef(_) | TokenKind::RustBinding(_) => {
+          if let Some(child) = self.parse_widget_value() {
+            widget.children.push(child);
+          }
+        }
+        TokenKind::Ident(ident) if ident == "fn" => {
+          if let Some(shader_fn) = self.parse_shader_fn() {
+            widget.shader_fns.push(shader_fn);
+          }
+        }
+        TokenKind::Ident(_) => self.parse_member(widget),
+        _ => {
+          let message = format!("unexpected {}", self.describe(Some(token)));
+          self.error(message, token.span);
+          self.bump();
+          self.recover();
+        }
+      }
+    }
+  }
+
+  /// Parses `name: value`, `name = <Widget> { ... }` or `instance name: value`.
+  fn parse_member(&mut self, widget: &mut WidgetNode) {
+    let Some(first) = self.bump() else { return };
+    let start = first.span.start;
+
+    let mut modifier = None;
+    let mut name = first;
+    if let Some(ident) = first.ident() {
+      let next_is_name = self.peek().and_then(Token::ident).is_some();
+      if SHADER_DECL_KEYWORDS.contains(&ident) && next_is_name {
+        modifier = Some(ident.to_string());
+        name = self.bump().unwrap_or(first);
+      }
+    }
+    let name_text = name.ident().unwrap_or_default().to_string();
+
+    match self.peek() {
+      Some(token) if token.is_punct(Punct::Colon) => {
+        self.bump();
+        let value_start = self.current_span().start;
+        let value = match self.parse_widget_value() {
+          Some(mut object) => {
+            object.name = name_text.clone();
+            object.name_span = Some(name.span);
+            Expression::Object(Box::new(object))
+          }
+          None => self.parse_expression(&format!("{}:", name_text)),
+        };
+        let value_end = self.prev_end().max(value_start);
+        widget.properties.push(PropertyNode {
+          name: name_text,
+          modifier,
+          value,
+          name_span: name.span,
+          value_span: Span::new(value_start.min(value_end), value_end),
+          span: Span::new(start, self.prev_end()),
+        });
+      }
+      Some(token) if token.is_punct(Punct::Eq) => {
+        self.bump();
+        match self.parse_widget_value() {
+          Some(mut child) => {
+            child.name = name_text;
+            child.name_span = Some(name.span);
+            child.span = Span::new(start, child.span.end);
+            widget.children.push(child);
+          }
+          None => {
+            let message = format!("expected a widget after `{} =`", name_text);
+            self.error(message, self.current_span());
+            self.recover();
+          }
+        }
+      }
+      token => {
+        let message = format!(
+          "expected `:` or `=` after `{}`, found {}",
+          name_text,
+          self.describe(token)
+        );
+        let found = token.map_or(self.end, |t| t.span.start).max(name.span.end);
+        self.error(message, Span::new(name.span.end, found));
+        // Keep half-typed properties around so completion can still see them.
+        widget.properties.push(PropertyNode {
+          name: name_text,
+          modifier,
+          value: Expression::Missing,
+          name_span: name.span,
+          value_span: Span::new(name.span.end, name.span.end),
+          span: Span::new(start, name.span.end),
+        });
+        if !token.is_some_and(|t| t.is_punct(Punct::RBrace)) {
+          self.recover();
+        }
+      }
+    }
+  }
+
+  /// Whether the next tokens start a new member rather than a value, e.g. `height:`.
+  fn at_member_start(&self) -> bool {
+    match (self.peek(), self.peek_nth(1)) {
+      (Some(token), Some(next)) if token.ident().is_some() => {
+        next.is_punct(Punct::Colon) || next.is_punct(Punct::Eq) || token.is_ident("fn")
+      }
+      _ => false,
+    }
+  }
+
+  fn parse_expression(&mut self, after: &str) -> Expression {
+    if self.at_member_start() {
+      self.missing_value(after);
+      return Expression::Missing;
+    }
+    let Some(token) = self.peek() else {
+      self.missing_value(after);
+      return Expression::Missing;
+    };
+
+    match &token.kind {
+      TokenKind::Color(color) => {
+        self.bump();
+        Expression::Color(color.clone())
+      }
+      TokenKind::Number { value, .. } => {
+        self.bump();
+        Expression::Number(*value)
+      }
+      TokenKind::Punct(Punct::Minus) => match self.peek_nth(1).map(|t| &t.kind) {
+        Some(TokenKind::Number { value, .. }) => {
+          self.bump();
+          self.bump();
+          Expression::Number(-value)
+        }
+        _ => {
+          self.bump();
+          self.parse_expression(after)
+        }
+      },
+      TokenKind::String(value) => {
+        self.bump();
+        Expression::String(value.clone())
+      }
+      TokenKind::Dep(path) => {
+        self.bump();
+        Expression::Dep(path.clone())
+      }
+      TokenKind::Ident(ident) if ident == "true" || ident == "false" => {
+        self.bump();
+        Expression::Boolean(ident == "true")
+      }
+      TokenKind::Ident(ident) => {
+        self.bump();
+        if let Some(open) = self.eat_punct(Punct::LParen) {
+          let args = self.parse_arguments(open);
+          Expression::Call(ident.clone(), args)
+        } else {
+          let mut path = ident.clone();
+          while self.at_punct(Punct::PathSep) && self.peek_nth(1).and_then(Token::ident).is_some() {
+            self.bump();
+            path.push_str("::");
+            path.push_str(self.bump().and_then(Token::ident).unwrap_or_default());
+          }
+          Expression::Ident(path)
+        }
+      }
+      TokenKind::Punct(Punct::LParen) => self.parse_parenthesized(),
+      TokenKind::Punct(Punct::LBracket) => {
+        let open = self.bump().unwrap_or(token);
+        let mut items = vec![];
+        loop {
+          if self.eat_punct(Punct::RBracket).is_some() {
+            break;
+          }
+          if self.peek().is_none() {
+            self.error("unclosed `[`, expected `]`", open.span);
+            break;
+          }
+          if self.eat_punct(Punct::Comma).is_some() {
+            continue;
+          }
+          let before = self.pos;
+          match self.parse_widget_value() {
+            Some(object) => items.push(Expression::Object(Box::new(object))),
+            None => items.push(self.parse_expression("[")),
+          }
+          if self.pos == before {
+            self.bump();
+          }
+        }
+        Expression::Array(items)
+      }
+      _ => {
+        self.missing_value(after);
+        if !token.is_punct(Punct::RBrace) {
+          self.bump();
+        }
+        Expression::Missing
+      }
+    }
+  }
+
+  fn missing_value(&mut self, after: &str) {
+    let at = self.prev_end();
+    self.error(format!("expected a value after `{}`", after), Span::new(at, at));
+  }
+
+  fn parse_arguments(&mut self, open: &Token) -> Vec<Expression> {
+    let mut args = vec![];
+    loop {
+      if self.eat_punct(Punct::RParen).is_some() {
+        return args;
+      }
+      match self.peek() {
+        None => {
+          self.error("unclosed `(`, expected `)`", open.span);
+          return args;
+        }
+        Some(token) if token.is_punct(Punct::RBrace) => {
+          self.error("unclosed `(`, expected `)`", open.span);
+          return args;
+        }
+        Some(token) if token.is_punct(Punct::Comma) => {
+          self.bump();
+        }
+        Some(_) => {
+          let before = self.pos;
+          args.push(self.parse_expression("("));
+          if self.pos == before {
+            self.bump();
+          }
+        }
+      }
+    }
+  }
+
+  /// Parses `(NAME)` as a constant reference, and anything else in parentheses as a
+  /// computed value whose identifiers are recorded as constant references.
+  fn parse_parenthesized(&mut self) -> Expression {
+    let Some(open) = self.bump() else {
+      return Expression::Missing;
+    };
+    let start = self.pos;
+    let mut depth = 0usize;
+    loop {
+      match self.peek() {
+        None => {
+          self.error("unclosed `(`, expected `)`", open.span);
+          break;
+        }
+        Some(token) if token.is_punct(Punct::LParen) => depth += 1,
+        Some(token) if token.is_punct(Punct::RParen) => {
+          if depth == 0 {
+            break;
+          }
+          depth -= 1;
+        }
+        Some(token) if token.is_punct(Punct::RBrace) && depth == 0 => {
+          self.error("unclosed `(`, expected `)`", open.span);
+          break;
+        }
+        Some(_) => {}
+      }
+      self.bump();
+    }
+    let inner: Vec<&Token> = self.tokens[start..self.pos].to_vec();
+    let close_end = self.eat_punct(Punct::RParen).map_or(self.prev_end(), |t| t.span.end);
+
+    for (i, token) in inner.iter().enumerate() {
+      let is_call = inner.get(i + 1).is_some_and(|next| next.is_punct(Punct::LParen));
+      let is_field = i > 0 && inner[i - 1].is_punct(Punct::Dot);
+      if token.ident().is_some() && !is_call && !is_field {
+        self.reference(token, ReferenceKind::Constant);
+      }
+    }
+
+    match inner.as_slice() {
+      [] => {
+        self.error("expected a constant inside `()`", open.span.join(Span::new(close_end, close_end)));
+        Expression::Missing
+      }
+      [Token { kind: TokenKind::Ident(name), .. }] => Expression::Constant(name.clone()),
+      _ => Expression::Computed(self.source[open.span.start..close_end].to_string()),
+    }
+  }
+
+  /// Parses `fn name(params) -> type { ... }`. The body is shader code and is only scanned
+  /// for `(CONSTANT)` references.
+  fn parse_shader_fn(&mut self) -> Option<ShaderFnNode> {
+    let fn_token = self.bump()?;
+    let Some(name @ Token { kind: TokenKind::Ident(name_text), .. }) = self.peek() else {
+      let message = format!("expected a function name, found {}", self.describe(self.peek()));
+      self.error(message, self.current_span());
+      self.recover();
+      return None;
+    };
+    self.bump();
+
+    let mut params = String::new();
+    match self.eat_punct(Punct::LParen) {
+      Some(open) => {
+        let params_start = open.span.end;
+        while self.peek().is_some_and(|t| !t.is_punct(Punct::RParen) && !t.is_punct(Punct::LBrace)) {
+          self.bump();
+        }
+        let params_end = self.current_span().start;
+        params = self.source[params_start..params_end].trim().to_string();
+        if self.eat_punct(Punct::RParen).is_none() {
+          self.error("unclosed `(`, expected `)`", open.span);
+        }
+      }
+      None => {
+        let message = format!("expected `(` after `fn {}`", name_text);
+        self.error(message, self.current_span());
+      }
+    }
+
+    let mut return_type = None;
+    if self.eat_punct(Punct::Arrow).is_some() {
+      match self.peek().and_then(Token::ident) {
+        Some(ty) => {
+          return_type = Some(ty.to_string());
+          self.bump();
+        }
+        None => {
+          let at = self.prev_end();
+          self.error("expected a return type after `->`", Span::new(at, at));
+        }
+      }
+    }
+
+    let Some(open) = self.eat_punct(Punct::LBrace) else {
+      let message = format!("expected `{{` to start the body of `fn {}`", name_text);
+      self.error(message, self.current_span());
+      return None;
+    };
+
+    let body_start = self.pos;
+    let mut depth = 0usize;
+    let close = loop {
+      match self.peek() {
+        None => {
+          self.error("unclosed `{`, expected `}`", open.span);
+          break None;
+        }
+        Some(token) if token.is_punct(Punct::LBrace) => depth += 1,
+        Some(token) if token.is_punct(Punct::RBrace) => {
+          if depth == 0 {
+            break self.bump();
+          }
+          depth -= 1;
+        }
+        Some(_) => {}
+      }
+      self.bump();
+    };
+
+    let body_end = close.map_or(self.end, |t| t.span.end);
+    let body_end_pos = self.pos.saturating_sub(close.is_some() as usize);
+    let body: Vec<&Token> = self.tokens[body_start..body_end_pos].to_vec();
+    for window in body.windows(3) {
+      if let [open, ident, close] = window {
+        let is_constant = ident.ident().is_some_and(is_constant_name);
+        if open.is_punct(Punct::LParen) && close.is_punct(Punct::RParen) && is_constant {
+          self.reference(ident, ReferenceKind::Constant);
+        }
+      }
+    }
+
+    Some(ShaderFnNode {
+      name: name_text.clone(),
+      params,
+      return_type,
+      name_span: name.span,
+      body_span: Span::new(open.span.start, body_end),
+      span: Span::new(fn_token.span.start, body_end),
+    })
+  }
+}
+
+/// Constants are conventionally `SCREAMING_SNAKE_CASE`, e.g. `APP_TAB_COLOR`.
+pub fn is_constant_name(name: &str) -> bool {
+  name.chars().any(|c| c.is_ascii_uppercase())
+    && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_definitions() {
+    let source = r#"live_design! {
+      use link::widgets::*;
+      use crate::shared::popup_list::PopupList;
+
+      ICON_CHAT = dep("crate://self/resources/icons/chat.svg")
+      APP_TAB_COLOR = #344054
+      SPACE = (THEME_SPACE * 2.0)
+
+      pub Card = <View> {
+        width: Fill, height: 20
+        draw_bg: {
+          instance hover: float
+          fn pixel(self) -> vec4 { return mix(#0000, (APP_TAB_COLOR), self.hover); }
+        }
+        title = <Label> { text: "Hi" }
+        <PopupList> {}
+      }
+    }"#;
+
+    let document = parse_document(source);
+    assert!(document.errors.is_empty(), "{:?}", document.errors);
+
+    let imports: Vec<String> = document.imports().map(ImportNode::path).collect();
+    assert_eq!(imports, vec!["link::widgets::*", "crate::shared::popup_list::PopupList"]);
+
+    let constants: Vec<&ConstantNode> = document.constants().collect();
+    let icon = "crate://self/resources/icons/chat.svg";
+    assert!(matches!(&constants[0].value, Expression::Dep(path) if path == icon));
+    assert!(matches!(&constants[1].value, Expression::Color(color) if color == "344054"));
+    assert!(matches!(&constants[2].value, Expression::Computed(text) if text == "(THEME_SPACE * 2.0)"));
+    assert_eq!(constants[1].name_span.text(source), "APP_TAB_COLOR");
+
+    let card = document.widgets().next().unwrap();
+    assert!(card.is_pub);
+    assert_eq!((card.name.as_str(), card.widget_type.as_str()), ("Card", "View"));
+    assert_eq!(card.type_span.unwrap().text(source), "View");
+    assert!(matches!(card.property("height").unwrap().value, Expression::Number(n) if n == 20.0));
+
+    let draw_bg = card.property("draw_bg").unwrap().value.as_object().unwrap();
+    assert_eq!(draw_bg.property("hover").unwrap().modifier.as_deref(), Some("instance"));
+    assert_eq!(draw_bg.shader_fns[0].name, "pixel");
+    assert_eq!(draw_bg.shader_fns[0].return_type.as_deref(), Some("vec4"));
+
+    assert_eq!(card.children[0].name, "title");
+    assert_eq!(card.children[1].widget_type, "PopupList");
+
+    let references: Vec<(&str, ReferenceKind)> = document
+      .references()
+      .map(|reference| (reference.name.as_str(), reference.kind))
+      .collect();
+    assert_eq!(references, vec![
+      ("THEME_SPACE", ReferenceKind::Constant),
+      ("View", ReferenceKind::Widget),
+      ("APP_TAB_COLOR", ReferenceKind::Constant),
+      ("Label", ReferenceKind::Widget),
+      ("PopupList", ReferenceKind::Widget),
+    ]);
+  }
+
+  #[test]
+  fn test_recover_from_half_typed_widget() {
+    let source = "live_design! { Screen = <View> { width: } }";
+    let document = parse_document(source);
+    let screen = document.widgets().next().unwrap();
+    assert_eq!(screen.widget_type, "View");
+    assert!(matches!(screen.property("width").unwrap().value, Expression::Missing));
+    assert_eq!(document.errors[0].message, "expected a value after `width:`");
+
+    let source = "live_design! { Screen = <View> {
+      width:
+      height: Fill
+      body = <View> { flow: Down, 42 }
+    } }";
+    let document = parse_document(source);
+    let screen = document.widgets().next().unwrap();
+    assert!(matches!(&screen.property("height").unwrap().value, Expression::Ident(ident) if ident == "Fill"));
+    assert!(matches!(&screen.child("body").unwrap().property("flow").unwrap().value, Expression::Ident(_)));
+    let messages: Vec<&str> = document.errors.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages, vec!["expected a value after `width:`", "unexpected `42`"]);
+  }
+
+  #[test]
+  fn test_recover_from_unclosed_body() {
+    let document = parse_document("live_design! { A = <View> { width: ");
+    assert_eq!(document.widgets().count(), 1);
+    let messages: Vec<&str> = document.errors.iter().map(|e| e.message.as_str()).collect();
+    assert!(messages.contains(&"unclosed `{`, expected `}`"));
+    assert!(messages.contains(&"expected a value after `width:`"));
+
+    let document = parse_document("live_design! { A = <View> { width: Fill ) height: Fit } B = #fff }");
+    let a = document.widgets().next().unwrap();
+    assert!(a.property("height").is_some());
+    assert_eq!(document.constants().count(), 1);
+    let messages: Vec<&str> = document.errors.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages, vec!["unexpected `)`"]);
+  }
+}
@@ -0,0 +1,135 @@
+use lsp_types::{Position, Range};
+
+/// A byte range into the source file a token or node was read from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Span {
+  pub start: usize,
+  pub end: usize,
+}
+
+impl Span {
+  pub fn new(start: usize, end: usize) -> Self {
+    Span { start, end }
+  }
+
+  pub fn len(&self) -> usize {
+    self.end - self.start
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.start == self.end
+  }
+
+  pub fn contains(&self, offset: usize) -> bool {
+    self.start <= offset && offset <= self.end
+  }
+
+  /// Returns the smallest span covering both `self` and `other`.
+  pub fn join(&self, other: Span) -> Span {
+    Span {
+      start: self.start.min(other.start),
+      end: self.end.max(other.end),
+    }
+  }
+
+  pub fn text<'a>(&self, source: &'a str) -> &'a str {
+    &source[self.start..self.end]
+  }
+}
+
+/// An error found while lexing or parsing a `live_design!` block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+  pub message: String,
+  pub span: Span,
+}
+
+impl SyntaxError {
+  pub fn new(message: impl Into<String>, span: Span) -> Self {
+    SyntaxError {
+      message: message.into(),
+      span,
+    }
+  }
+}
+
+/// Converts between byte offsets and LSP positions, which count UTF-16 code units.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+  source: &'a str,
+  line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+  pub fn new(source: &'a str) -> Self {
+    let mut line_starts = vec![0];
+    line_starts.extend(source.match_indices('\n').map(|(i, _)| i + 1));
+    LineIndex {
+      source,
+      line_starts,
+    }
+  }
+
+  pub fn position(&self, offset: usize) -> Position {
+    let offset = offset.min(self.source.len());
+    let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+    let line_start = self.line_starts[line];
+    let character = self.source[line_start..offset]
+      .chars()
+      .map(char::len_utf16)
+      .sum::<usize>();
+    Position::new(line as u32, character as u32)
+  }
+
+  pub fn offset(&self, position: Position) -> usize {
+    let Some(&line_start) = self.line_starts.get(position.line as usize) else {
+      return self.source.len();
+    };
+    let line_end = self
+      .line_starts
+      .get(position.line as usize + 1)
+      .copied()
+      .unwrap_or(self.source.len());
+
+    let mut utf16_count = 0;
+    for (i, c) in self.source[line_start..line_end].char_indices() {
+      if utf16_count >= position.character as usize || c == '\n' {
+        return line_start + i;
+      }
+      utf16_count += c.len_utf16();
+    }
+    line_end
+  }
+
+  pub fn range(&self, span: Span) -> Range {
+    Range::new(self.position(span.start), self.position(span.end))
+  }
+
+  pub fn span(&self, range: Range) -> Span {
+    Span::new(self.offset(range.start), self.offset(range.end))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_line_index_round_trip() {
+    let source = "a = 1\n// é😀\nb = #fff";
+    let index = LineIndex::new(source);
+
+    let b = source.find('b').unwrap();
+    assert_eq!(index.position(b), Position::new(2, 0));
+    assert_eq!(index.offset(Position::new(2, 0)), b);
+
+    // `é` is one UTF-16 code unit and `😀` is two.
+    let end_of_comment = source.find("\nb").unwrap();
+    assert_eq!(index.position(end_of_comment), Position::new(1, 6));
+    assert_eq!(index.offset(Position::new(1, 6)), end_of_comment);
+    assert_eq!(index.offset(Position::new(1, 99)), end_of_comment);
+
+    let span = Span::new(b, source.len());
+    assert_eq!(index.span(index.range(span)), span);
+  }
+}
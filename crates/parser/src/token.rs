@@ -1,27 +1,159 @@
-use std::collections::HashMap;
+use crate::span::Span;
 
+#[derive(Debug, Clone)]
 pub enum LiveDSLASTNode {
   Import(ImportNode),
   Constant(ConstantNode),
   Widget(WidgetNode),
 }
 
+impl LiveDSLASTNode {
+  pub fn span(&self) -> Span {
+    match self {
+      LiveDSLASTNode::Import(node) => node.span,
+      LiveDSLASTNode::Constant(node) => node.span,
+      LiveDSLASTNode::Widget(node) => node.span,
+    }
+  }
+}
+
+/// `use crate::home::home_screen::HomeScreen;` or `use link::widgets::*;`
 #[derive(Debug, Clone)]
-pub struct ImportNode {}
+pub struct ImportNode {
+  pub segments: Vec<PathSegment>,
+  pub is_glob: bool,
+  pub span: Span,
+}
 
+impl ImportNode {
+  /// The path as written, e.g. `crate::home::home_screen::HomeScreen` or `link::widgets::*`.
+  pub fn path(&self) -> String {
+    let mut path = self
+      .segments
+      .iter()
+      .map(|segment| segment.name.as_str())
+      .collect::<Vec<_>>()
+      .join("::");
+    if self.is_glob {
+      path.push_str("::*");
+    }
+    path
+  }
+
+  /// The name brought into scope by a non-glob import, e.g. `HomeScreen`.
+  pub fn imported_name(&self) -> Option<&PathSegment> {
+    if self.is_glob {
+      None
+    } else {
+      self.segments.last()
+    }
+  }
+
+  /// The module segments, i.e. the path without the imported name.
+  pub fn module_segments(&self) -> &[PathSegment] {
+    if self.is_glob || self.segments.is_empty() {
+      &self.segments
+    } else {
+      &self.segments[..self.segments.len() - 1]
+    }
+  }
+}
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathSegment {
+  pub name: String,
+  pub span: Span,
+}
+
+/// `APP_TAB_COLOR = #344054`
 #[derive(Debug, Clone)]
 pub struct ConstantNode {
   pub name: String,
-  pub value: String,
+  pub value: Expression,
+  pub is_pub: bool,
+  pub name_span: Span,
+  pub value_span: Span,
+  pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidgetKind {
+  /// `<View> { ... }`
+  Instance,
+  /// `{{App}} { ... }`
+  RustBinding,
+  /// `{ ... }` or `Right { ... }`
+  Object,
 }
 
+/// A widget definition (`AppTab = <RadioButton> {}`), a named or anonymous instance
+/// (`home_screen = <HomeScreen> {}`, `<View> {}`) or a nested object (`draw_text: {}`).
 #[derive(Debug, Clone)]
 pub struct WidgetNode {
+  /// Empty for anonymous instances such as `<View> {}`.
   pub name: String,
+  /// The type inside `<...>` or `{{...}}`, empty for plain `{ ... }` objects.
   pub widget_type: String,
-  pub properties: HashMap<String, Expression>,
+  pub kind: WidgetKind,
+  pub is_pub: bool,
+  pub properties: Vec<PropertyNode>,
   pub children: Vec<WidgetNode>,
+  pub shader_fns: Vec<ShaderFnNode>,
+  pub name_span: Option<Span>,
+  pub type_span: Option<Span>,
+  /// The braces of the body, `None` when the body is missing.
+  pub body_span: Option<Span>,
+  pub span: Span,
+}
+
+impl WidgetNode {
+  pub fn property(&self, name: &str) -> Option<&PropertyNode> {
+    self.properties.iter().find(|property| property.name == name)
+  }
+
+  pub fn child(&self, name: &str) -> Option<&WidgetNode> {
+    self.children.iter().find(|child| child.name == name)
+  }
+
+  /// Widget nodes nested directly in this one: children and object-valued properties.
+  pub fn nested(&self) -> impl Iterator<Item = &WidgetNode> {
+    self
+      .properties
+      .iter()
+      .filter_map(|property| property.value.as_object())
+      .chain(self.children.iter())
+  }
+
+  /// Calls `f` on this node and every node nested in it, depth first.
+  pub fn walk<'a>(&'a self, f: &mut impl FnMut(&'a WidgetNode)) {
+    f(self);
+    for node in self.nested() {
+      node.walk(f);
+    }
+  }
+}
+
+/// `width: Fill` or `instance hover: float`
+#[derive(Debug, Clone)]
+pub struct PropertyNode {
+  pub name: String,
+  /// The shader declaration keyword, e.g. `instance`, `uniform` or `varying`.
+  pub modifier: Option<String>,
+  pub value: Expression,
+  pub name_span: Span,
+  pub value_span: Span,
+  pub span: Span,
+}
+
+/// `fn pixel(self) -> vec4 { ... }`
+#[derive(Debug, Clone)]
+pub struct ShaderFnNode {
+  pub name: String,
+  pub params: String,
+  pub return_type: Option<String>,
+  pub name_span: Span,
+  pub body_span: Span,
+  pub span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -30,14 +162,53 @@ pub enum Expression {
   Number(f64),
   Boolean(bool),
   String(String),
+  /// `dep("crate://self/resources/icons/chat.svg")`
+  Dep(String),
+  /// `Fill`, `Down`, or a shader type such as `float`.
+  Ident(String),
+  /// `(APP_TAB_COLOR)`
+  Constant(String),
+  /// A parenthesized computation such as `(THEME_SPACE * 2.0)`, kept as written.
+  Computed(String),
+  /// `vec2(1280, 800)`
+  Call(String, Vec<Expression>),
+  Array(Vec<Expression>),
+  Object(Box<WidgetNode>),
+  /// Recovered from a syntax error, e.g. the value in a half-typed `width: `.
+  Missing,
 }
 
+impl Expression {
+  pub fn as_object(&self) -> Option<&WidgetNode> {
+    match self {
+      Expression::Object(node) => Some(node),
+      _ => None,
+    }
+  }
+}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceKind {
+  /// `<HomeScreen>`
+  Widget,
+  /// `(APP_TAB_COLOR)`
+  Constant,
+  /// `{{App}}`
+  RustType,
+}
 
+/// A use of a name defined elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+  pub name: String,
+  pub kind: ReferenceKind,
+  pub span: Span,
+}
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::parse_document;
 
   #[test]
   fn test_parsed_token() {
@@ -180,6 +351,42 @@ mod tests {
       }
       "#;
 
-      println!("{:?}", raw_source_code);
+      let document = parse_document(raw_source_code);
+      assert_eq!(document.blocks.len(), 1);
+      assert!(document.errors.is_empty(), "{:?}", document.errors);
+      assert_eq!(document.imports().count(), 9);
+      assert_eq!(document.constants().count(), 7);
+
+      let widgets: Vec<&WidgetNode> = document.widgets().collect();
+      let names: Vec<(&str, &str)> = widgets
+        .iter()
+        .map(|widget| (widget.name.as_str(), widget.widget_type.as_str()))
+        .collect();
+      assert_eq!(names, vec![("AppTab", "RadioButton"), ("App", "App")]);
+
+      let app_tab = widgets[0];
+      let draw_radio = app_tab.property("draw_radio").unwrap().value.as_object().unwrap();
+      assert_eq!(draw_radio.shader_fns[0].name, "pixel");
+      let draw_icon = app_tab.property("draw_icon").unwrap().value.as_object().unwrap();
+      assert_eq!(draw_icon.property("color_selected").unwrap().modifier.as_deref(), Some("instance"));
+
+      let app = widgets[1];
+      assert_eq!(app.kind, WidgetKind::RustBinding);
+      let window = app.property("ui").unwrap().value.as_object().unwrap();
+      assert_eq!(window.widget_type, "Window");
+      let overlay = &window.child("body").unwrap().children[0];
+      assert_eq!(overlay.widget_type, "View");
+      let children: Vec<(&str, &str)> = overlay
+        .children
+        .iter()
+        .map(|child| (child.name.as_str(), child.widget_type.as_str()))
+        .collect();
+      assert_eq!(children, vec![
+        ("home_screen_view", "View"),
+        ("login_screen_view", "View"),
+        ("popup", "PopupNotification"),
+        ("verification_modal", "Modal"),
+      ]);
+      assert_eq!(overlay.child("home_screen_view").unwrap().children[0].widget_type, "HomeScreen");
   }
 }
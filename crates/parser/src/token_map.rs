@@ -1,29 +1,311 @@
-// use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-// use dashmap::DashMap;
-// use lsp_types::Range;
+use dashmap::DashMap;
+use lsp_types::{Position, Range};
 
-// #[derive(Debug, Default)]
-// pub struct TokenMap(DashMap<TokenIdent, Token>);
+use crate::{
+  parser::{parse_document, ParsedDocument},
+  span::LineIndex,
+  token::{ConstantNode, ImportNode, LiveDSLASTNode, ReferenceKind, WidgetNode},
+};
 
-// impl std::ops::Deref for TokenMap {
-//   type Target = DashMap<TokenIdent, Token>;
+/// The symbol index of a session: every name declared in the `live_design!` blocks of its files,
+/// and every `<Widget>`, `(CONSTANT)` and `{{Type}}` use of a name.
+#[derive(Debug, Default)]
+pub struct TokenMap {
+  tokens: DashMap<TokenIdent, Token>,
+  references: DashMap<PathBuf, Vec<ReferenceIdent>>,
+}
 
-//   fn deref(&self) -> &Self::Target {
-//     &self.0
-//   }
-// }
+impl std::ops::Deref for TokenMap {
+  type Target = DashMap<TokenIdent, Token>;
 
+  fn deref(&self) -> &Self::Target {
+    &self.tokens
+  }
+}
 
-// #[derive(Debug, Default, Clone, PartialEq, Eq)]
-// pub struct TokenIdent {
-//   pub name: String,
-//   pub range: Range,
-//   pub path: Option<PathBuf>,
-// }
+impl TokenMap {
+  pub fn new() -> Self {
+    TokenMap::default()
+  }
 
+  /// Replaces the tokens of the file at `path` with the ones declared in `source`.
+  pub fn update_file(&self, path: &Path, source: &str) -> ParsedDocument {
+    let document = parse_document(source);
+    self.remove_file(path);
+    self.insert_document(path, source, &document);
+    document
+  }
 
-// #[derive(Debug, Clone)]
-// pub struct Token {
-//   pub ast_node: TokenAstNode,
-// }
+  pub fn remove_file(&self, path: &Path) {
+    self.retain(|ident, _| ident.path.as_deref() != Some(path));
+    self.references.remove(path);
+  }
+
+  fn insert_document(&self, path: &Path, source: &str, document: &ParsedDocument) {
+    let line_index = LineIndex::new(source);
+    let ident = |name: &str, span| TokenIdent {
+      name: name.to_string(),
+      range: line_index.range(span),
+      path: Some(path.to_path_buf()),
+    };
+
+    let references = document
+      .references()
+      .map(|reference| ReferenceIdent {
+        name: reference.name.clone(),
+        kind: reference.kind,
+        range: line_index.range(reference.span),
+      })
+      .collect();
+    self.references.insert(path.to_path_buf(), references);
+
+    for node in document.blocks.iter().flat_map(|block| block.nodes.iter()) {
+      match node {
+        LiveDSLASTNode::Import(import) => {
+          let (name, span) = match import.imported_name() {
+            Some(segment) => (segment.name.clone(), segment.span),
+            None => (import.path(), import.span),
+          };
+          self.insert(ident(&name, span), Token::new(TokenAstNode::Import(import.clone())));
+        }
+        LiveDSLASTNode::Constant(constant) => {
+          self.insert(
+            ident(&constant.name, constant.name_span),
+            Token::new(TokenAstNode::Constant(constant.clone())),
+          );
+        }
+        LiveDSLASTNode::Widget(widget) => {
+          if let Some(name_span) = widget.name_span {
+            self.insert(
+              ident(&widget.name, name_span),
+              Token::new(TokenAstNode::WidgetDefinition(widget.clone())),
+            );
+          }
+          let mut instances = vec![];
+          collect_named_instances(widget, &mut instances);
+          for instance in instances {
+            if let Some(name_span) = instance.name_span {
+              self.insert(
+                ident(&instance.name, name_span),
+                Token::new(TokenAstNode::NamedInstance(instance.clone())),
+              );
+            }
+          }
+        }
+      }
+    }
+  }
+
+  /// All tokens declared in the file at `path`.
+  pub fn tokens_for_file(&self, path: &Path) -> Vec<(TokenIdent, Token)> {
+    self
+      .iter()
+      .filter(|entry| entry.key().path.as_deref() == Some(path))
+      .map(|entry| (entry.key().clone(), entry.value().clone()))
+      .collect()
+  }
+
+  /// All tokens declared with `name`, across every file of the session.
+  pub fn tokens_for_name(&self, name: &str) -> Vec<(TokenIdent, Token)> {
+    self
+      .iter()
+      .filter(|entry| entry.key().name == name)
+      .map(|entry| (entry.key().clone(), entry.value().clone()))
+      .collect()
+  }
+
+  /// Every use of `name` across the files of the session.
+  pub fn references_for_name(&self, name: &str) -> Vec<(PathBuf, ReferenceIdent)> {
+    self
+      .references
+      .iter()
+      .flat_map(|entry| {
+        entry
+          .value()
+          .iter()
+          .filter(|reference| reference.name == name)
+          .map(|reference| (entry.key().clone(), reference.clone()))
+          .collect::<Vec<_>>()
+      })
+      .collect()
+  }
+
+  /// The token declared at `position` in the file at `path`.
+  pub fn token_at_position(&self, path: &Path, position: Position) -> Option<(TokenIdent, Token)> {
+    self
+      .iter()
+      .find(|entry| {
+        let ident = entry.key();
+        ident.path.as_deref() == Some(path) && range_contains(&ident.range, position)
+      })
+      .map(|entry| (entry.key().clone(), entry.value().clone()))
+  }
+}
+
+/// Named instances are the `name = <Widget> {}` children of a widget tree, as opposed to
+/// object-valued properties such as `draw_bg: {}`.
+fn collect_named_instances<'a>(widget: &'a WidgetNode, instances: &mut Vec<&'a WidgetNode>) {
+  for object in widget.properties.iter().filter_map(|property| property.value.as_object()) {
+    collect_named_instances(object, instances);
+  }
+  for child in &widget.children {
+    if !child.name.is_empty() {
+      instances.push(child);
+    }
+    collect_named_instances(child, instances);
+  }
+}
+
+pub fn range_contains(range: &Range, position: Position) -> bool {
+  range.start <= position && position <= range.end
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TokenIdent {
+  pub name: String,
+  pub range: Range,
+  pub path: Option<PathBuf>,
+}
+
+impl std::hash::Hash for TokenIdent {
+  fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    self.name.hash(state);
+    self.range.start.line.hash(state);
+    self.range.start.character.hash(state);
+    self.range.end.line.hash(state);
+    self.range.end.character.hash(state);
+    self.path.hash(state);
+  }
+}
+
+/// A use of a name in a file, see [`crate::Reference`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceIdent {
+  pub name: String,
+  pub kind: ReferenceKind,
+  pub range: Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+  pub ast_node: TokenAstNode,
+}
+
+impl Token {
+  pub fn new(ast_node: TokenAstNode) -> Self {
+    Token { ast_node }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub enum TokenAstNode {
+  /// `use crate::home::home_screen::HomeScreen;`
+  Import(ImportNode),
+  /// `APP_TAB_COLOR = #344054`
+  Constant(ConstantNode),
+  /// `AppTab = <RadioButton> { ... }`
+  WidgetDefinition(WidgetNode),
+  /// `home_screen = <HomeScreen> { ... }`
+  NamedInstance(WidgetNode),
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const SOURCE: &str = r#"
+    live_design! {
+      use link::widgets::*;
+      use crate::home::home_screen::HomeScreen;
+
+      APP_TAB_COLOR = #344054
+
+      AppTab = <RadioButton> {
+        draw_text: { color: (APP_TAB_COLOR) }
+      }
+
+      App = {{App}} {
+        ui: <Window> {
+          body = {
+            <View> {
+              home_screen = <HomeScreen> {}
+            }
+          }
+        }
+      }
+    }
+  "#;
+
+  fn names(map: &TokenMap, path: &Path) -> Vec<String> {
+    let mut names: Vec<String> = map
+      .tokens_for_file(path)
+      .into_iter()
+      .map(|(ident, token)| {
+        let kind = match token.ast_node {
+          TokenAstNode::Import(_) => "import",
+          TokenAstNode::Constant(_) => "constant",
+          TokenAstNode::WidgetDefinition(_) => "widget",
+          TokenAstNode::NamedInstance(_) => "instance",
+        };
+        format!("{} {}", kind, ident.name)
+      })
+      .collect();
+    names.sort();
+    names
+  }
+
+  #[test]
+  fn test_token_map_records_declarations() {
+    let map = TokenMap::new();
+    let path = PathBuf::from("/project/src/app.rs");
+    map.update_file(&path, SOURCE);
+
+    assert_eq!(names(&map, &path), vec![
+      "constant APP_TAB_COLOR",
+      "import HomeScreen",
+      "import link::widgets::*",
+      "instance body",
+      "instance home_screen",
+      "widget App",
+      "widget AppTab",
+    ]);
+
+    let (ident, _) = map.tokens_for_name("AppTab").pop().unwrap();
+    assert_eq!(ident.range.start, Position::new(7, 6));
+    let (found, _) = map.token_at_position(&path, Position::new(7, 8)).unwrap();
+    assert_eq!(found, ident);
+  }
+
+  #[test]
+  fn test_token_map_updates_incrementally() {
+    let map = TokenMap::new();
+    let app = PathBuf::from("/project/src/app.rs");
+    let other = PathBuf::from("/project/src/other.rs");
+    map.update_file(&app, SOURCE);
+    map.update_file(&other, "live_design! { OTHER_COLOR = #fff }");
+
+    map.update_file(&app, "live_design! { APP_TAB_COLOR = #000 }");
+    assert_eq!(names(&map, &app), vec!["constant APP_TAB_COLOR"]);
+    assert_eq!(names(&map, &other), vec!["constant OTHER_COLOR"]);
+
+    map.remove_file(&other);
+    assert!(map.tokens_for_file(&other).is_empty());
+  }
+
+  #[test]
+  fn test_token_map_records_references() {
+    let map = TokenMap::new();
+    let path = PathBuf::from("/project/src/app.rs");
+    map.update_file(&path, SOURCE);
+
+    let references = map.references_for_name("APP_TAB_COLOR");
+    assert_eq!(references.len(), 1);
+    assert_eq!(references[0].1.kind, ReferenceKind::Constant);
+    assert_eq!(references[0].1.range.start, Position::new(8, 29));
+
+    map.update_file(&path, "live_design! { A = <View> {} }");
+    assert!(map.references_for_name("APP_TAB_COLOR").is_empty());
+  }
+}
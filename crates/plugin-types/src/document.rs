@@ -0,0 +1,127 @@
+use lsp_types::{Position, Url};
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{parse_document, LineIndex, ParsedDocument};
+
+/// The languages of a Rust file with `live_design!` blocks. Requests are only forwarded to the
+/// plugins that handle the region they are made in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DocumentRegion {
+  /// Outside of `live_design!` blocks.
+  Rust,
+  /// The live DSL of a `live_design!` block.
+  LiveDesign,
+  /// The body of a shader `fn`, e.g. `fn pixel(self) -> vec4 { ... }`.
+  Shader,
+}
+
+/// A read-only snapshot of a document handed to plugins, with its `live_design!` blocks
+/// already parsed. Plugins never see the document store itself, so they can't change it.
+#[derive(Debug, Clone)]
+pub struct PluginDocument {
+  uri: Url,
+  text: String,
+  ast: ParsedDocument,
+}
+
+impl PluginDocument {
+  pub fn new(uri: Url, text: impl Into<String>) -> Self {
+    let text = text.into();
+    let ast = parse_document(&text);
+    PluginDocument { uri, text, ast }
+  }
+
+  /// Takes a snapshot of the document at `uri` in `documents`.
+  pub fn from_documents(documents: &Documents, uri: &Url) -> Result<Self, DocumentError> {
+    let document = documents.get_text_document(uri)?;
+    Ok(PluginDocument::new(uri.clone(), document.get_text()))
+  }
+
+  pub fn uri(&self) -> &Url {
+    &self.uri
+  }
+
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+
+  /// The `live_design!` blocks of the document.
+  pub fn ast(&self) -> &ParsedDocument {
+    &self.ast
+  }
+
+  pub fn line_index(&self) -> LineIndex<'_> {
+    LineIndex::new(&self.text)
+  }
+
+  pub fn offset(&self, position: Position) -> usize {
+    self.line_index().offset(position)
+  }
+
+  /// The line `line`, without its line break.
+  pub fn line(&self, line: u32) -> Option<&str> {
+    self.text.split('\n').nth(line as usize).map(|line| line.trim_end_matches('\r'))
+  }
+
+  pub fn region_at(&self, offset: usize) -> DocumentRegion {
+    if self.ast.block_at(offset).is_none() {
+      return DocumentRegion::Rust;
+    }
+    let mut in_shader_fn = false;
+    for widget in self.ast.widgets() {
+      widget.walk(&mut |node| {
+        let mut shader_fns = node.shader_fns.iter();
+        in_shader_fn |= shader_fns.any(|shader_fn| shader_fn.body_span.contains(offset));
+      });
+    }
+    match in_shader_fn {
+      true => DocumentRegion::Shader,
+      false => DocumentRegion::LiveDesign,
+    }
+  }
+
+  /// The text between the start of the line of `position` and `position`.
+  pub fn line_prefix(&self, position: Position) -> &str {
+    let offset = self.offset(position);
+    let line_start = self.text[..offset].rfind('\n').map_or(0, |index| index + 1);
+    &self.text[line_start..offset]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_plugin_document() {
+    let uri = Url::parse("file:///app/src/app.rs").unwrap();
+    let text = "live_design! {\r\n  use link::wid\r\n  App = {{App}} {}\r\n}\r\n";
+    let document = PluginDocument::new(uri, text);
+    assert_eq!(document.line(1), Some("  use link::wid"));
+    assert_eq!(document.line(9), None);
+    assert_eq!(document.line_prefix(Position::new(1, 12)), "  use link::");
+    let widgets: Vec<&str> = document.ast().widgets().map(|widget| widget.name.as_str()).collect();
+    assert_eq!(widgets, ["App"]);
+  }
+
+  #[test]
+  fn test_region_at() {
+    let uri = Url::parse("file:///app/src/app.rs").unwrap();
+    let text = [
+      "live_design! {",
+      "  Tab = <View> {",
+      "    draw_bg: {",
+      "      fn pixel(self) -> vec4 { return #f00; }",
+      "    }",
+      "  }",
+      "}",
+      "fn main() {}",
+    ]
+    .join("\n");
+    let document = PluginDocument::new(uri, text.as_str());
+    let region = |needle: &str| document.region_at(text.find(needle).unwrap());
+    assert_eq!(region("draw_bg"), DocumentRegion::LiveDesign);
+    assert_eq!(region("#f00"), DocumentRegion::Shader);
+    assert_eq!(region("main"), DocumentRegion::Rust);
+  }
+}
@@ -0,0 +1,22 @@
+mod document;
+mod plugin;
+
+pub use document::*;
+pub use plugin::*;
+
+pub use lsp_types::{
+  CodeAction, CodeActionKind, CodeActionOrCommand, CompletionItem, CompletionItemKind,
+  Diagnostic, DiagnosticSeverity, Documentation, Hover, HoverContents, InsertTextFormat,
+  Location, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, Position, Range,
+  SignatureHelp, SignatureInformation, TextEdit, Url, WorkspaceEdit,
+};
+
+/// The syntax tree of `live_design!` blocks, as plugins see it through
+/// [`PluginDocument::ast`].
+pub mod ast {
+  pub use makepad_analyzer_parser::{
+    ConstantNode, Expression, ImportNode, LineIndex, LiveDesignBlock, ParsedDocument,
+    PropertyNode, Reference, ReferenceKind, ShaderFnNode, Span, SyntaxError, WidgetKind,
+    WidgetNode,
+  };
+}
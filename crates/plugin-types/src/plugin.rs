@@ -0,0 +1,80 @@
+use lsp_types::{
+  CodeActionOrCommand, CompletionItem, Diagnostic, Hover, Location, Position, Range,
+};
+
+use crate::{DocumentRegion, PluginDocument};
+
+/// The version of the plugin API. It changes whenever a method of [`PluginCapability`] or
+/// [`MakepadAnalyzerPlugin`] changes, and plugins built against another version are not
+/// loaded.
+pub const PLUGIN_API_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginInfo {
+  pub name: String,
+  pub description: String,
+  pub version: String,
+}
+
+/// The requests a plugin answers. The server only advertises, and only forwards, these.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProvidedCapabilities {
+  /// Where the position-based requests are forwarded. Diagnostics are asked for whole
+  /// documents.
+  pub regions: Vec<DocumentRegion>,
+  pub completion: bool,
+  /// Characters that open completion besides typing a word, e.g. `:` for `link::`.
+  pub completion_trigger_characters: Vec<String>,
+  pub hover: bool,
+  pub diagnostics: bool,
+  pub definition: bool,
+  pub code_actions: bool,
+}
+
+/// The requests of a plugin. Every method has an empty default, so a plugin only implements
+/// what it declares in [`PluginCapability::provided`].
+pub trait PluginCapability: Send + Sync {
+  fn provided(&self) -> ProvidedCapabilities;
+
+  /// `trigger_char` is the character that opened completion, if not a word character.
+  fn handle_completion(
+    &self,
+    _document: &PluginDocument,
+    _position: Position,
+    _trigger_char: Option<&str>,
+  ) -> Vec<CompletionItem> {
+    vec![]
+  }
+
+  fn handle_hover(&self, _document: &PluginDocument, _position: Position) -> Option<Hover> {
+    None
+  }
+
+  fn handle_diagnostics(&self, _document: &PluginDocument) -> Vec<Diagnostic> {
+    vec![]
+  }
+
+  fn handle_definition(&self, _document: &PluginDocument, _position: Position) -> Vec<Location> {
+    vec![]
+  }
+
+  /// `diagnostics` are the ones the client sent for `range`.
+  fn handle_code_actions(
+    &self,
+    _document: &PluginDocument,
+    _range: Range,
+    _diagnostics: &[Diagnostic],
+  ) -> Vec<CodeActionOrCommand> {
+    vec![]
+  }
+}
+
+pub trait MakepadAnalyzerPlugin: Send + Sync {
+  fn plugin_info(&self) -> PluginInfo;
+
+  fn capabilities(&self) -> &dyn PluginCapability;
+
+  /// The [`PLUGIN_API_VERSION`] the plugin was written against. Plugins return the version
+  /// they target as a literal, so that an API change refuses them until they are updated.
+  fn api_version(&self) -> u32;
+}
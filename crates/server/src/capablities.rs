@@ -1,10 +1,65 @@
-use tower_lsp::lsp_types::{ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind};
+use makepad_analyzer_plugin_types::ProvidedCapabilities;
+use makepad_analyzer_session::capabilities::semantic_tokens;
+use tower_lsp::lsp_types::{
+  CodeActionKind, CodeActionOptions, CodeActionProviderCapability, ColorProviderCapability,
+  CompletionOptions, DocumentLinkOptions, FoldingRangeProviderCapability, HoverProviderCapability,
+  OneOf, RenameOptions, SemanticTokensFullOptions, SemanticTokensOptions,
+  SemanticTokensServerCapabilities, ServerCapabilities, SignatureHelpOptions,
+  TextDocumentSyncCapability, TextDocumentSyncKind,
+};
+
+/// The capabilities of the session together with the ones `plugins` provide.
+pub fn server_capabilities(plugins: &ProvidedCapabilities) -> ServerCapabilities {
+  let mut trigger_characters: Vec<String> =
+    ["<", "(", ":", "/", "\"", "."].iter().map(|c| c.to_string()).collect();
+  for character in &plugins.completion_trigger_characters {
+    if !trigger_characters.contains(character) {
+      trigger_characters.push(character.clone());
+    }
+  }
 
-pub fn server_capabilities() -> ServerCapabilities {
   ServerCapabilities {
     text_document_sync: Some(TextDocumentSyncCapability::Kind(
       TextDocumentSyncKind::INCREMENTAL,
     )),
+    completion_provider: Some(CompletionOptions {
+      trigger_characters: Some(trigger_characters),
+      ..CompletionOptions::default()
+    }),
+    definition_provider: Some(OneOf::Left(true)),
+    hover_provider: Some(HoverProviderCapability::Simple(true)),
+    references_provider: Some(OneOf::Left(true)),
+    document_highlight_provider: Some(OneOf::Left(true)),
+    color_provider: Some(ColorProviderCapability::Simple(true)),
+    document_symbol_provider: Some(OneOf::Left(true)),
+    document_link_provider: Some(DocumentLinkOptions {
+      resolve_provider: Some(false),
+      work_done_progress_options: Default::default(),
+    }),
+    folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+    document_formatting_provider: Some(OneOf::Left(true)),
+    document_range_formatting_provider: Some(OneOf::Left(true)),
+    signature_help_provider: Some(SignatureHelpOptions {
+      trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+      ..SignatureHelpOptions::default()
+    }),
+    inlay_hint_provider: Some(OneOf::Left(true)),
+    code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+      code_action_kinds: Some(vec![CodeActionKind::QUICKFIX, CodeActionKind::REFACTOR_EXTRACT]),
+      ..CodeActionOptions::default()
+    })),
+    workspace_symbol_provider: Some(OneOf::Left(true)),
+    rename_provider: Some(OneOf::Right(RenameOptions {
+      prepare_provider: Some(true),
+      work_done_progress_options: Default::default(),
+    })),
+    semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+      SemanticTokensOptions {
+        legend: semantic_tokens::legend(),
+        full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+        ..SemanticTokensOptions::default()
+      },
+    )),
     ..ServerCapabilities::default()
   }
 }
@@ -1,10 +1,13 @@
 use std::sync::Arc;
 
 use makepad_analyzer_core::config::Config;
-use makepad_analyzer_session::SessionManager;
+use makepad_analyzer_plugin_types::PluginDocument;
+use makepad_analyzer_session::{Session, SessionManager};
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
-use tower_lsp::Client;
+use tower_lsp::{lsp_types::Url, Client};
+
+use crate::plugins::PluginRegistry;
 
 const DEFAULT_SESSION_CACHE_SIZE: usize = 7;
 static SESSION_MANAGER: Lazy<Arc<SessionManager>> = Lazy::new(|| {
@@ -13,21 +16,23 @@ static SESSION_MANAGER: Lazy<Arc<SessionManager>> = Lazy::new(|| {
     .build()
 });
 
+#[derive(Clone)]
 pub struct ServerContext {
   pub(crate) client: Option<Client>,
   pub config: Arc<RwLock<Config>>,
 
   pub session_manager: &'static SessionManager,
+  pub plugins: Arc<PluginRegistry>,
 }
 
 impl Default for ServerContext {
   fn default() -> Self {
-    let context = ServerContext {
+    ServerContext {
       client: None,
       config: Arc::new(RwLock::new(Config::default())),
-      session_manager: &*SESSION_MANAGER
-    };
-    context
+      session_manager: &SESSION_MANAGER,
+      plugins: Arc::new(PluginRegistry::with_builtin_plugins()),
+    }
   }
 }
 
@@ -38,6 +43,46 @@ impl ServerContext {
       ..Default::default()
     }
   }
+
+  pub fn with_plugins(client: Client, plugins: PluginRegistry) -> ServerContext {
+    ServerContext {
+      client: Some(client),
+      config: Arc::new(RwLock::new(Config::default())),
+      session_manager: &SESSION_MANAGER,
+      plugins: Arc::new(plugins),
+    }
+  }
+
+  /// A snapshot for the plugins of the document at the temp `uri`, under the `workspace_uri`
+  /// the client knows it by.
+  pub(crate) fn plugin_document(
+    &self,
+    workspace_uri: &Url,
+    uri: &Url,
+  ) -> Option<Arc<PluginDocument>> {
+    let document = self.session_manager.documents.get_text_document(uri).ok()?;
+    Some(Arc::new(PluginDocument::new(workspace_uri.clone(), document.get_text())))
+  }
+
+  /// Publishes the diagnostics of the document at the temp `uri` against its `workspace_uri`,
+  /// so the client sees them on the file it actually opened.
+  pub(crate) async fn publish_diagnostics(&self, workspace_uri: &Url, uri: &Url, session: &Session) {
+    let Some(client) = &self.client else {
+      return;
+    };
+
+    match session.diagnostics(uri, &self.session_manager.documents) {
+      Ok(mut diagnostics) => {
+        if let Some(document) = self.plugin_document(workspace_uri, uri) {
+          diagnostics.extend(self.plugins.diagnostics(document).await);
+        }
+        client
+          .publish_diagnostics(workspace_uri.clone(), diagnostics, None)
+          .await;
+      }
+      Err(err) => tracing::error!("Failed to compute diagnostics: {}", err),
+    }
+  }
 }
 
 #[cfg(test)]
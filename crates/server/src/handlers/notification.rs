@@ -11,12 +11,24 @@ pub async fn handle_did_open_text_document(
   tracing::info!("Opened document: {:?}", params.text_document.uri.path());
 
   // Get the URI and session from the workspace.
-  let (uri, _session) = cx
+  let (uri, session) = cx
     .session_manager
     .uri_and_session_from_workspace(&params.text_document.uri)
     .await?;
 
   cx.session_manager.documents.handle_open_file(&uri).await;
+  session.update_token_map(&uri, &cx.session_manager.documents)?;
+  cx.publish_diagnostics(&params.text_document.uri, &uri, &session).await;
+
+  // Unknown crates are only reported once `cargo metadata` is done, so publish again then.
+  if !session.dependency_dirs_loaded() {
+    let cx = cx.clone();
+    let workspace_uri = params.text_document.uri;
+    tokio::spawn(async move {
+      session.wait_for_dependency_dirs().await;
+      cx.publish_diagnostics(&workspace_uri, &uri, &session).await;
+    });
+  }
   Ok(())
 }
 
@@ -26,11 +38,13 @@ pub async fn handle_did_change_text_document(
   params: DidChangeTextDocumentParams
 ) -> Result<(), MakepadAnalyzerError> {
   tracing::info!("Changed document: {:?}", params.text_document.uri);
-  let (uri, _session) = cx
+  let (uri, session) = cx
     .session_manager
     .uri_and_session_from_workspace(&params.text_document.uri)
     .await?;
   cx.session_manager.documents.write_changes_to_file(&uri, &params.content_changes).await?;
+  session.update_token_map(&uri, &cx.session_manager.documents)?;
+  cx.publish_diagnostics(&params.text_document.uri, &uri, &session).await;
   Ok(())
 }
 
@@ -40,10 +54,11 @@ pub async fn handle_did_save_text_document(
   params: DidSaveTextDocumentParams
 ) -> Result<(), MakepadAnalyzerError> {
   tracing::info!("Saved document: {:?}", params.text_document.uri);
-  let (_uri, session) = cx
+  let (uri, session) = cx
     .session_manager
     .uri_and_session_from_workspace(&params.text_document.uri)
     .await?;
   session.sync.resync()?;
+  cx.publish_diagnostics(&params.text_document.uri, &uri, &session).await;
   Ok(())
 }
@@ -1,10 +1,14 @@
-use makepad_analyzer_core::config::LSPClient;
+use makepad_analyzer_core::{config::LSPClient, errors::MakepadAnalyzerError};
 use makepad_analyzer_tracing::{tracing_subscriber, FmtSpan, StdioTracingWriter};
-use tower_lsp::lsp_types::{CompletionParams, CompletionResponse, InitializeParams, InitializeResult};
+use tower_lsp::lsp_types::{CodeActionParams, CodeActionResponse, ColorInformation, ColorPresentation, ColorPresentationParams, CompletionParams, CompletionResponse, DocumentColorParams, DocumentFormattingParams, DocumentHighlight, DocumentHighlightParams, DocumentLink, DocumentLinkParams, DocumentRangeFormattingParams, DocumentSymbolParams, DocumentSymbolResponse, FoldingRange, FoldingRangeParams, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverParams, InitializeParams, InitializeResult, InlayHint, InlayHintParams, Location, PrepareRenameResponse, Range, ReferenceParams, RenameParams, SemanticTokensDeltaParams, SemanticTokensFullDeltaResult, SemanticTokensParams, SemanticTokensResult, SignatureHelp, SignatureHelpParams, SymbolInformation, TextDocumentPositionParams, TextEdit, Url, WorkspaceEdit, WorkspaceSymbolParams};
 use tracing::level_filters::LevelFilter;
 
-use crate::{capablities, context::ServerContext};
-use tower_lsp::jsonrpc::Result;
+use crate::{
+  capablities,
+  context::ServerContext,
+  plugins::{merge_completion_items, merge_definitions, merge_hovers},
+};
+use tower_lsp::jsonrpc::{Error, Result};
 
 pub fn handle_initialize(
   cx: &ServerContext,
@@ -50,7 +54,7 @@ pub fn handle_initialize(
 
   Ok(InitializeResult {
     server_info: None,
-    capabilities: capablities::server_capabilities(),
+    capabilities: capablities::server_capabilities(&cx.plugins.provided()),
     ..InitializeResult::default()
   })
 }
@@ -59,11 +63,176 @@ pub async fn handle_completion(
   cx: &ServerContext,
   params: CompletionParams,
 ) -> Result<Option<CompletionResponse>> {
-  let trigger_char = params
-  .context
-  .as_ref()
-  .and_then(|ctx| ctx.trigger_character.as_deref())
-  .unwrap_or("");
+  let position = params.text_document_position.position;
+  let workspace_uri = &params.text_document_position.text_document.uri;
+  let trigger_char = params.context.and_then(|context| context.trigger_character);
+
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(workspace_uri)
+    .await
+  {
+    Ok((uri, session)) => {
+      let items = match session.completion_items(&uri, position, &cx.session_manager.documents) {
+        Ok(items) => items,
+        Err(err) => {
+          tracing::error!("{}", err.to_string());
+          None
+        }
+      };
+      let plugin_items = match cx.plugin_document(workspace_uri, &uri) {
+        Some(document) => cx.plugins.completion(document, position, trigger_char).await,
+        None => vec![],
+      };
+      Ok(merge_completion_items(items, plugin_items).map(CompletionResponse::Array))
+    }
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_goto_definition(
+  cx: &ServerContext,
+  params: GotoDefinitionParams,
+) -> Result<Option<GotoDefinitionResponse>> {
+  let position = params.text_document_position_params.position;
+  let workspace_uri = &params.text_document_position_params.text_document.uri;
+
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(workspace_uri)
+    .await
+  {
+    Ok((uri, session)) => {
+      let response = match session.goto_definition(&uri, position, &cx.session_manager.documents) {
+        Ok(response) => response,
+        Err(err) => {
+          tracing::error!("{}", err.to_string());
+          None
+        }
+      };
+      let plugin_locations = match cx.plugin_document(workspace_uri, &uri) {
+        Some(document) => cx.plugins.definition(document, position).await,
+        None => vec![],
+      };
+      Ok(merge_definitions(response, plugin_locations))
+    }
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_hover(
+  cx: &ServerContext,
+  params: HoverParams,
+) -> Result<Option<Hover>> {
+  let position = params.text_document_position_params.position;
+  let workspace_uri = &params.text_document_position_params.text_document.uri;
+
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(workspace_uri)
+    .await
+  {
+    Ok((uri, session)) => {
+      let hover = match session.hover(&uri, position, &cx.session_manager.documents) {
+        Ok(hover) => hover,
+        Err(err) => {
+          tracing::error!("{}", err.to_string());
+          None
+        }
+      };
+      let plugin_hovers = match cx.plugin_document(workspace_uri, &uri) {
+        Some(document) => cx.plugins.hover(document, position).await,
+        None => vec![],
+      };
+      Ok(merge_hovers(hover, plugin_hovers))
+    }
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_references(
+  cx: &ServerContext,
+  params: ReferenceParams,
+) -> Result<Option<Vec<Location>>> {
+  let position = params.text_document_position.position;
+  let include_declaration = params.context.include_declaration;
+
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(&params.text_document_position.text_document.uri)
+    .await
+  {
+    Ok((uri, session)) => match session.references(&uri, position, include_declaration, &cx.session_manager.documents) {
+      Ok(locations) => Ok(locations),
+      Err(err) => {
+        tracing::error!("{}", err.to_string());
+        Ok(None)
+      }
+    },
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_document_highlight(
+  cx: &ServerContext,
+  params: DocumentHighlightParams,
+) -> Result<Option<Vec<DocumentHighlight>>> {
+  let position = params.text_document_position_params.position;
+
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(&params.text_document_position_params.text_document.uri)
+    .await
+  {
+    Ok((uri, session)) => match session.document_highlights(&uri, position, &cx.session_manager.documents) {
+      Ok(highlights) => Ok(highlights),
+      Err(err) => {
+        tracing::error!("{}", err.to_string());
+        Ok(None)
+      }
+    },
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_prepare_rename(
+  cx: &ServerContext,
+  params: TextDocumentPositionParams,
+) -> Result<Option<PrepareRenameResponse>> {
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(&params.text_document.uri)
+    .await
+  {
+    Ok((uri, session)) => session
+      .prepare_rename(&uri, params.position, &cx.session_manager.documents)
+      .or_else(rename_error),
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_rename(
+  cx: &ServerContext,
+  params: RenameParams,
+) -> Result<Option<WorkspaceEdit>> {
   let position = params.text_document_position.position;
 
   match cx
@@ -71,12 +240,315 @@ pub async fn handle_completion(
     .uri_and_session_from_workspace(&params.text_document_position.text_document.uri)
     .await
   {
-    Ok((uri, session)) => Ok(session
-      .completion_items(&uri, position, trigger_char)
-      .map(CompletionResponse::Array)),
+    Ok((uri, session)) => session
+      .rename(&uri, position, &params.new_name, &cx.session_manager.documents)
+      .or_else(rename_error),
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+/// Refused renames are reported to the client, which shows the message to the user.
+fn rename_error<T>(err: MakepadAnalyzerError) -> Result<Option<T>> {
+  match err {
+    MakepadAnalyzerError::RenameError(err) => Err(Error::invalid_params(err.to_string())),
+    err => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_document_symbol(
+  cx: &ServerContext,
+  params: DocumentSymbolParams,
+) -> Result<Option<DocumentSymbolResponse>> {
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(&params.text_document.uri)
+    .await
+  {
+    Ok((uri, session)) => match session.document_symbols(&uri, &cx.session_manager.documents) {
+      Ok(symbols) => Ok(symbols),
+      Err(err) => {
+        tracing::error!("{}", err.to_string());
+        Ok(None)
+      }
+    },
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_document_link(
+  cx: &ServerContext,
+  params: DocumentLinkParams,
+) -> Result<Option<Vec<DocumentLink>>> {
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(&params.text_document.uri)
+    .await
+  {
+    Ok((uri, session)) => match session.document_links(&uri, &cx.session_manager.documents) {
+      Ok(links) => Ok(Some(links)),
+      Err(err) => {
+        tracing::error!("{}", err.to_string());
+        Ok(None)
+      }
+    },
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_folding_range(
+  cx: &ServerContext,
+  params: FoldingRangeParams,
+) -> Result<Option<Vec<FoldingRange>>> {
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(&params.text_document.uri)
+    .await
+  {
+    Ok((uri, session)) => match session.folding_ranges(&uri, &cx.session_manager.documents) {
+      Ok(ranges) => Ok(Some(ranges)),
+      Err(err) => {
+        tracing::error!("{}", err.to_string());
+        Ok(None)
+      }
+    },
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_formatting(
+  cx: &ServerContext,
+  params: DocumentFormattingParams,
+) -> Result<Option<Vec<TextEdit>>> {
+  format(cx, &params.text_document.uri, None).await
+}
+
+pub async fn handle_range_formatting(
+  cx: &ServerContext,
+  params: DocumentRangeFormattingParams,
+) -> Result<Option<Vec<TextEdit>>> {
+  format(cx, &params.text_document.uri, Some(params.range)).await
+}
+
+async fn format(
+  cx: &ServerContext,
+  workspace_uri: &Url,
+  range: Option<Range>,
+) -> Result<Option<Vec<TextEdit>>> {
+  let config = cx.config.read().formatting.clone();
+  match cx.session_manager.uri_and_session_from_workspace(workspace_uri).await {
+    Ok((uri, session)) => match session.format(&uri, range, &config, &cx.session_manager.documents) {
+      Ok(edits) => Ok(Some(edits)),
+      Err(err) => {
+        tracing::error!("{}", err.to_string());
+        Ok(None)
+      }
+    },
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_inlay_hint(
+  cx: &ServerContext,
+  params: InlayHintParams,
+) -> Result<Option<Vec<InlayHint>>> {
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(&params.text_document.uri)
+    .await
+  {
+    Ok((uri, session)) => match session.inlay_hints(&uri, params.range, &cx.session_manager.documents) {
+      Ok(hints) => Ok(Some(hints)),
+      Err(err) => {
+        tracing::error!("{}", err.to_string());
+        Ok(None)
+      }
+    },
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_signature_help(
+  cx: &ServerContext,
+  params: SignatureHelpParams,
+) -> Result<Option<SignatureHelp>> {
+  let position_params = params.text_document_position_params;
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(&position_params.text_document.uri)
+    .await
+  {
+    Ok((uri, session)) => match session.signature_help(
+      &uri,
+      position_params.position,
+      &cx.session_manager.documents,
+    ) {
+      Ok(help) => Ok(help),
+      Err(err) => {
+        tracing::error!("{}", err.to_string());
+        Ok(None)
+      }
+    },
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_code_action(
+  cx: &ServerContext,
+  params: CodeActionParams,
+) -> Result<Option<CodeActionResponse>> {
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(&params.text_document.uri)
+    .await
+  {
+    Ok((uri, session)) => match session.code_actions(&uri, params.range, &cx.session_manager.documents) {
+      Ok(mut actions) => {
+        if let Some(document) = cx.plugin_document(&params.text_document.uri, &uri) {
+          let diagnostics = params.context.diagnostics;
+          actions.extend(cx.plugins.code_actions(document, params.range, diagnostics).await);
+        }
+        Ok(Some(actions))
+      }
+      Err(err) => {
+        tracing::error!("{}", err.to_string());
+        Ok(None)
+      }
+    },
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_workspace_symbol(
+  cx: &ServerContext,
+  params: WorkspaceSymbolParams,
+) -> Result<Option<Vec<SymbolInformation>>> {
+  Ok(Some(cx.session_manager.workspace_symbols(&params.query)))
+}
+
+pub async fn handle_semantic_tokens_full(
+  cx: &ServerContext,
+  params: SemanticTokensParams,
+) -> Result<Option<SemanticTokensResult>> {
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(&params.text_document.uri)
+    .await
+  {
+    Ok((uri, session)) => match session.semantic_tokens_full(&uri, &cx.session_manager.documents) {
+      Ok(tokens) => Ok(Some(SemanticTokensResult::Tokens(tokens))),
+      Err(err) => {
+        tracing::error!("{}", err.to_string());
+        Ok(None)
+      }
+    },
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(None)
+    }
+  }
+}
+
+pub async fn handle_semantic_tokens_full_delta(
+  cx: &ServerContext,
+  params: SemanticTokensDeltaParams,
+) -> Result<Option<SemanticTokensFullDeltaResult>> {
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(&params.text_document.uri)
+    .await
+  {
+    Ok((uri, session)) => match session.semantic_tokens_delta(
+      &uri,
+      &params.previous_result_id,
+      &cx.session_manager.documents,
+    ) {
+      Ok(result) => Ok(Some(result)),
+      Err(err) => {
+        tracing::error!("{}", err.to_string());
+        Ok(None)
+      }
+    },
     Err(err) => {
       tracing::error!("{}", err.to_string());
       Ok(None)
     }
   }
 }
+
+pub async fn handle_document_color(
+  cx: &ServerContext,
+  params: DocumentColorParams,
+) -> Result<Vec<ColorInformation>> {
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(&params.text_document.uri)
+    .await
+  {
+    Ok((uri, session)) => match session.document_colors(&uri, &cx.session_manager.documents) {
+      Ok(colors) => Ok(colors),
+      Err(err) => {
+        tracing::error!("{}", err.to_string());
+        Ok(vec![])
+      }
+    },
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(vec![])
+    }
+  }
+}
+
+pub async fn handle_color_presentation(
+  cx: &ServerContext,
+  params: ColorPresentationParams,
+) -> Result<Vec<ColorPresentation>> {
+  match cx
+    .session_manager
+    .uri_and_session_from_workspace(&params.text_document.uri)
+    .await
+  {
+    Ok((uri, session)) => match session.color_presentations(
+      &uri,
+      params.color,
+      params.range,
+      &cx.session_manager.documents,
+    ) {
+      Ok(presentations) => Ok(presentations),
+      Err(err) => {
+        tracing::error!("{}", err.to_string());
+        Ok(vec![])
+      }
+    },
+    Err(err) => {
+      tracing::error!("{}", err.to_string());
+      Ok(vec![])
+    }
+  }
+}
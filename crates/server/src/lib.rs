@@ -1,6 +1,7 @@
 pub mod server;
 pub mod context;
 pub mod capablities;
+pub mod plugins;
 pub mod handlers {
   pub mod notification;
   pub mod request;
@@ -8,10 +9,16 @@ pub mod handlers {
 
 use tower_lsp::{LspService, Server};
 use context::ServerContext;
+use plugins::PluginRegistry;
 
 pub async fn start() {
+  start_with_plugins(PluginRegistry::with_builtin_plugins()).await
+}
+
+/// Starts the server with `plugins`, e.g. the built-in ones and third-party plugins.
+pub async fn start_with_plugins(plugins: PluginRegistry) {
   let (service, socket) =
-    LspService::build(ServerContext::new).finish();
+    LspService::build(|client| ServerContext::with_plugins(client, plugins)).finish();
 
   Server::new(tokio::io::stdin(), tokio::io::stdout(), socket)
     .serve(service)
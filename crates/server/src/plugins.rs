@@ -0,0 +1,497 @@
+use std::{
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  time::Duration,
+};
+
+use makepad_analyzer_core::errors::PluginError;
+use makepad_analyzer_plugin_live::MakepadAnalyzerLivePlugin;
+use makepad_analyzer_plugin_shader::MakepadAnalyzerShaderPlugin;
+use makepad_analyzer_plugin_types::{
+  DocumentRegion, MakepadAnalyzerPlugin, PluginDocument, PluginInfo, ProvidedCapabilities,
+  PLUGIN_API_VERSION,
+};
+use tokio::time::{timeout_at, Instant};
+use tower_lsp::lsp_types::{
+  CodeActionOrCommand, CompletionItem, Diagnostic, GotoDefinitionResponse, Hover, HoverContents,
+  Location, LocationLink, MarkedString, MarkupContent, MarkupKind, Position, Range,
+};
+
+/// How long a plugin may take to answer before its results are left out.
+pub const DEFAULT_PLUGIN_TIMEOUT: Duration = Duration::from_millis(300);
+
+struct RegisteredPlugin {
+  plugin: Arc<dyn MakepadAnalyzerPlugin>,
+  info: PluginInfo,
+  provided: ProvidedCapabilities,
+  timeout: Duration,
+  /// Set while a call that timed out still runs. It keeps its blocking thread until it
+  /// returns, so the plugin is skipped until then rather than piling up threads.
+  quarantined: Arc<AtomicBool>,
+}
+
+/// The plugins of the server. Requests are forwarded to every plugin that handles them in the
+/// region they are made in, all at once, and the answers of the ones that make it in time are
+/// merged.
+#[derive(Default)]
+pub struct PluginRegistry {
+  plugins: Vec<RegisteredPlugin>,
+}
+
+impl PluginRegistry {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// A registry with the live DSL and shader plugins.
+  pub fn with_builtin_plugins() -> Self {
+    let mut registry = PluginRegistry::new();
+    let plugins: [Arc<dyn MakepadAnalyzerPlugin>; 2] = [
+      Arc::new(MakepadAnalyzerLivePlugin::new()),
+      Arc::new(MakepadAnalyzerShaderPlugin::new()),
+    ];
+    for plugin in plugins {
+      if let Err(err) = registry.register(plugin) {
+        tracing::error!("{}", err);
+      }
+    }
+    registry
+  }
+
+  pub fn register(&mut self, plugin: Arc<dyn MakepadAnalyzerPlugin>) -> Result<(), PluginError> {
+    self.register_with_timeout(plugin, DEFAULT_PLUGIN_TIMEOUT)
+  }
+
+  pub fn register_with_timeout(
+    &mut self,
+    plugin: Arc<dyn MakepadAnalyzerPlugin>,
+    timeout: Duration,
+  ) -> Result<(), PluginError> {
+    let info = plugin.plugin_info();
+    if plugin.api_version() != PLUGIN_API_VERSION {
+      return Err(PluginError::ApiVersionMismatch {
+        name: info.name,
+        expected: PLUGIN_API_VERSION,
+        found: plugin.api_version(),
+      });
+    }
+    if self.plugins.iter().any(|registered| registered.info.name == info.name) {
+      return Err(PluginError::AlreadyRegistered { name: info.name });
+    }
+    tracing::info!("Registered plugin `{}` {}", info.name, info.version);
+    let provided = plugin.capabilities().provided();
+    let quarantined = Arc::new(AtomicBool::new(false));
+    self.plugins.push(RegisteredPlugin { plugin, info, provided, timeout, quarantined });
+    Ok(())
+  }
+
+  pub fn plugin_infos(&self) -> impl Iterator<Item = &PluginInfo> {
+    self.plugins.iter().map(|registered| &registered.info)
+  }
+
+  /// What the plugins answer together, with every region and trigger character.
+  pub fn provided(&self) -> ProvidedCapabilities {
+    let mut union = ProvidedCapabilities::default();
+    for ProvidedCapabilities {
+      regions,
+      completion,
+      completion_trigger_characters,
+      hover,
+      diagnostics,
+      definition,
+      code_actions,
+    } in self.plugins.iter().map(|registered| &registered.provided)
+    {
+      for region in regions {
+        if !union.regions.contains(region) {
+          union.regions.push(*region);
+        }
+      }
+      for character in completion_trigger_characters {
+        if !union.completion_trigger_characters.contains(character) {
+          union.completion_trigger_characters.push(character.clone());
+        }
+      }
+      union.completion |= completion;
+      union.hover |= hover;
+      union.diagnostics |= diagnostics;
+      union.definition |= definition;
+      union.code_actions |= code_actions;
+    }
+    union
+  }
+
+  pub async fn completion(
+    &self,
+    document: Arc<PluginDocument>,
+    position: Position,
+    trigger_char: Option<String>,
+  ) -> Vec<CompletionItem> {
+    let region = document.region_at(document.offset(position));
+    self
+      .fan_out(document, Some(region), |provided| provided.completion, move |plugin, document| {
+        plugin.capabilities().handle_completion(document, position, trigger_char.as_deref())
+      })
+      .await
+      .into_iter()
+      .flatten()
+      .collect()
+  }
+
+  pub async fn hover(&self, document: Arc<PluginDocument>, position: Position) -> Vec<Hover> {
+    let region = document.region_at(document.offset(position));
+    self
+      .fan_out(document, Some(region), |provided| provided.hover, move |plugin, document| {
+        plugin.capabilities().handle_hover(document, position)
+      })
+      .await
+      .into_iter()
+      .flatten()
+      .collect()
+  }
+
+  pub async fn diagnostics(&self, document: Arc<PluginDocument>) -> Vec<Diagnostic> {
+    self
+      .fan_out(document, None, |provided| provided.diagnostics, |plugin, document| {
+        plugin.capabilities().handle_diagnostics(document)
+      })
+      .await
+      .into_iter()
+      .flatten()
+      .collect()
+  }
+
+  pub async fn definition(
+    &self,
+    document: Arc<PluginDocument>,
+    position: Position,
+  ) -> Vec<Location> {
+    let region = document.region_at(document.offset(position));
+    self
+      .fan_out(document, Some(region), |provided| provided.definition, move |plugin, document| {
+        plugin.capabilities().handle_definition(document, position)
+      })
+      .await
+      .into_iter()
+      .flatten()
+      .collect()
+  }
+
+  pub async fn code_actions(
+    &self,
+    document: Arc<PluginDocument>,
+    range: Range,
+    diagnostics: Vec<Diagnostic>,
+  ) -> Vec<CodeActionOrCommand> {
+    let region = document.region_at(document.offset(range.start));
+    self
+      .fan_out(document, Some(region), |provided| provided.code_actions, move |plugin, document| {
+        plugin.capabilities().handle_code_actions(document, range, &diagnostics)
+      })
+      .await
+      .into_iter()
+      .flatten()
+      .collect()
+  }
+
+  /// Runs `request` on every plugin that `wants` it and handles `region`, each on a blocking
+  /// thread, and collects the answers that come in before the plugin's timeout. A plugin that
+  /// is too slow or panics is logged and left out, and skipped until its late call returns.
+  async fn fan_out<T: Send + 'static>(
+    &self,
+    document: Arc<PluginDocument>,
+    region: Option<DocumentRegion>,
+    wants: impl Fn(&ProvidedCapabilities) -> bool,
+    request: impl Fn(&dyn MakepadAnalyzerPlugin, &PluginDocument) -> T + Send + Sync + 'static,
+  ) -> Vec<T> {
+    let request = Arc::new(request);
+    let tasks: Vec<_> = self
+      .plugins
+      .iter()
+      .filter(|registered| wants(&registered.provided))
+      .filter(|registered| {
+        region.is_none_or(|region| registered.provided.regions.contains(&region))
+      })
+      .filter(|registered| {
+        let quarantined = registered.quarantined.load(Ordering::Acquire);
+        if quarantined {
+          tracing::debug!("Plugin `{}` is still on a late call, skipping it", registered.info.name);
+        }
+        !quarantined
+      })
+      .map(|registered| {
+        let plugin = registered.plugin.clone();
+        let document = document.clone();
+        let request = request.clone();
+        let deadline = Instant::now() + registered.timeout;
+        let task = tokio::task::spawn_blocking(move || request(plugin.as_ref(), &document));
+        (registered, deadline, task)
+      })
+      .collect();
+
+    let mut results = vec![];
+    for (registered, deadline, mut task) in tasks {
+      match timeout_at(deadline, &mut task).await {
+        Ok(Ok(result)) => results.push(result),
+        Ok(Err(err)) => tracing::error!("Plugin `{}` failed: {}", registered.info.name, err),
+        Err(_) => {
+          tracing::warn!(
+            "Plugin `{}` took longer than {:?}, leaving its results out",
+            registered.info.name,
+            registered.timeout
+          );
+          let quarantined = registered.quarantined.clone();
+          quarantined.store(true, Ordering::Release);
+          tokio::spawn(async move {
+            let _ = task.await;
+            quarantined.store(false, Ordering::Release);
+          });
+        }
+      }
+    }
+    results
+  }
+}
+
+/// The completion items of the session followed by the plugins' ones it doesn't have yet.
+pub fn merge_completion_items(
+  items: Option<Vec<CompletionItem>>,
+  plugin_items: Vec<CompletionItem>,
+) -> Option<Vec<CompletionItem>> {
+  if plugin_items.is_empty() {
+    return items;
+  }
+  let mut items = items.unwrap_or_default();
+  for item in plugin_items {
+    if !items.iter().any(|known| known.label == item.label) {
+      items.push(item);
+    }
+  }
+  Some(items)
+}
+
+/// One hover with the markdown of each, separated by rules, over the range of the first.
+pub fn merge_hovers(hover: Option<Hover>, plugin_hovers: Vec<Hover>) -> Option<Hover> {
+  let mut hovers = hover.into_iter().chain(plugin_hovers);
+  let first = hovers.next()?;
+  let range = first.range;
+  let value = std::iter::once(first)
+    .chain(hovers)
+    .map(|hover| hover_markdown(hover.contents))
+    .collect::<Vec<_>>()
+    .join("\n\n---\n\n");
+  Some(Hover {
+    contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }),
+    range,
+  })
+}
+
+fn hover_markdown(contents: HoverContents) -> String {
+  let marked = |marked: MarkedString| match marked {
+    MarkedString::String(value) => value,
+    MarkedString::LanguageString(code) => format!("```{}\n{}\n```", code.language, code.value),
+  };
+  match contents {
+    HoverContents::Scalar(value) => marked(value),
+    HoverContents::Array(values) => {
+      values.into_iter().map(marked).collect::<Vec<_>>().join("\n\n")
+    }
+    HoverContents::Markup(markup) => markup.value,
+  }
+}
+
+/// The definitions of the session followed by the plugins' ones.
+pub fn merge_definitions(
+  response: Option<GotoDefinitionResponse>,
+  plugin_locations: Vec<Location>,
+) -> Option<GotoDefinitionResponse> {
+  if plugin_locations.is_empty() {
+    return response;
+  }
+  match response {
+    Some(GotoDefinitionResponse::Link(mut links)) => {
+      links.extend(plugin_locations.into_iter().map(|location| LocationLink {
+        origin_selection_range: None,
+        target_uri: location.uri,
+        target_range: location.range,
+        target_selection_range: location.range,
+      }));
+      Some(GotoDefinitionResponse::Link(links))
+    }
+    response => {
+      let mut locations = match response {
+        Some(GotoDefinitionResponse::Scalar(location)) => vec![location],
+        Some(GotoDefinitionResponse::Array(locations)) => locations,
+        _ => vec![],
+      };
+      for location in plugin_locations {
+        if !locations.contains(&location) {
+          locations.push(location);
+        }
+      }
+      Some(GotoDefinitionResponse::Array(locations))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use makepad_analyzer_plugin_types::{PluginCapability, Url};
+
+  use super::*;
+
+  #[derive(Default)]
+  struct SlowPlugin {
+    calls: std::sync::atomic::AtomicUsize,
+    api_version: u32,
+    delay: Duration,
+  }
+
+  impl SlowPlugin {
+    fn new(delay: Duration) -> Self {
+      SlowPlugin { api_version: PLUGIN_API_VERSION, delay, ..SlowPlugin::default() }
+    }
+  }
+
+  impl PluginCapability for SlowPlugin {
+    fn provided(&self) -> ProvidedCapabilities {
+      ProvidedCapabilities {
+        regions: vec![DocumentRegion::LiveDesign, DocumentRegion::Shader],
+        completion: true,
+        completion_trigger_characters: vec!["@".to_string()],
+        ..ProvidedCapabilities::default()
+      }
+    }
+
+    fn handle_completion(
+      &self,
+      _document: &PluginDocument,
+      _position: Position,
+      _trigger_char: Option<&str>,
+    ) -> Vec<CompletionItem> {
+      self.calls.fetch_add(1, Ordering::Relaxed);
+      std::thread::sleep(self.delay);
+      vec![CompletionItem { label: "slow".to_string(), ..Default::default() }]
+    }
+  }
+
+  impl MakepadAnalyzerPlugin for SlowPlugin {
+    fn plugin_info(&self) -> PluginInfo {
+      PluginInfo {
+        name: "slow".to_string(),
+        description: "Answers too late".to_string(),
+        version: "0.0.1".to_string(),
+      }
+    }
+
+    fn capabilities(&self) -> &dyn PluginCapability {
+      self
+    }
+
+    fn api_version(&self) -> u32 {
+      self.api_version
+    }
+  }
+
+  fn document() -> Arc<PluginDocument> {
+    let text = [
+      "live_design! {",
+      "  Tab = <View> {",
+      "    draw_bg: {",
+      "      fn pixel(self) -> vec4 { return mix; }",
+      "    }",
+      "  }",
+      "  App = {{App}} {",
+      "    ",
+      "  }",
+      "}",
+    ]
+    .join("\n");
+    Arc::new(PluginDocument::new(Url::parse("file:///app/src/app.rs").unwrap(), text))
+  }
+
+  fn labels(items: &[CompletionItem]) -> Vec<&str> {
+    items.iter().map(|item| item.label.as_str()).collect()
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_plugin_registry() {
+    let mut registry = PluginRegistry::with_builtin_plugins();
+    assert_eq!(
+      registry.register(Arc::new(SlowPlugin::default())),
+      Err(PluginError::ApiVersionMismatch {
+        name: "slow".to_string(),
+        expected: PLUGIN_API_VERSION,
+        found: 0,
+      })
+    );
+    let slow = Arc::new(SlowPlugin::new(Duration::from_millis(400)));
+    registry.register_with_timeout(slow.clone(), Duration::from_millis(50)).unwrap();
+    assert_eq!(
+      registry.register(slow.clone()),
+      Err(PluginError::AlreadyRegistered { name: "slow".to_string() })
+    );
+
+    let provided = registry.provided();
+    assert!(provided.completion && provided.hover);
+    assert!(provided.completion_trigger_characters.contains(&".".to_string()));
+    assert!(provided.completion_trigger_characters.contains(&"@".to_string()));
+
+    // The shader plugin answers in shader bodies, the live plugin in the DSL, and the slow
+    // plugin doesn't hold either up.
+    let started = std::time::Instant::now();
+    let items = registry.completion(document(), Position::new(3, 38), None).await;
+    assert!(labels(&items).contains(&"mix"));
+    assert!(!labels(&items).contains(&"pub"));
+    assert!(!labels(&items).contains(&"slow"));
+    let items = registry.completion(document(), Position::new(7, 4), None).await;
+    assert!(items.is_empty(), "{:?}", labels(&items));
+    assert!(started.elapsed() < Duration::from_millis(300));
+    // The second request came in while the timed out call was still running.
+    assert_eq!(slow.calls.load(Ordering::Relaxed), 1);
+
+    let hovers = registry.hover(document(), Position::new(3, 39)).await;
+    assert_eq!(hovers.len(), 1);
+
+    // Once the late call returns, the plugin is asked again.
+    tokio::time::sleep(Duration::from_millis(450)).await;
+    registry.completion(document(), Position::new(7, 4), None).await;
+    assert_eq!(slow.calls.load(Ordering::Relaxed), 2);
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_overlapping_calls_both_answer() {
+    let mut registry = PluginRegistry::new();
+    registry.register(Arc::new(SlowPlugin::new(Duration::from_millis(100)))).unwrap();
+    let (first, second) = tokio::join!(
+      registry.completion(document(), Position::new(7, 4), None),
+      registry.completion(document(), Position::new(7, 4), None),
+    );
+    assert_eq!(labels(&first), ["slow"]);
+    assert_eq!(labels(&second), ["slow"]);
+  }
+
+  #[test]
+  fn test_merge_results() {
+    let item = |label: &str| CompletionItem { label: label.to_string(), ..Default::default() };
+    let items = merge_completion_items(Some(vec![item("theme")]), vec![item("theme"), item("pub")]);
+    assert_eq!(labels(items.as_deref().unwrap()), ["theme", "pub"]);
+    assert_eq!(merge_completion_items(None, vec![]), None);
+
+    let hover = |value: &str| Hover {
+      contents: HoverContents::Scalar(MarkedString::String(value.to_string())),
+      range: None,
+    };
+    let merged = merge_hovers(Some(hover("a")), vec![hover("b")]).unwrap();
+    assert_eq!(
+      merged.contents,
+      HoverContents::Markup(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: "a\n\n---\n\nb".to_string(),
+      })
+    );
+    assert_eq!(merge_hovers(None, vec![]), None);
+  }
+}
@@ -1,4 +1,4 @@
-use tower_lsp::{jsonrpc::Result, lsp_types::{CompletionParams, CompletionResponse, DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams, InitializeParams, InitializeResult, InitializedParams}, LanguageServer};
+use tower_lsp::{jsonrpc::Result, lsp_types::{CodeActionParams, CodeActionResponse, ColorInformation, ColorPresentation, ColorPresentationParams, CompletionParams, CompletionResponse, DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams, DocumentColorParams, DocumentFormattingParams, DocumentHighlight, DocumentHighlightParams, DocumentLink, DocumentLinkParams, DocumentRangeFormattingParams, DocumentSymbolParams, DocumentSymbolResponse, FoldingRange, FoldingRangeParams, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverParams, InitializeParams, InitializeResult, InlayHint, InlayHintParams, InitializedParams, Location, PrepareRenameResponse, ReferenceParams, RenameParams, SemanticTokensDeltaParams, SemanticTokensFullDeltaResult, SemanticTokensParams, SemanticTokensResult, SignatureHelp, SignatureHelpParams, SymbolInformation, TextDocumentPositionParams, TextEdit, WorkspaceEdit, WorkspaceSymbolParams}, LanguageServer};
 
 use crate::{context::ServerContext, handlers::{notification, request}};
 
@@ -42,4 +42,80 @@ impl LanguageServer for ServerContext {
   async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
     request::handle_completion(&self, params).await
   }
+
+  async fn goto_definition(&self, params: GotoDefinitionParams) -> Result<Option<GotoDefinitionResponse>> {
+    request::handle_goto_definition(self, params).await
+  }
+
+  async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+    request::handle_hover(self, params).await
+  }
+
+  async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+    request::handle_references(self, params).await
+  }
+
+  async fn document_highlight(&self, params: DocumentHighlightParams) -> Result<Option<Vec<DocumentHighlight>>> {
+    request::handle_document_highlight(self, params).await
+  }
+
+  async fn prepare_rename(&self, params: TextDocumentPositionParams) -> Result<Option<PrepareRenameResponse>> {
+    request::handle_prepare_rename(self, params).await
+  }
+
+  async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+    request::handle_rename(self, params).await
+  }
+
+  async fn document_symbol(&self, params: DocumentSymbolParams) -> Result<Option<DocumentSymbolResponse>> {
+    request::handle_document_symbol(self, params).await
+  }
+
+  async fn document_link(&self, params: DocumentLinkParams) -> Result<Option<Vec<DocumentLink>>> {
+    request::handle_document_link(self, params).await
+  }
+
+  async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+    request::handle_folding_range(self, params).await
+  }
+
+  async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+    request::handle_formatting(self, params).await
+  }
+
+  async fn range_formatting(&self, params: DocumentRangeFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+    request::handle_range_formatting(self, params).await
+  }
+
+  async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+    request::handle_signature_help(self, params).await
+  }
+
+  async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+    request::handle_inlay_hint(self, params).await
+  }
+
+  async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+    request::handle_code_action(self, params).await
+  }
+
+  async fn symbol(&self, params: WorkspaceSymbolParams) -> Result<Option<Vec<SymbolInformation>>> {
+    request::handle_workspace_symbol(self, params).await
+  }
+
+  async fn semantic_tokens_full(&self, params: SemanticTokensParams) -> Result<Option<SemanticTokensResult>> {
+    request::handle_semantic_tokens_full(self, params).await
+  }
+
+  async fn semantic_tokens_full_delta(&self, params: SemanticTokensDeltaParams) -> Result<Option<SemanticTokensFullDeltaResult>> {
+    request::handle_semantic_tokens_full_delta(self, params).await
+  }
+
+  async fn document_color(&self, params: DocumentColorParams) -> Result<Vec<ColorInformation>> {
+    request::handle_document_color(self, params).await
+  }
+
+  async fn color_presentation(&self, params: ColorPresentationParams) -> Result<Vec<ColorPresentation>> {
+    request::handle_color_presentation(self, params).await
+  }
 }
@@ -0,0 +1,179 @@
+/// A property the Makepad framework understands on widgets, with the values it accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuiltinProperty {
+  pub name: &'static str,
+  /// The Rust type the property is read into.
+  pub ty: &'static str,
+  /// The identifiers or shapes the property accepts.
+  pub values: &'static [&'static str],
+  pub doc: &'static str,
+}
+
+const SIZE: &[&str] = &["Fill", "Fit", "All", "<number>"];
+const MARGIN: &[&str] = &["<number>", "{left: f64, top: f64, right: f64, bottom: f64}"];
+const BOOL: &[&str] = &["true", "false"];
+
+pub const BUILTIN_PROPERTIES: &[BuiltinProperty] = &[
+  BuiltinProperty {
+    name: "width",
+    ty: "Size",
+    values: SIZE,
+    doc: "The width of the widget's walk.",
+  },
+  BuiltinProperty {
+    name: "height",
+    ty: "Size",
+    values: SIZE,
+    doc: "The height of the widget's walk.",
+  },
+  BuiltinProperty {
+    name: "margin",
+    ty: "Margin",
+    values: MARGIN,
+    doc: "Space around the widget, one number for every side or one per side.",
+  },
+  BuiltinProperty {
+    name: "padding",
+    ty: "Padding",
+    values: MARGIN,
+    doc: "Space between the widget's edges and its children.",
+  },
+  BuiltinProperty {
+    name: "flow",
+    ty: "Flow",
+    values: &["Right", "Down", "Overlay", "RightWrap"],
+    doc: "The direction children are laid out in.",
+  },
+  BuiltinProperty {
+    name: "align",
+    ty: "Align",
+    values: &["{x: f64, y: f64}"],
+    doc: "Where children are placed in the free space, `0.0` to `1.0` on each axis.",
+  },
+  BuiltinProperty {
+    name: "spacing",
+    ty: "f64",
+    values: &["<number>"],
+    doc: "The gap between consecutive children.",
+  },
+  BuiltinProperty {
+    name: "line_spacing",
+    ty: "f64",
+    values: &["<number>"],
+    doc: "The gap between lines of wrapped children.",
+  },
+  BuiltinProperty {
+    name: "clip_x",
+    ty: "bool",
+    values: BOOL,
+    doc: "Whether children are clipped horizontally.",
+  },
+  BuiltinProperty {
+    name: "clip_y",
+    ty: "bool",
+    values: BOOL,
+    doc: "Whether children are clipped vertically.",
+  },
+  BuiltinProperty {
+    name: "abs_pos",
+    ty: "DVec2",
+    values: &["vec2(x, y)"],
+    doc: "Positions the widget absolutely, relative to its parent.",
+  },
+  BuiltinProperty {
+    name: "visible",
+    ty: "bool",
+    values: BOOL,
+    doc: "Whether the widget is drawn.",
+  },
+  BuiltinProperty {
+    name: "show_bg",
+    ty: "bool",
+    values: BOOL,
+    doc: "Whether `draw_bg` is drawn behind the widget.",
+  },
+  BuiltinProperty {
+    name: "cursor",
+    ty: "MouseCursor",
+    values: &["Default", "Arrow", "Hand", "Text", "Move", "NotAllowed", "Hidden"],
+    doc: "The mouse cursor shown while hovering the widget.",
+  },
+  BuiltinProperty {
+    name: "text",
+    ty: "String",
+    values: &["\"<text>\""],
+    doc: "The text the widget displays.",
+  },
+  BuiltinProperty {
+    name: "font_size",
+    ty: "f64",
+    values: &["<number>"],
+    doc: "The font size in points.",
+  },
+  BuiltinProperty {
+    name: "wrap",
+    ty: "TextWrap",
+    values: &["Ellipsis", "Word", "Line"],
+    doc: "How text that does not fit is wrapped.",
+  },
+  BuiltinProperty {
+    name: "color",
+    ty: "Vec4",
+    values: &["#rrggbb", "#rrggbbaa", "vec4(r, g, b, a)", "(CONSTANT)"],
+    doc: "A color, as a hex literal, a `vec4` or a color constant.",
+  },
+  BuiltinProperty {
+    name: "svg_file",
+    ty: "LiveDependency",
+    values: &["dep(\"crate://self/<path>\")"],
+    doc: "The SVG file an icon is drawn from.",
+  },
+  BuiltinProperty {
+    name: "source",
+    ty: "LiveDependency",
+    values: &["dep(\"crate://self/<path>\")"],
+    doc: "The image file an image widget is drawn from.",
+  },
+  BuiltinProperty {
+    name: "radio_type",
+    ty: "RadioType",
+    values: &["Round", "Tab"],
+    doc: "The shape a radio button is drawn with.",
+  },
+];
+
+pub fn builtin_property(name: &str) -> Option<&'static BuiltinProperty> {
+  BUILTIN_PROPERTIES.iter().find(|property| property.name == name)
+}
+
+/// The widgets `use link::widgets::*` brings into scope.
+pub const FRAMEWORK_WIDGETS: &[(&str, &str)] = &[
+  ("View", "A container that lays out its children."),
+  ("SolidView", "A view with a solid background."),
+  ("RoundedView", "A view with a rounded background."),
+  ("ScrollXView", "A view scrolling horizontally."),
+  ("ScrollYView", "A view scrolling vertically."),
+  ("ScrollXYView", "A view scrolling in both directions."),
+  ("Window", "The top-level window of an application."),
+  ("Label", "A line or paragraph of text."),
+  ("Button", "A clickable button with a label."),
+  ("RadioButton", "One option of a group of which only one is selected."),
+  ("CheckBox", "A toggle with a label."),
+  ("TextInput", "An editable text field."),
+  ("Image", "A bitmap image loaded from a `dep()` file."),
+  ("Icon", "An SVG icon loaded from a `dep()` file."),
+  ("LinkLabel", "A label that behaves like a hyperlink."),
+  ("PortalList", "A virtualized list of items."),
+  ("Slider", "A slider over a range of numbers."),
+  ("DropDown", "A button that opens a list of options."),
+  ("Splitter", "Two panes with a draggable divider."),
+  ("Filler", "An empty view filling the remaining space."),
+  ("PopupNotification", "A popup shown above the other widgets."),
+];
+
+/// The modules of the framework that `use link::` can import from.
+pub const LINK_MODULES: &[(&str, &str)] = &[
+  ("theme", "The colors, fonts and sizes of the default theme."),
+  ("shaders", "The shader helpers such as `Sdf2d`."),
+  ("widgets", "The framework widgets such as `View` and `Button`."),
+];
@@ -0,0 +1,385 @@
+use std::{collections::HashMap, path::Path};
+
+use lsp_types::{
+  CodeAction, CodeActionKind, CodeActionOrCommand, Position, Range, TextEdit, WorkspaceEdit,
+};
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{
+  parse_document, LineIndex, ParsedDocument, Span, WidgetKind, WidgetNode,
+};
+use url::Url;
+
+use crate::{
+  builtins::FRAMEWORK_WIDGETS, capabilities::diagnostic::missing_import_diagnostic, Session,
+};
+
+impl Session {
+  /// The code actions available for `range` of the document at the temp `uri`.
+  pub fn code_actions(
+    &self,
+    uri: &Url,
+    range: Range,
+    documents: &Documents,
+  ) -> Result<Vec<CodeActionOrCommand>, DocumentError> {
+    let text_document = documents.get_text_document(uri)?;
+    let source = text_document.get_text();
+    let path = Path::new(uri.path());
+    let Some(workspace_url) = self.workspace_url(path) else {
+      return Ok(vec![]);
+    };
+    let document = parse_document(source);
+    let mut actions = self.import_actions(path, source, &document, range, &workspace_url);
+    actions.extend(self.extract_actions(path, source, &document, range, &workspace_url));
+    Ok(actions)
+  }
+
+  /// One `use crate::...;` quick fix per file declaring each missing name within `range`.
+  fn import_actions(
+    &self,
+    path: &Path,
+    source: &str,
+    document: &ParsedDocument,
+    range: Range,
+    workspace_url: &Url,
+  ) -> Vec<CodeActionOrCommand> {
+    let line_index = LineIndex::new(source);
+    let mut actions = vec![];
+    for missing in self.missing_imports(path, document) {
+      let reference_range = line_index.range(missing.reference.span);
+      if reference_range.end < range.start || range.end < reference_range.start {
+        continue;
+      }
+      let Some((position, indent, separator)) =
+        import_insertion(source, document, missing.reference.span.start)
+      else {
+        continue;
+      };
+      let diagnostic = missing_import_diagnostic(&missing, &line_index);
+      for file in &missing.candidates {
+        let Some(import_path) = self.import_path(file, &missing.reference.name) else {
+          continue;
+        };
+        let text = format!("{}use {};\n{}", indent, import_path, separator);
+        let changes = HashMap::from([(
+          workspace_url.clone(),
+          vec![TextEdit::new(Range::new(position, position), text)],
+        )]);
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+          title: format!("Import `{}`", import_path),
+          kind: Some(CodeActionKind::QUICKFIX),
+          diagnostics: Some(vec![diagnostic.clone()]),
+          edit: Some(WorkspaceEdit::new(changes)),
+          is_preferred: Some(missing.candidates.len() == 1),
+          ..CodeAction::default()
+        }));
+      }
+    }
+    actions
+  }
+
+  /// Extracts the innermost `<Type> { ... }` instance around `range` into a new top-level
+  /// definition placed before the one containing it, private and `pub`. The body moves as
+  /// is, so the overrides it holds carry over, and the instance becomes `<Name> {}`.
+  fn extract_actions(
+    &self,
+    path: &Path,
+    source: &str,
+    document: &ParsedDocument,
+    range: Range,
+    workspace_url: &Url,
+  ) -> Vec<CodeActionOrCommand> {
+    let line_index = LineIndex::new(source);
+    let selection = line_index.span(range);
+    let Some(block) = document.block_at(selection.start) else {
+      return vec![];
+    };
+    if document.errors.iter().any(|error| block.span.contains(error.span.start)) {
+      return vec![];
+    }
+    let Some((definition, instance)) = block
+      .widgets()
+      .find_map(|definition| Some((definition, extracted_instance(definition, selection)?)))
+    else {
+      return vec![];
+    };
+    let (Some(type_span), Some(body_span)) = (instance.type_span, instance.body_span) else {
+      return vec![];
+    };
+
+    let base_name = match instance.name.is_empty() {
+      true => format!("My{}", instance.widget_type),
+      false => pascal_case(&instance.name),
+    };
+    let is_taken = |name: &str| {
+      FRAMEWORK_WIDGETS.iter().any(|(widget, _)| *widget == name)
+        || !self.resolve_name(path, document, name).is_empty()
+    };
+    let name = (1..)
+      .map(|index| match index {
+        1 => base_name.clone(),
+        _ => format!("{}{}", base_name, index),
+      })
+      .find(|name| !is_taken(name))
+      .unwrap_or(base_name);
+
+    let indent = line_indent(source, definition.span.start);
+    let depth = line_indent(source, instance.span.start).len().saturating_sub(indent.len());
+    let body = dedent(body_span.text(source), depth);
+    let line = definition_line(source, &line_index, definition.span.start);
+    let position = Position::new(line, 0);
+
+    [false, true]
+      .into_iter()
+      .map(|is_pub| {
+        let visibility = if is_pub { "pub " } else { "" };
+        let text = format!(
+          "{}{}{} = <{}> {}\n\n",
+          indent, visibility, name, instance.widget_type, body
+        );
+        let changes = HashMap::from([(workspace_url.clone(), vec![
+          TextEdit::new(Range::new(position, position), text),
+          TextEdit::new(line_index.range(type_span), name.clone()),
+          TextEdit::new(line_index.range(body_span), "{}".to_string()),
+        ])]);
+        CodeActionOrCommand::CodeAction(CodeAction {
+          title: format!("Extract `<{}>` into `{}{}`", instance.widget_type, visibility, name),
+          kind: Some(CodeActionKind::REFACTOR_EXTRACT),
+          edit: Some(WorkspaceEdit::new(changes)),
+          ..CodeAction::default()
+        })
+      })
+      .collect()
+  }
+}
+
+/// The innermost `<Type> { ... }` instance nested in `definition` whose name or type the
+/// `selection` starts on and which contains its end. `<Type> {}` has nothing worth extracting.
+fn extracted_instance(definition: &WidgetNode, selection: Span) -> Option<&WidgetNode> {
+  let mut found = None;
+  for node in definition.nested() {
+    node.walk(&mut |node| {
+      let Some(body_span) = node.body_span.filter(|_| node.kind == WidgetKind::Instance) else {
+        return;
+      };
+      if node.properties.is_empty() && node.children.is_empty() && node.shader_fns.is_empty() {
+        return;
+      }
+      let header = Span::new(node.span.start, body_span.start);
+      if header.contains(selection.start) && node.span.contains(selection.end) {
+        found = Some(node);
+      }
+    });
+  }
+  found
+}
+
+/// The line to insert a definition above the one starting at `offset`, keeping the comment
+/// lines right above it attached to it.
+fn definition_line(source: &str, line_index: &LineIndex, offset: usize) -> u32 {
+  let mut line = line_index.position(offset).line;
+  let lines: Vec<&str> = source.lines().collect();
+  while line > 0 && lines[line as usize - 1].trim_start().starts_with("//") {
+    line -= 1;
+  }
+  line
+}
+
+/// The whitespace starting the line containing `offset`.
+fn line_indent(source: &str, offset: usize) -> String {
+  let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+  source[line_start..].chars().take_while(|c| *c == ' ' || *c == '\t').collect()
+}
+
+/// Removes up to `depth` leading whitespace characters from every line but the first.
+fn dedent(text: &str, depth: usize) -> String {
+  text
+    .split('\n')
+    .enumerate()
+    .map(|(index, line)| match index {
+      0 => line,
+      _ => {
+        let whitespace = line.len() - line.trim_start().len();
+        &line[whitespace.min(depth)..]
+      }
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// `home_screen_view` to `HomeScreenView`.
+fn pascal_case(name: &str) -> String {
+  name
+    .split('_')
+    .map(|word| {
+      let mut chars = word.chars();
+      chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+    })
+    .collect()
+}
+
+/// Where a `use` goes in the block containing `offset`: on the line after its last import,
+/// or before its first declaration followed by a blank line. Returns the position, the
+/// indentation and the text to put after the new line.
+fn import_insertion(
+  source: &str,
+  document: &ParsedDocument,
+  offset: usize,
+) -> Option<(Position, String, &'static str)> {
+  let line_index = LineIndex::new(source);
+  let block = document.block_at(offset)?;
+  let indent_of = |offset: usize| -> String {
+    let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+    source[line_start..offset].chars().take_while(|c| c.is_whitespace()).collect()
+  };
+  match block.imports().last() {
+    Some(import) => {
+      let line = line_index.position(import.span.end).line + 1;
+      Some((Position::new(line, 0), indent_of(import.span.start), ""))
+    }
+    None => {
+      let first = block.nodes.first()?.span().start;
+      let line = line_index.position(first).line;
+      Some((Position::new(line, 0), indent_of(first), "\n"))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use lsp_types::{DiagnosticSeverity, TextDocumentContentChangeEvent};
+
+  use super::*;
+  use crate::capabilities::tests::{fixture_session, position_of};
+
+  fn replace_text(session: &Session, documents: &Documents, uri: &Url, from: &str, to: &str) {
+    let text = documents.get_text_document(uri).unwrap().get_text().replacen(from, to, 1);
+    let change = TextDocumentContentChangeEvent { range: None, range_length: None, text };
+    documents.update_text_document(uri, &[change]).unwrap();
+    session.update_token_map(uri, documents).unwrap();
+  }
+
+  fn edits(action: &CodeActionOrCommand) -> Vec<TextEdit> {
+    let CodeActionOrCommand::CodeAction(action) = action else {
+      panic!("expected a code action");
+    };
+    let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+    changes.values().next().unwrap().clone()
+  }
+
+  /// The text of the document at `uri` with the edits of `action` applied.
+  fn apply(documents: &Documents, uri: &Url, action: &CodeActionOrCommand) -> String {
+    let mut text = documents.get_text_document(uri).unwrap().get_text().to_string();
+    let mut spans: Vec<(Span, String)> = {
+      let line_index = LineIndex::new(&text);
+      edits(action).into_iter().map(|edit| (line_index.span(edit.range), edit.new_text)).collect()
+    };
+    spans.sort_by_key(|(span, _)| std::cmp::Reverse(*span));
+    for (span, new_text) in spans {
+      text.replace_range(span.start..span.end, &new_text);
+    }
+    text
+  }
+
+  fn titles(actions: Vec<CodeActionOrCommand>) -> Vec<String> {
+    actions
+      .into_iter()
+      .map(|action| match action {
+        CodeActionOrCommand::CodeAction(action) => action.title,
+        CodeActionOrCommand::Command(command) => command.title,
+      })
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn test_missing_import_quick_fix() {
+    let (session, documents, uri) = fixture_session("src/app.rs").await;
+    let everything = Range::new(Position::new(0, 0), Position::new(u32::MAX, 0));
+    assert!(session.code_actions(&uri, everything, &documents).unwrap().is_empty());
+
+    replace_text(&session, &documents, &uri, "    use crate::shared::popup_list::PopupList;\n", "");
+    let diagnostics = session.diagnostics(&uri, &documents).unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "`PopupList` is not imported");
+    assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+
+    let actions = session.code_actions(&uri, diagnostics[0].range, &documents).unwrap();
+    assert_eq!(actions.len(), 1);
+    assert_eq!(edits(&actions[0]), vec![TextEdit::new(
+      Range::new(Position::new(9, 0), Position::new(9, 0)),
+      "    use crate::shared::popup_list::PopupList;\n".to_string(),
+    )]);
+  }
+
+  #[tokio::test]
+  async fn test_ambiguous_import_offers_each_candidate() {
+    let (session, documents, uri) = fixture_session("src/app.rs").await;
+    replace_text(&session, &documents, &uri, "    use crate::shared::popup_list::PopupList;\n", "");
+    let home_mod = Url::from_file_path(session.src_dir().unwrap().join("home/mod.rs")).unwrap();
+    replace_text(&session, &documents, &home_mod, "", "live_design! {\n    pub PopupList = <View> {}\n}\n");
+
+    let everything = Range::new(Position::new(0, 0), Position::new(u32::MAX, 0));
+    let titles = titles(session.code_actions(&uri, everything, &documents).unwrap());
+    assert_eq!(titles, vec![
+      "Import `crate::home::PopupList`",
+      "Import `crate::shared::popup_list::PopupList`",
+    ]);
+  }
+
+  #[tokio::test]
+  async fn test_missing_imports_skip_private_and_framework_names() {
+    let (session, documents, uri) = fixture_session("src/app.rs").await;
+    replace_text(&session, &documents, &uri, "    use crate::shared::popup_list::PopupList;\n", "");
+    let home_mod = Url::from_file_path(session.src_dir().unwrap().join("home/mod.rs")).unwrap();
+    let declarations = concat!(
+      "live_design! {\n",
+      "    PopupList = <View> {}\n",
+      "    pub RadioButton = <View> {}\n",
+      "}\n",
+    );
+    replace_text(&session, &documents, &home_mod, "", declarations);
+
+    // The private `PopupList` can't be imported, and `<RadioButton>` comes from
+    // `use link::widgets::*`.
+    let diagnostics = session.diagnostics(&uri, &documents).unwrap();
+    let messages: Vec<&str> =
+      diagnostics.iter().map(|diagnostic| diagnostic.message.as_str()).collect();
+    assert_eq!(messages, vec!["`PopupList` is not imported"]);
+    let everything = Range::new(Position::new(0, 0), Position::new(u32::MAX, 0));
+    let titles = titles(session.code_actions(&uri, everything, &documents).unwrap());
+    assert_eq!(titles, vec!["Import `crate::shared::popup_list::PopupList`"]);
+  }
+
+  #[tokio::test]
+  async fn test_extract_widget_definition() {
+    let (session, documents, uri) = fixture_session("src/app.rs").await;
+    let cursor = position_of(&documents, &uri, "home_screen_view");
+    let actions = session.code_actions(&uri, Range::new(cursor, cursor), &documents).unwrap();
+    assert_eq!(titles(actions.clone()), vec![
+      "Extract `<View>` into `HomeScreenView`",
+      "Extract `<View>` into `pub HomeScreenView`",
+    ]);
+    let text = apply(&documents, &uri, &actions[1]);
+    assert!(text.contains(concat!(
+      "    pub HomeScreenView = <View> {\n",
+      "        visible: true\n",
+      "        home_screen = <HomeScreen> {}\n",
+      "    }\n",
+      "\n",
+      "    App = {{App}} {\n",
+    )));
+    assert!(text.contains("home_screen_view = <HomeScreenView> {}\n"));
+
+    // Anonymous instances are named after their type.
+    let start = position_of(&documents, &uri, "<View> {\n                    width: Fill");
+    let end = position_of(&documents, &uri, "flow: Overlay");
+    let actions = session.code_actions(&uri, Range::new(start, end), &documents).unwrap();
+    assert_eq!(titles(actions)[0], "Extract `<View>` into `MyView`");
+
+    // Neither top-level definitions nor plain objects are extracted.
+    let cursor = position_of(&documents, &uri, "AppTab = ");
+    assert!(session.code_actions(&uri, Range::new(cursor, cursor), &documents).unwrap().is_empty());
+    let cursor = position_of(&documents, &uri, "body = {");
+    assert!(session.code_actions(&uri, Range::new(cursor, cursor), &documents).unwrap().is_empty());
+  }
+}
@@ -0,0 +1,206 @@
+use lsp_types::{Color, ColorInformation, ColorPresentation, Range, TextEdit};
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{
+  parse_document, LineIndex, Punct, Rgba, Span, Token, TokenKind,
+};
+use url::Url;
+
+use crate::Session;
+
+impl Session {
+  /// Finds the `#hex` literals and literal `vec4(r, g, b, a)` constructors of the document at
+  /// the temp `uri`.
+  pub fn document_colors(
+    &self,
+    uri: &Url,
+    documents: &Documents,
+  ) -> Result<Vec<ColorInformation>, DocumentError> {
+    let document = documents.get_text_document(uri)?;
+    Ok(document_colors(document.get_text()))
+  }
+
+  /// Offers the literals that can replace the color at `range`, keeping its current notation
+  /// first so that picking a color doesn't turn a `vec4` into a hex literal or vice versa.
+  pub fn color_presentations(
+    &self,
+    uri: &Url,
+    color: Color,
+    range: Range,
+    documents: &Documents,
+  ) -> Result<Vec<ColorPresentation>, DocumentError> {
+    let document = documents.get_text_document(uri)?;
+    let source = document.get_text();
+    let span = LineIndex::new(source).span(range);
+    let is_vec4 = span.text(source).trim_start().starts_with("vec4");
+    Ok(color_presentations(color, range, is_vec4))
+  }
+}
+
+pub fn document_colors(source: &str) -> Vec<ColorInformation> {
+  let line_index = LineIndex::new(source);
+  let mut colors = vec![];
+  for block in parse_document(source).blocks {
+    let tokens: Vec<&Token> = block.tokens.iter().filter(|token| !token.is_trivia()).collect();
+    for (index, token) in tokens.iter().enumerate() {
+      let found = match &token.kind {
+        TokenKind::Color(digits) => Rgba::from_hex(digits).map(|color| (token.span, color)),
+        TokenKind::Ident(ident) if ident == "vec4" => vec4_color(&tokens[index..]),
+        _ => None,
+      };
+      if let Some((span, color)) = found {
+        colors.push(ColorInformation {
+          range: line_index.range(span),
+          color: to_lsp_color(color),
+        });
+      }
+    }
+  }
+  colors
+}
+
+/// Reads `vec4(r, g, b, a)` with four literal numbers from the start of `tokens`.
+fn vec4_color(tokens: &[&Token]) -> Option<(Span, Rgba)> {
+  let mut channels = [0.0; 4];
+  let mut rest = tokens.get(1..)?.iter();
+  if !rest.next()?.is_punct(Punct::LParen) {
+    return None;
+  }
+  for (index, channel) in channels.iter_mut().enumerate() {
+    if index > 0 && !rest.next()?.is_punct(Punct::Comma) {
+      return None;
+    }
+    match &rest.next()?.kind {
+      TokenKind::Number { value, unit: None } => *channel = *value as f32,
+      _ => return None,
+    }
+  }
+  let close = rest.next()?;
+  if !close.is_punct(Punct::RParen) {
+    return None;
+  }
+  let [r, g, b, a] = channels;
+  Some((tokens[0].span.join(close.span), Rgba::from_f32(r, g, b, a)))
+}
+
+pub fn color_presentations(color: Color, range: Range, is_vec4: bool) -> Vec<ColorPresentation> {
+  let rgba = Rgba::from_f32(color.red, color.green, color.blue, color.alpha);
+  let mut labels = vec![color_literal(rgba.to_short_hex()), color_literal(rgba.to_hex())];
+  labels.dedup();
+  let vec4 = format!(
+    "vec4({}, {}, {}, {})",
+    format_channel(color.red),
+    format_channel(color.green),
+    format_channel(color.blue),
+    format_channel(color.alpha)
+  );
+  if is_vec4 {
+    labels.insert(0, vec4);
+  } else {
+    labels.push(vec4);
+  }
+
+  labels
+    .into_iter()
+    .map(|label| ColorPresentation {
+      text_edit: Some(TextEdit::new(range, label.clone())),
+      label,
+      additional_text_edits: None,
+    })
+    .collect()
+}
+
+/// Adds the `x` of `#x` to a `#` literal whose digits Rust would read as a float missing its
+/// exponent, such as `#2e` or `#3eff00`: decimal digits, then `e`, then no digit.
+fn color_literal(hex: String) -> String {
+  let digits = hex.trim_start_matches('#');
+  let after_number = digits.trim_start_matches(|c: char| c.is_ascii_digit());
+  let exponent = match after_number.strip_prefix(['e', 'E']) {
+    Some(exponent) if after_number.len() < digits.len() => exponent,
+    _ => return hex,
+  };
+  if exponent.starts_with(|c: char| c.is_ascii_digit()) {
+    return hex;
+  }
+  format!("#x{}", digits)
+}
+
+fn to_lsp_color(color: Rgba) -> Color {
+  let [red, green, blue, alpha] = color.to_f32();
+  Color { red, green, blue, alpha }
+}
+
+/// `0.204` or `1.0`: three decimals at most, always with a decimal point.
+fn format_channel(channel: f32) -> String {
+  let formatted = format!("{:.3}", channel.clamp(0.0, 1.0));
+  let trimmed = formatted.trim_end_matches('0');
+  if trimmed.ends_with('.') {
+    format!("{}0", trimmed)
+  } else {
+    trimmed.to_string()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use lsp_types::Position;
+
+  use super::*;
+
+  #[test]
+  fn test_document_colors() {
+    let source = r#"live_design! {
+    A = #344054
+    B = { pass: {clear_color: #2A} }
+    fn pixel(self) -> vec4 { return mix(#0000, vec4(1.0, 0.0, 0.5, 1), self.x); }
+    C = vec4(self.x, 0, 0, 1)
+}"#;
+    let colors = document_colors(source);
+    let found: Vec<(&str, Rgba)> = colors
+      .iter()
+      .map(|info| {
+        let span = LineIndex::new(source).span(info.range);
+        let c = info.color;
+        (span.text(source), Rgba::from_f32(c.red, c.green, c.blue, c.alpha))
+      })
+      .collect();
+    assert_eq!(found, vec![
+      ("#344054", Rgba::new(0x34, 0x40, 0x54, 255)),
+      ("#2A", Rgba::new(0x2a, 0x2a, 0x2a, 255)),
+      ("#0000", Rgba::new(0, 0, 0, 0)),
+      ("vec4(1.0, 0.0, 0.5, 1)", Rgba::new(255, 0, 128, 255)),
+    ]);
+  }
+
+  #[test]
+  fn test_color_presentations() {
+    let range = Range::new(Position::new(1, 8), Position::new(1, 11));
+    let color = to_lsp_color(Rgba::new(0x00, 0x99, 0x11, 255));
+
+    let labels: Vec<String> = color_presentations(color, range, false)
+      .into_iter()
+      .map(|presentation| presentation.label)
+      .collect();
+    assert_eq!(labels, vec!["#091", "#009911", "vec4(0.0, 0.6, 0.067, 1.0)"]);
+
+    let presentations = color_presentations(color, range, true);
+    assert_eq!(presentations[0].label, "vec4(0.0, 0.6, 0.067, 1.0)");
+    assert_eq!(presentations[0].text_edit.as_ref().unwrap().range, range);
+
+    let transparent = to_lsp_color(Rgba::new(0, 0, 0, 0));
+    assert_eq!(color_presentations(transparent, range, false)[0].label, "#0000");
+
+    let labels = |color: Rgba| -> Vec<String> {
+      let presentations = color_presentations(to_lsp_color(color), range, false);
+      presentations.into_iter().map(|presentation| presentation.label).collect()
+    };
+    let gray = Rgba::from_hex("2e2e2e").unwrap();
+    assert_eq!(labels(gray), vec!["#x2e", "#2e2e2e", "vec4(0.18, 0.18, 0.18, 1.0)"]);
+    let blue = Rgba::from_hex("1122ee").unwrap();
+    assert_eq!(labels(blue), vec!["#x12e", "#x1122ee", "vec4(0.067, 0.133, 0.933, 1.0)"]);
+    // `0f88fe` and `636e82` never read as a float missing its exponent.
+    assert_eq!(labels(Rgba::from_hex("0f88fe").unwrap())[0], "#0f88fe");
+    assert_eq!(labels(Rgba::from_hex("636e82").unwrap())[0], "#636e82");
+    assert_eq!(labels(Rgba::from_hex("33eeff").unwrap())[..2], ["#x3ef", "#x33eeff"]);
+  }
+}
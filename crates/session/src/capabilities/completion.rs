@@ -0,0 +1,472 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use lsp_types::{
+  CompletionItem, CompletionItemKind, Documentation, MarkupContent, MarkupKind, Position,
+};
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{
+  parse_document, LineIndex, ParsedDocument, TokenAstNode, WidgetNode,
+};
+use makepad_analyzer_plugin_shader::field_completion_items;
+use url::Url;
+
+use crate::{
+  builtins::{BUILTIN_PROPERTIES, FRAMEWORK_WIDGETS, LINK_MODULES},
+  resolve::{framework_module, module_file},
+  shader::shader_fn_at,
+  Session,
+};
+
+/// What the text before the cursor asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompletionContext {
+  /// `<Vi`
+  WidgetType,
+  /// `(APP_`
+  Constant,
+  /// `use link::widgets::`, with the segments typed after `link`.
+  LinkPath(Vec<String>),
+  /// `use crate::home::`, with the segments typed after `crate`.
+  CratePath(Vec<String>),
+  /// `dep("crate://self/resources/`, with the path typed after `crate://`.
+  Dependency(String),
+  /// The start of a line or an entry, where a property, child or declaration goes.
+  Entry,
+  /// `self.ho` in a shader `fn`.
+  ShaderField,
+}
+
+impl Session {
+  /// Completes the `live_design!` code at `position` in the document at the temp `uri`.
+  /// Returns `None` outside of `live_design!` blocks.
+  pub fn completion_items(
+    &self,
+    uri: &Url,
+    position: Position,
+    documents: &Documents,
+  ) -> Result<Option<Vec<CompletionItem>>, DocumentError> {
+    let text_document = documents.get_text_document(uri)?;
+    let source = text_document.get_text();
+    let offset = LineIndex::new(source).offset(position);
+    let document = parse_document(source);
+    if document.block_at(offset).is_none() {
+      return Ok(None);
+    }
+
+    let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let Some(context) = completion_context(&source[line_start..offset]) else {
+      return Ok(None);
+    };
+    let path = Path::new(uri.path());
+    let items = match context {
+      CompletionContext::WidgetType => self.widget_type_items(&document),
+      CompletionContext::Constant => self.constant_items(path, &document),
+      CompletionContext::LinkPath(segments) => link_path_items(&segments),
+      CompletionContext::CratePath(segments) => self.crate_path_items(&segments, documents),
+      CompletionContext::Dependency(partial) => self.dependency_items(&partial),
+      CompletionContext::Entry => match widget_body_at(&document, offset) {
+        Some(widget) => self.property_items(path, &document, widget, documents),
+        None => keyword_items(),
+      },
+      CompletionContext::ShaderField => match shader_fn_at(&document, offset) {
+        Some((object, _)) => {
+          field_completion_items(&self.shader_scope(path, &document, object, documents))
+        }
+        None => vec![],
+      },
+    };
+    Ok(Some(items))
+  }
+
+  /// The widget definitions of the package and the framework widgets `document` imports.
+  fn widget_type_items(&self, document: &ParsedDocument) -> Vec<CompletionItem> {
+    let mut items: Vec<CompletionItem> = self
+      .token_map()
+      .iter()
+      .filter(|entry| matches!(entry.value().ast_node, TokenAstNode::WidgetDefinition(_)))
+      .map(|entry| {
+        let ident = entry.key();
+        let detail = ident.path.as_deref().map(|path| self.relative_path(path));
+        item(&ident.name, CompletionItemKind::CLASS, detail, None)
+      })
+      .collect();
+    items.extend(
+      FRAMEWORK_WIDGETS
+        .iter()
+        .filter_map(|(name, doc)| {
+          let module = framework_module(document, name)?;
+          Some(item(name, CompletionItemKind::CLASS, Some(module), Some(doc)))
+        }),
+    );
+    dedup_items(items)
+  }
+
+  /// The constants declared in `document` or imported into it from the package.
+  fn constant_items(&self, path: &Path, document: &ParsedDocument) -> Vec<CompletionItem> {
+    let globs: Vec<_> = document
+      .imports()
+      .filter(|import| import.is_glob)
+      .filter_map(|import| self.module_file(import.module_segments()))
+      .collect();
+    let named: Vec<_> = document
+      .imports()
+      .filter_map(|import| {
+        let name = import.imported_name()?.name.clone();
+        Some((name, self.module_file(import.module_segments())?))
+      })
+      .collect();
+
+    let items = self
+      .token_map()
+      .iter()
+      .filter(|entry| matches!(entry.value().ast_node, TokenAstNode::Constant(_)))
+      .filter_map(|entry| {
+        let ident = entry.key();
+        let file = ident.path.as_deref()?;
+        let in_scope = file == path
+          || globs.iter().any(|glob| glob == file)
+          || named.iter().any(|(name, named_file)| *name == ident.name && named_file == file);
+        in_scope.then(|| {
+          item(&ident.name, CompletionItemKind::CONSTANT, Some(self.relative_path(file)), None)
+        })
+      })
+      .collect();
+    dedup_items(items)
+  }
+
+  /// The modules of `crate::<segments>`, then the declarations of its file.
+  fn crate_path_items(&self, segments: &[String], documents: &Documents) -> Vec<CompletionItem> {
+    let Some(src_dir) = self.src_dir() else {
+      return vec![];
+    };
+    let mut items = vec![];
+    let dir = segments.iter().fold(src_dir.clone(), |dir, segment| dir.join(segment));
+    for entry in read_dir_sorted(&dir) {
+      let path = entry.as_path();
+      let name = if path.is_dir() && path.join("mod.rs").is_file() {
+        path.file_name()
+      } else if path.extension().is_some_and(|extension| extension == "rs") {
+        path.file_stem()
+      } else {
+        None
+      };
+      let Some(name) = name.map(|name| name.to_string_lossy().to_string()) else {
+        continue;
+      };
+      let is_root = segments.is_empty() && (name == "lib" || name == "main");
+      if name != "mod" && !is_root {
+        items.push(item(&name, CompletionItemKind::MODULE, None, None));
+      }
+    }
+
+    let names: Vec<&str> = segments.iter().map(String::as_str).collect();
+    let module = module_file(&src_dir, &names).filter(|_| !segments.is_empty());
+    if let Some((_, document)) = module.and_then(|file| self.parse_file(&file, documents)) {
+      items.push(item("*", CompletionItemKind::MODULE, None, None));
+      for widget in document.widgets().filter(|widget| widget.is_pub) {
+        items.push(item(&widget.name, CompletionItemKind::CLASS, None, None));
+      }
+      for constant in document.constants().filter(|constant| constant.is_pub) {
+        items.push(item(&constant.name, CompletionItemKind::CONSTANT, None, None));
+      }
+    }
+    items
+  }
+
+  /// The crates `crate://` can point into, then the files and folders under
+  /// `crate://<name>/<partial>`. Files are read from disk so that resources added since the
+  /// session started show up.
+  fn dependency_items(&self, partial: &str) -> Vec<CompletionItem> {
+    let Some((name, path)) = partial.split_once('/') else {
+      let mut names = vec!["self".to_string()];
+      if let Some(dirs) = self.dependency_dirs() {
+        names.extend(dirs.keys().cloned());
+        names[1..].sort();
+      }
+      return names
+        .iter()
+        .map(|name| {
+          let mut folder = item(name, CompletionItemKind::FOLDER, None, None);
+          folder.insert_text = Some(format!("{}/", name));
+          folder
+        })
+        .collect();
+    };
+    let Some(root) = self.dependency_root(name) else {
+      return vec![];
+    };
+    let dir = match path.rfind('/') {
+      Some(index) => root.join(&path[..index]),
+      None => root,
+    };
+    read_dir_sorted(&dir)
+      .into_iter()
+      .filter_map(|path| {
+        let name = path.file_name()?.to_string_lossy().to_string();
+        if name.starts_with('.') || name == "target" {
+          return None;
+        }
+        if path.is_dir() {
+          let mut folder = item(&name, CompletionItemKind::FOLDER, None, None);
+          folder.insert_text = Some(format!("{}/", name));
+          Some(folder)
+        } else {
+          Some(item(&name, CompletionItemKind::FILE, None, None))
+        }
+      })
+      .collect()
+  }
+
+  /// The framework properties and the ones `widget` inherits from its definitions, leaving
+  /// out the properties its body already sets.
+  fn property_items(
+    &self,
+    path: &Path,
+    document: &ParsedDocument,
+    widget: &WidgetNode,
+    documents: &Documents,
+  ) -> Vec<CompletionItem> {
+    let set: HashSet<&str> = widget.properties.iter().map(|property| property.name.as_str()).collect();
+    let mut items = vec![];
+    for (base, properties) in self.inherited_properties(path, document, widget, documents) {
+      for name in properties {
+        let detail = format!("from {}", base);
+        items.push(item(&name, CompletionItemKind::PROPERTY, Some(detail), None));
+      }
+    }
+    for property in BUILTIN_PROPERTIES {
+      items.push(item(
+        property.name,
+        CompletionItemKind::PROPERTY,
+        Some(property.ty.to_string()),
+        Some(property.doc),
+      ));
+    }
+    items.retain(|item| !set.contains(item.label.as_str()));
+    dedup_items(items)
+  }
+
+  /// The properties set by each definition `widget` derives from, nearest first.
+  fn inherited_properties(
+    &self,
+    path: &Path,
+    document: &ParsedDocument,
+    widget: &WidgetNode,
+    documents: &Documents,
+  ) -> Vec<(String, Vec<String>)> {
+    self
+      .base_definitions(path, document, widget, documents)
+      .into_iter()
+      .map(|(_, base)| {
+        let properties = base.properties.iter().map(|property| property.name.clone()).collect();
+        (base.name, properties)
+      })
+      .collect()
+  }
+}
+
+/// Classifies the text of the line before the cursor.
+pub fn completion_context(line_prefix: &str) -> Option<CompletionContext> {
+  if let Some(index) = line_prefix.rfind("dep(\"crate://") {
+    let partial = &line_prefix[index + "dep(\"crate://".len()..];
+    if !partial.contains('"') {
+      return Some(CompletionContext::Dependency(partial.to_string()));
+    }
+  }
+
+  let trimmed = line_prefix.trim_start();
+  let use_path = trimmed.strip_prefix("pub use ").or_else(|| trimmed.strip_prefix("use "));
+  if let Some(use_path) = use_path {
+    let mut segments: Vec<String> = use_path.trim_start().split("::").map(str::to_string).collect();
+    // The last segment is the one being typed.
+    segments.pop();
+    if segments.is_empty() {
+      return None;
+    }
+    let root = segments.remove(0);
+    return match root.as_str() {
+      "link" => Some(CompletionContext::LinkPath(segments)),
+      "crate" => Some(CompletionContext::CratePath(segments)),
+      _ => None,
+    };
+  }
+
+  let word_start = line_prefix
+    .rfind(|c: char| !(c == '_' || c.is_alphanumeric()))
+    .map_or(0, |index| index + 1);
+  if let Some(before) = line_prefix[..word_start].strip_suffix("self.") {
+    if !before.ends_with(|c: char| c == '_' || c.is_alphanumeric()) {
+      return Some(CompletionContext::ShaderField);
+    }
+  }
+  let before = line_prefix[..word_start].trim_end();
+  match before.chars().next_back() {
+    Some('<') => Some(CompletionContext::WidgetType),
+    Some('(') => Some(CompletionContext::Constant),
+    None | Some('{') | Some(',') => Some(CompletionContext::Entry),
+    _ => None,
+  }
+}
+
+/// The innermost widget or object whose body contains `offset`, unless the offset is inside
+/// one of its shader functions.
+fn widget_body_at(document: &ParsedDocument, offset: usize) -> Option<&WidgetNode> {
+  let mut innermost: Option<&WidgetNode> = None;
+  for widget in document.widgets() {
+    widget.walk(&mut |node| {
+      let Some(body) = node.body_span else {
+        return;
+      };
+      if body.start < offset && offset < body.end {
+        innermost = Some(node);
+      }
+    });
+  }
+  let widget = innermost?;
+  let in_shader_fn = widget.shader_fns.iter().any(|shader_fn| shader_fn.body_span.contains(offset));
+  (!in_shader_fn).then_some(widget)
+}
+
+fn link_path_items(segments: &[String]) -> Vec<CompletionItem> {
+  let mut items = vec![];
+  match segments {
+    [] => {
+      for (name, doc) in LINK_MODULES {
+        items.push(item(name, CompletionItemKind::MODULE, None, Some(doc)));
+      }
+    }
+    [module] => {
+      items.push(item("*", CompletionItemKind::MODULE, None, None));
+      if module == "widgets" {
+        for (name, doc) in FRAMEWORK_WIDGETS {
+          items.push(item(name, CompletionItemKind::CLASS, None, Some(doc)));
+        }
+      }
+    }
+    _ => {}
+  }
+  items
+}
+
+/// The keywords that start a top-level declaration.
+fn keyword_items() -> Vec<CompletionItem> {
+  ["use", "pub"]
+    .iter()
+    .map(|keyword| item(keyword, CompletionItemKind::KEYWORD, None, None))
+    .collect()
+}
+
+fn item(
+  label: &str,
+  kind: CompletionItemKind,
+  detail: Option<String>,
+  doc: Option<&str>,
+) -> CompletionItem {
+  CompletionItem {
+    label: label.to_string(),
+    kind: Some(kind),
+    detail,
+    documentation: doc.map(|doc| {
+      Documentation::MarkupContent(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: doc.to_string(),
+      })
+    }),
+    ..CompletionItem::default()
+  }
+}
+
+/// Keeps the first item of each label.
+fn dedup_items(items: Vec<CompletionItem>) -> Vec<CompletionItem> {
+  let mut seen = HashSet::new();
+  items.into_iter().filter(|item| seen.insert(item.label.clone())).collect()
+}
+
+fn read_dir_sorted(dir: &Path) -> Vec<std::path::PathBuf> {
+  let mut paths: Vec<_> = fs::read_dir(dir)
+    .map(|entries| entries.filter_map(Result::ok).map(|entry| entry.path()).collect())
+    .unwrap_or_default();
+  paths.sort();
+  paths
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::capabilities::tests::{fixture_session, position_of};
+
+  fn labels(items: &[CompletionItem]) -> Vec<&str> {
+    items.iter().map(|item| item.label.as_str()).collect()
+  }
+
+  async fn complete_after(file: &str, needle: &str) -> Vec<CompletionItem> {
+    let (session, documents, uri) = fixture_session(file).await;
+    let mut position = position_of(&documents, &uri, needle);
+    position.character += needle.len() as u32;
+    session.completion_items(&uri, position, &documents).unwrap().unwrap()
+  }
+
+  #[test]
+  fn test_completion_context() {
+    use CompletionContext::*;
+    assert_eq!(completion_context("    home = <Ap"), Some(WidgetType));
+    assert_eq!(completion_context("    color: ( "), Some(Constant));
+    assert_eq!(completion_context("    use link::"), Some(LinkPath(vec![])));
+    assert_eq!(completion_context("    use link::widgets::Vi"), Some(LinkPath(vec!["widgets".into()])));
+    assert_eq!(completion_context("    use crate::home::"), Some(CratePath(vec!["home".into()])));
+    assert_eq!(
+      completion_context("    ICON = dep(\"crate://self/resources/ic"),
+      Some(Dependency("self/resources/ic".into()))
+    );
+    assert_eq!(completion_context("        wid"), Some(Entry));
+    assert_eq!(completion_context("    width: Fill, hei"), Some(Entry));
+    assert_eq!(completion_context("    width: Fi"), None);
+    assert_eq!(completion_context("        return self.ho"), Some(ShaderField));
+    assert_eq!(completion_context("        let c = self."), Some(ShaderField));
+    assert_eq!(completion_context("        return myself.ho"), None);
+    assert_eq!(completion_context("    use lin"), None);
+  }
+
+  #[tokio::test]
+  async fn test_complete_widget_types_and_constants() {
+    let items = complete_after("src/home/home_screen.rs", "home_tab = <").await;
+    let widgets = labels(&items);
+    assert!(widgets.contains(&"AppTab"));
+    assert!(widgets.contains(&"HomeScreen"));
+    assert!(widgets.contains(&"View"));
+
+    let items = complete_after("src/home/home_screen.rs", "color: (").await;
+    let mut constants = labels(&items);
+    constants.sort();
+    assert_eq!(constants, vec!["COLOR_DIVIDER", "COLOR_PRIMARY"]);
+  }
+
+  #[tokio::test]
+  async fn test_complete_module_paths_and_resources() {
+    let items = complete_after("src/home/home_screen.rs", "use crate::").await;
+    assert_eq!(labels(&items), vec!["app", "home", "shared"]);
+
+    let items = complete_after("src/app.rs", "use crate::home::home_screen::").await;
+    assert_eq!(labels(&items), vec!["*", "HomeScreen"]);
+
+    let items = complete_after("src/app.rs", "use link::").await;
+    assert_eq!(labels(&items), vec!["theme", "shaders", "widgets"]);
+
+    let items = complete_after("src/app.rs", "dep(\"crate://self/resources/").await;
+    assert_eq!(labels(&items), vec!["icons"]);
+    assert_eq!(items[0].insert_text.as_deref(), Some("icons/"));
+  }
+
+  #[tokio::test]
+  async fn test_complete_inherited_properties() {
+    let items = complete_after("src/app.rs", "chat_tab = <AppTab> {").await;
+    let labels = labels(&items);
+    assert!(labels.contains(&"draw_radio"));
+    assert!(labels.contains(&"width"));
+    // Already set in the body.
+    assert!(!labels.contains(&"icon_walk"));
+    let draw_radio = items.iter().find(|item| item.label == "draw_radio").unwrap();
+    assert_eq!(draw_radio.detail.as_deref(), Some("from AppTab"));
+  }
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use lsp_types::{GotoDefinitionResponse, Location, Position, Range};
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{parse_document, ReferenceKind};
+use url::Url;
+
+use crate::{resolve::{symbol_at, SymbolAt}, Session};
+
+impl Session {
+  /// Finds where the symbol at `position` in the document at the temp `uri` is declared.
+  ///
+  /// `<Widget>` and `(CONSTANT)` go to their `live_design!` declaration, `{{Type}}` to the
+  /// Rust struct, and the segments of a `use crate::...` path to the module file or the
+  /// declaration they import. Locations are workspace URLs.
+  pub fn goto_definition(
+    &self,
+    uri: &Url,
+    position: Position,
+    documents: &Documents,
+  ) -> Result<Option<GotoDefinitionResponse>, DocumentError> {
+    let text_document = documents.get_text_document(uri)?;
+    let source = text_document.get_text();
+    let document = parse_document(source);
+    let path = Path::new(uri.path());
+
+    let Some(symbol) = symbol_at(self, path, source, &document, position) else {
+      return Ok(None);
+    };
+
+    let locations: Vec<Location> = match &symbol {
+      SymbolAt::Reference(reference) if reference.kind == ReferenceKind::RustType => self
+        .find_rust_struct(path, &reference.name, documents)
+        .and_then(|(file, range)| Some(Location::new(self.workspace_url(&file)?, range)))
+        .into_iter()
+        .collect(),
+      SymbolAt::Reference(reference) => self
+        .resolve_name(path, &document, &reference.name)
+        .iter()
+        .filter_map(|(ident, _)| self.workspace_location(ident))
+        .collect(),
+      SymbolAt::ImportSegment(import, index) => {
+        let is_imported_name = !import.is_glob && *index == import.segments.len() - 1;
+        if is_imported_name {
+          self
+            .resolve_name(path, &document, symbol.name())
+            .iter()
+            .filter_map(|(ident, _)| self.workspace_location(ident))
+            .collect()
+        } else {
+          self
+            .module_file(&import.segments[..=*index])
+            .and_then(|file| self.workspace_url(&file))
+            .map(|url| Location::new(url, Range::default()))
+            .into_iter()
+            .collect()
+        }
+      }
+      SymbolAt::Declaration(..) => vec![],
+    };
+
+    Ok(match locations.len() {
+      0 => None,
+      1 => locations.into_iter().next().map(GotoDefinitionResponse::Scalar),
+      _ => Some(GotoDefinitionResponse::Array(locations)),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::capabilities::tests::{fixture_session, position_of};
+
+  use super::*;
+
+  async fn definition(file: &str, needle: &str, offset: u32) -> Location {
+    let (session, documents, uri) = fixture_session(file).await;
+    let mut position = position_of(&documents, &uri, needle);
+    position.character += offset;
+    match session.goto_definition(&uri, position, &documents).unwrap() {
+      Some(GotoDefinitionResponse::Scalar(location)) => location,
+      other => panic!("expected a single location, got {:?}", other),
+    }
+  }
+
+  #[tokio::test]
+  async fn test_goto_widget_definition_in_imported_file() {
+    let location = definition("src/app.rs", "<HomeScreen>", 2).await;
+    assert!(location.uri.path().ends_with("tests/fixtures/robrix/src/home/home_screen.rs"));
+    assert_eq!(location.range.start, Position::new(9, 8));
+  }
+
+  #[tokio::test]
+  async fn test_goto_constant_definition() {
+    let location = definition("src/app.rs", "(APP_TAB_COLOR)", 2).await;
+    assert!(location.uri.path().ends_with("tests/fixtures/robrix/src/app.rs"));
+    assert_eq!(location.range.start, Position::new(13, 4));
+  }
+
+  #[tokio::test]
+  async fn test_goto_rust_struct() {
+    let location = definition("src/app.rs", "{{App}}", 3).await;
+    assert!(location.uri.path().ends_with("tests/fixtures/robrix/src/app.rs"));
+    assert_eq!(location.range.start, Position::new(124, 11));
+  }
+
+  #[tokio::test]
+  async fn test_goto_import_path() {
+    let location = definition("src/app.rs", "home_screen::HomeScreen", 2).await;
+    assert!(location.uri.path().ends_with("tests/fixtures/robrix/src/home/home_screen.rs"));
+    assert_eq!(location.range, Range::default());
+
+    let location = definition("src/app.rs", "::HomeScreen;", 4).await;
+    assert_eq!(location.range.start, Position::new(9, 8));
+  }
+}
@@ -0,0 +1,318 @@
+use std::path::{Path, PathBuf};
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{
+  parse_document, Expression, LineIndex, ParsedDocument, Span, SyntaxError, TokenKind,
+};
+use makepad_analyzer_plugin_shader::{
+  check_shader_fn, check_signature, ShaderFn, ShaderScope, ShaderType,
+};
+use url::Url;
+
+use crate::{
+  resolve::{dependency_span, split_dependency, MissingImport},
+  shader::{shader_objects, ShaderObject},
+  Session,
+};
+
+pub const DIAGNOSTIC_SOURCE: &str = "makepad-analyzer";
+/// The code of the diagnostics the auto-import quick fix answers.
+pub const MISSING_IMPORT: &str = "missing-import";
+
+impl Session {
+  /// Returns the diagnostics for the document at the temp `uri`.
+  pub fn diagnostics(
+    &self,
+    uri: &Url,
+    documents: &Documents,
+  ) -> Result<Vec<Diagnostic>, DocumentError> {
+    let document = documents.get_text_document(uri)?;
+    let source = document.get_text();
+    let mut diagnostics = syntax_diagnostics(source);
+    let crates_known = self.dependency_dirs().is_some();
+    diagnostics.extend(dependency_diagnostics(source, crates_known, |name| {
+      self.dependency_root(name)
+    }));
+    diagnostics.extend(self.missing_import_diagnostics(Path::new(uri.path()), source));
+    let path = Path::new(uri.path());
+    diagnostics.extend(shader_diagnostics(source, |document, object| {
+      self.shader_scope(path, document, object, documents)
+    }));
+    Ok(diagnostics)
+  }
+
+  /// Reports the widgets and constants of the package used without an import.
+  pub fn missing_import_diagnostics(&self, path: &Path, source: &str) -> Vec<Diagnostic> {
+    let line_index = LineIndex::new(source);
+    self
+      .missing_imports(path, &parse_document(source))
+      .iter()
+      .map(|missing| missing_import_diagnostic(missing, &line_index))
+      .collect()
+  }
+}
+
+pub(crate) fn missing_import_diagnostic(missing: &MissingImport, line_index: &LineIndex) -> Diagnostic {
+  Diagnostic {
+    range: line_index.range(missing.reference.span),
+    severity: Some(DiagnosticSeverity::ERROR),
+    code: Some(NumberOrString::String(MISSING_IMPORT.to_string())),
+    source: Some(DIAGNOSTIC_SOURCE.to_string()),
+    message: format!("`{}` is not imported", missing.reference.name),
+    ..Default::default()
+  }
+}
+
+/// Converts the syntax errors of every `live_design!` block in `source` into diagnostics.
+pub fn syntax_diagnostics(source: &str) -> Vec<Diagnostic> {
+  let line_index = LineIndex::new(source);
+  parse_document(source)
+    .errors
+    .iter()
+    .map(|error| syntax_error_to_diagnostic(error, &line_index))
+    .collect()
+}
+
+/// Reports the `dep("crate://...")` paths that point at a missing file. `root` maps a crate
+/// name to the directory its paths start from; crates it can't find are reported as unknown
+/// when `crates_known`, that is when the package's dependencies could be read.
+pub fn dependency_diagnostics(
+  source: &str,
+  crates_known: bool,
+  root: impl Fn(&str) -> Option<PathBuf>,
+) -> Vec<Diagnostic> {
+  let line_index = LineIndex::new(source);
+  let mut diagnostics = vec![];
+  for block in parse_document(source).blocks {
+    for token in &block.tokens {
+      let TokenKind::Dep(dependency) = &token.kind else {
+        continue;
+      };
+      let Some((name, path)) = split_dependency(dependency) else {
+        continue;
+      };
+      let message = match root(name) {
+        Some(root) if root.join(path).is_file() => continue,
+        Some(_) => format!("`{}` does not exist", dependency),
+        None if crates_known => format!("unknown crate `{}` in `{}`", name, dependency),
+        None => continue,
+      };
+      diagnostics.push(Diagnostic {
+        range: line_index.range(dependency_span(source, token.span, dependency)),
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message,
+        ..Default::default()
+      });
+    }
+  }
+  diagnostics
+}
+
+/// Type checks the shader `fn`s of every `live_design!` block in `source` and the types of
+/// their `instance`, `uniform` and `varying` declarations. `scope` gives what `self.` reaches
+/// in the shader `fn`s of an object.
+pub fn shader_diagnostics(
+  source: &str,
+  scope: impl Fn(&ParsedDocument, ShaderObject) -> ShaderScope,
+) -> Vec<Diagnostic> {
+  let line_index = LineIndex::new(source);
+  let mut diagnostics = vec![];
+  let mut push = |span: Span, message: String, severity: DiagnosticSeverity| {
+    diagnostics.push(Diagnostic {
+      range: line_index.range(span),
+      severity: Some(severity),
+      source: Some(DIAGNOSTIC_SOURCE.to_string()),
+      message,
+      ..Default::default()
+    });
+  };
+
+  let document = parse_document(source);
+  for widget in document.widgets() {
+    widget.walk(&mut |node| {
+      for property in node.properties.iter().filter(|property| property.modifier.is_some()) {
+        if let Expression::Ident(name) = &property.value {
+          if ShaderType::from_name(name).is_none() {
+            let message = format!("unknown shader type `{}`", name);
+            push(property.value_span, message, DiagnosticSeverity::ERROR);
+          }
+        }
+      }
+    });
+  }
+  for object in shader_objects(&document) {
+    let scope = scope(&document, object);
+    for shader_fn in &object.object.shader_fns {
+      let shader_fn_source = ShaderFn {
+        name: &shader_fn.name,
+        params: &shader_fn.params,
+        return_type: shader_fn.return_type.as_deref(),
+        body: shader_fn.body_span.text(source),
+      };
+      if let Some(message) = check_signature(&shader_fn_source) {
+        push(shader_fn.name_span, message, DiagnosticSeverity::ERROR);
+      }
+      for diagnostic in check_shader_fn(&shader_fn_source, &scope) {
+        let start = shader_fn.body_span.start;
+        let span = Span::new(start + diagnostic.span.start, start + diagnostic.span.end);
+        let severity = match diagnostic.is_warning {
+          true => DiagnosticSeverity::WARNING,
+          false => DiagnosticSeverity::ERROR,
+        };
+        push(span, diagnostic.message, severity);
+      }
+    }
+  }
+  diagnostics
+}
+
+fn syntax_error_to_diagnostic(error: &SyntaxError, line_index: &LineIndex) -> Diagnostic {
+  Diagnostic {
+    range: line_index.range(error.span),
+    severity: Some(DiagnosticSeverity::ERROR),
+    source: Some(DIAGNOSTIC_SOURCE.to_string()),
+    message: error.message.clone(),
+    ..Default::default()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use lsp_types::{Position, Range};
+
+  use super::*;
+
+  #[test]
+  fn test_syntax_diagnostics() {
+    let source = [
+      "fn main() {}",
+      "live_design! {",
+      "  A = #12g4",
+      "  B = dep(\"crate://self/icon.svg",
+      "  C = <View> {",
+      "}",
+    ]
+    .join("\n");
+
+    let diagnostics = syntax_diagnostics(&source);
+    let messages: Vec<(&str, Range)> = diagnostics
+      .iter()
+      .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.range))
+      .collect();
+
+    assert_eq!(messages, vec![
+      (
+        "malformed color literal `#12g4`, expected hex digits",
+        Range::new(Position::new(2, 6), Position::new(2, 11)),
+      ),
+      (
+        "unterminated `dep(` string",
+        Range::new(Position::new(3, 6), Position::new(3, 32)),
+      ),
+      (
+        "expected `)` to close `dep(`",
+        Range::new(Position::new(3, 6), Position::new(3, 32)),
+      ),
+      (
+        "unclosed `live_design!` block, expected `}`",
+        Range::new(Position::new(1, 0), Position::new(1, 14)),
+      ),
+    ]);
+    assert!(diagnostics.iter().all(|d| d.severity == Some(DiagnosticSeverity::ERROR)));
+  }
+
+  #[test]
+  fn test_dependency_diagnostics() {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/robrix");
+    let source = [
+      "live_design! {",
+      "  A = dep(\"crate://self/resources/icons/chat.svg\")",
+      "  B = dep(\"crate://self/resources/icons/missing.svg\")",
+      "  C = dep(\"crate://makepad-widgets/resources/icon.svg\")",
+      "}",
+    ]
+    .join("\n");
+
+    let root = |name: &str| (name == "self").then(|| fixture.clone());
+    let diagnostics = dependency_diagnostics(&source, true, root);
+    let messages: Vec<(&str, Range)> = diagnostics
+      .iter()
+      .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.range))
+      .collect();
+    assert_eq!(messages, vec![
+      (
+        "`crate://self/resources/icons/missing.svg` does not exist",
+        Range::new(Position::new(2, 11), Position::new(2, 51)),
+      ),
+      (
+        "unknown crate `makepad-widgets` in `crate://makepad-widgets/resources/icon.svg`",
+        Range::new(Position::new(3, 11), Position::new(3, 53)),
+      ),
+    ]);
+
+    // Without the package's dependencies, other crates can't be told apart from unknown ones.
+    let diagnostics = dependency_diagnostics(&source, false, root);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].message.ends_with("does not exist"));
+  }
+
+  #[test]
+  fn test_shader_diagnostics() {
+    let source = [
+      "live_design! {",
+      "  Tab = <View> {",
+      "    draw_bg: {",
+      "      instance hover: 0.0",
+      "      instance glow: flaot",
+      "      fn pixel(self) -> vec3 {",
+      "        let size = vec2(1.0, 2.0) * vec4(self.hover);",
+      "        return mix(#f00, #0f0, self.hover + self.pressed);",
+      "      }",
+      "    }",
+      "  }",
+      "}",
+    ]
+    .join("\n");
+
+    let session = Session::new();
+    let documents = Documents::new();
+    let diagnostics = shader_diagnostics(&source, |document, object| {
+      session.shader_scope(Path::new("/tmp/app.rs"), document, object, &documents)
+    });
+    let warnings: Vec<&str> = diagnostics
+      .iter()
+      .filter(|diagnostic| diagnostic.severity == Some(DiagnosticSeverity::WARNING))
+      .map(|diagnostic| diagnostic.message.as_str())
+      .collect();
+    assert_eq!(warnings, vec!["no field `pressed` on `self`"]);
+    let messages: Vec<(String, Range)> = diagnostics
+      .into_iter()
+      .map(|diagnostic| (diagnostic.message, diagnostic.range))
+      .collect();
+    assert_eq!(messages, vec![
+      (
+        "unknown shader type `flaot`".to_string(),
+        Range::new(Position::new(4, 21), Position::new(4, 26)),
+      ),
+      (
+        "`fn pixel` must return `vec4`, found `vec3`".to_string(),
+        Range::new(Position::new(5, 9), Position::new(5, 14)),
+      ),
+      (
+        "mismatched types `vec2` and `vec4` in `*`".to_string(),
+        Range::new(Position::new(6, 19), Position::new(6, 52)),
+      ),
+      (
+        "no field `pressed` on `self`".to_string(),
+        Range::new(Position::new(7, 49), Position::new(7, 56)),
+      ),
+      (
+        "expected `vec3` to be returned, found `vec4`".to_string(),
+        Range::new(Position::new(7, 15), Position::new(7, 57)),
+      ),
+    ]);
+  }
+}
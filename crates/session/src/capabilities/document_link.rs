@@ -0,0 +1,90 @@
+use lsp_types::DocumentLink;
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{parse_document, LineIndex, Span, TokenKind};
+use url::Url;
+
+use crate::{resolve::dependency_span, Session};
+
+impl Session {
+  /// Links every `dep("crate://...")` string of the document at the temp `uri` to its resource
+  /// file, and every `use crate::...` path to the Rust module file it names.
+  pub fn document_links(
+    &self,
+    uri: &Url,
+    documents: &Documents,
+  ) -> Result<Vec<DocumentLink>, DocumentError> {
+    let document = documents.get_text_document(uri)?;
+    let source = document.get_text();
+    let line_index = LineIndex::new(source);
+    let parsed = parse_document(source);
+    let mut links = vec![];
+
+    for token in parsed.blocks.iter().flat_map(|block| block.tokens.iter()) {
+      let TokenKind::Dep(dependency) = &token.kind else {
+        continue;
+      };
+      let Some(file) = self.resolve_dependency(dependency).filter(|file| file.is_file()) else {
+        continue;
+      };
+      let Ok(target) = Url::from_file_path(&file) else {
+        continue;
+      };
+      links.push(DocumentLink {
+        range: line_index.range(dependency_span(source, token.span, dependency)),
+        target: Some(target),
+        tooltip: Some(file.to_string_lossy().to_string()),
+        data: None,
+      });
+    }
+
+    for import in parsed.imports() {
+      let segments = import.module_segments();
+      let (Some(first), Some(last)) = (segments.first(), segments.last()) else {
+        continue;
+      };
+      let Some(file) = self.module_file(segments) else {
+        continue;
+      };
+      let Some(target) = self.workspace_url(&file) else {
+        continue;
+      };
+      links.push(DocumentLink {
+        range: line_index.range(Span::new(first.span.start, last.span.end)),
+        target: Some(target),
+        tooltip: Some(self.relative_path(&file)),
+        data: None,
+      });
+    }
+
+    links.sort_by_key(|link| link.range.start);
+    Ok(links)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use lsp_types::{Position, Range};
+
+  use crate::capabilities::tests::fixture_session;
+
+  #[tokio::test]
+  async fn test_document_links() {
+    let (session, documents, uri) = fixture_session("src/app.rs").await;
+    let links = session.document_links(&uri, &documents).unwrap();
+    let found: Vec<(Range, &str)> = links
+      .iter()
+      .map(|link| (link.range, link.tooltip.as_deref().unwrap()))
+      .collect();
+    assert_eq!(found[..3], [
+      (Range::new(Position::new(7, 8), Position::new(7, 29)), "src/shared/styles.rs"),
+      (Range::new(Position::new(8, 8), Position::new(8, 32)), "src/home/home_screen.rs"),
+      (Range::new(Position::new(9, 8), Position::new(9, 33)), "src/shared/popup_list.rs"),
+    ]);
+    assert_eq!(found[3].0, Range::new(Position::new(11, 21), Position::new(11, 58)));
+    assert!(found[3].1.ends_with("fixtures/robrix/resources/icons/chat.svg"));
+
+    let module_target = links[1].target.as_ref().unwrap();
+    assert!(module_target.path().ends_with("fixtures/robrix/src/home/home_screen.rs"));
+  }
+}
@@ -0,0 +1,196 @@
+use lsp_types::{DocumentSymbol, DocumentSymbolResponse, SymbolKind};
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{
+  parse_document, LineIndex, LiveDSLASTNode, Span, WidgetKind, WidgetNode,
+};
+use url::Url;
+
+use crate::Session;
+
+impl Session {
+  /// Outlines the `live_design!` blocks of the document at the temp `uri`: one symbol per
+  /// block holding its imports, constants and widget definitions, with the named children
+  /// of each widget tree nested below them.
+  pub fn document_symbols(
+    &self,
+    uri: &Url,
+    documents: &Documents,
+  ) -> Result<Option<DocumentSymbolResponse>, DocumentError> {
+    let document = documents.get_text_document(uri)?;
+    Ok(Some(DocumentSymbolResponse::Nested(outline(document.get_text()))))
+  }
+}
+
+pub fn outline(source: &str) -> Vec<DocumentSymbol> {
+  let document = parse_document(source);
+  let line_index = LineIndex::new(source);
+  let symbol = |name: String, detail: Option<String>, kind, span: Span, name_span: Span, children| {
+    #[allow(deprecated)]
+    DocumentSymbol {
+      name,
+      detail,
+      kind,
+      tags: None,
+      deprecated: None,
+      range: line_index.range(span),
+      selection_range: line_index.range(name_span),
+      children: Some(children),
+    }
+  };
+
+  document
+    .blocks
+    .iter()
+    .map(|block| {
+      let children = block
+        .nodes
+        .iter()
+        .map(|node| match node {
+          LiveDSLASTNode::Import(import) => symbol(
+            import.path(),
+            None,
+            SymbolKind::MODULE,
+            import.span,
+            import.span,
+            vec![],
+          ),
+          LiveDSLASTNode::Constant(constant) => symbol(
+            constant.name.clone(),
+            Some(constant.value_span.text(source).to_string()),
+            SymbolKind::CONSTANT,
+            constant.span,
+            constant.name_span,
+            vec![],
+          ),
+          LiveDSLASTNode::Widget(widget) => symbol(
+            widget.name.clone(),
+            widget_detail(widget),
+            SymbolKind::CLASS,
+            widget.span,
+            widget.name_span.unwrap_or(widget.span),
+            nested_symbols(widget, &symbol),
+          ),
+        })
+        .collect();
+      let name_span = Span::new(block.span.start, block.body_span.start);
+      symbol(
+        "live_design!".to_string(),
+        None,
+        SymbolKind::NAMESPACE,
+        block.span,
+        name_span,
+        children,
+      )
+    })
+    .collect()
+}
+
+/// The named children of `widget`, looking through anonymous `<View> {}` wrappers and
+/// object-valued properties such as `ui: <Window> {}` that lead to named children.
+fn nested_symbols(
+  widget: &WidgetNode,
+  symbol: &impl Fn(String, Option<String>, SymbolKind, Span, Span, Vec<DocumentSymbol>) -> DocumentSymbol,
+) -> Vec<DocumentSymbol> {
+  let mut symbols = vec![];
+  for property in &widget.properties {
+    let Some(object) = property.value.as_object() else {
+      continue;
+    };
+    let children = nested_symbols(object, symbol);
+    if !children.is_empty() {
+      symbols.push(symbol(
+        property.name.clone(),
+        widget_detail(object),
+        SymbolKind::PROPERTY,
+        property.span,
+        property.name_span,
+        children,
+      ));
+    }
+  }
+  for child in &widget.children {
+    let children = nested_symbols(child, symbol);
+    match child.name_span {
+      Some(name_span) if !child.name.is_empty() => symbols.push(symbol(
+        child.name.clone(),
+        widget_detail(child),
+        SymbolKind::FIELD,
+        child.span,
+        name_span,
+        children,
+      )),
+      _ => symbols.extend(children),
+    }
+  }
+  symbols.sort_by_key(|symbol| symbol.range.start);
+  symbols
+}
+
+/// `<RadioButton>` or `{{App}}`.
+fn widget_detail(widget: &WidgetNode) -> Option<String> {
+  match widget.kind {
+    WidgetKind::Instance => Some(format!("<{}>", widget.widget_type)),
+    WidgetKind::RustBinding => Some(format!("{{{{{}}}}}", widget.widget_type)),
+    WidgetKind::Object => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn tree(symbols: &[DocumentSymbol], depth: usize, lines: &mut Vec<String>) {
+    for symbol in symbols {
+      lines.push(format!("{}{:?} {}", "  ".repeat(depth), symbol.kind, symbol.name));
+      tree(symbol.children.as_deref().unwrap_or_default(), depth + 1, lines);
+    }
+  }
+
+  #[test]
+  fn test_outline() {
+    let source = r#"
+live_design! {
+    use link::widgets::*;
+    APP_TAB_COLOR = #344054
+
+    AppTab = <RadioButton> {
+        draw_text: { color: (APP_TAB_COLOR) }
+    }
+
+    App = {{App}} {
+        ui: <Window> {
+            body = {
+                <View> {
+                    home_screen_view = <View> {
+                        home_screen = <HomeScreen> {}
+                    }
+                    popup = <PopupNotification> {}
+                }
+            }
+        }
+    }
+}
+"#;
+    let symbols = outline(source);
+    let mut lines = vec![];
+    tree(&symbols, 0, &mut lines);
+    assert_eq!(lines, vec![
+      "Namespace live_design!",
+      "  Module link::widgets::*",
+      "  Constant APP_TAB_COLOR",
+      "  Class AppTab",
+      "  Class App",
+      "    Property ui",
+      "      Field body",
+      "        Field home_screen_view",
+      "          Field home_screen",
+      "        Field popup",
+    ]);
+
+    let app_tab = &symbols[0].children.as_ref().unwrap()[2];
+    assert_eq!(app_tab.detail.as_deref(), Some("<RadioButton>"));
+    assert_eq!(app_tab.selection_range.start, lsp_types::Position::new(5, 4));
+    assert_eq!(app_tab.range.end, lsp_types::Position::new(7, 5));
+  }
+}
@@ -0,0 +1,109 @@
+use lsp_types::{FoldingRange, FoldingRangeKind};
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{parse_document, LineIndex, Span};
+use url::Url;
+
+use crate::Session;
+
+impl Session {
+  /// Folds the `live_design!` blocks of the document at the temp `uri` along their structure.
+  pub fn folding_ranges(
+    &self,
+    uri: &Url,
+    documents: &Documents,
+  ) -> Result<Vec<FoldingRange>, DocumentError> {
+    let document = documents.get_text_document(uri)?;
+    Ok(folding_ranges(document.get_text()))
+  }
+}
+
+/// One range per `live_design!` block, widget body, object such as `draw_bg: { }`, shader
+/// `fn` and run of `use` lines on consecutive lines.
+pub fn folding_ranges(source: &str) -> Vec<FoldingRange> {
+  let line_index = LineIndex::new(source);
+  let mut ranges = vec![];
+  let mut push = |span: Span, kind: Option<FoldingRangeKind>| {
+    let start_line = line_index.position(span.start).line;
+    let mut end_line = line_index.position(span.end).line;
+    // Keep a closing brace that starts its line visible, as rust-analyzer does.
+    let line_start = source[..span.end].rfind('\n').map_or(0, |index| index + 1);
+    if source[line_start..span.end].trim() == "}" {
+      end_line = end_line.saturating_sub(1);
+    }
+    if end_line > start_line {
+      ranges.push(FoldingRange {
+        start_line,
+        start_character: None,
+        end_line,
+        end_character: None,
+        kind,
+        collapsed_text: None,
+      });
+    }
+  };
+
+  for block in parse_document(source).blocks {
+    push(block.span, None);
+
+    let mut imports = block.imports().map(|import| import.span).peekable();
+    while let Some(first) = imports.next() {
+      let mut last = first;
+      while let Some(next) = imports.next_if(|next| {
+        line_index.position(next.start).line == line_index.position(last.end).line + 1
+      }) {
+        last = next;
+      }
+      push(first.join(last), Some(FoldingRangeKind::Imports));
+    }
+
+    for widget in block.widgets() {
+      widget.walk(&mut |node| {
+        if let Some(body) = node.body_span {
+          push(body, None);
+        }
+        for shader_fn in &node.shader_fns {
+          push(shader_fn.body_span, None);
+        }
+      });
+    }
+  }
+
+  ranges.sort_by_key(|range| (range.start_line, range.end_line));
+  ranges
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_folding_ranges() {
+    let source = r#"live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::styles::*;
+
+    AppTab = <RadioButton> {
+        draw_radio: {
+            fn pixel(self) -> vec4 {
+                return #f;
+            }
+        }
+        label_walk: {margin: 0.0}
+    }
+}"#;
+    let ranges: Vec<(u32, u32, Option<FoldingRangeKind>)> = folding_ranges(source)
+      .into_iter()
+      .map(|range| (range.start_line, range.end_line, range.kind))
+      .collect();
+    assert_eq!(ranges, vec![
+      (0, 13, None),
+      (1, 2, Some(FoldingRangeKind::Imports)),
+      (6, 12, None),
+      (7, 10, None),
+      (8, 9, None),
+    ]);
+  }
+}
@@ -0,0 +1,650 @@
+use lsp_types::{Range, TextEdit};
+use makepad_analyzer_core::{
+  config::{FormattingConfig, TrailingCommas},
+  errors::DocumentError,
+};
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{parse_document, LineIndex, Punct, Span, Token, TokenKind};
+use url::Url;
+
+use crate::Session;
+
+/// Bodies written on one line stay on one line when they fit in this many columns.
+const MAX_LINE_WIDTH: usize = 100;
+
+impl Session {
+  /// Formats the `live_design!` blocks of the document at the temp `uri`, or only the
+  /// top-level entries overlapping `range` when given.
+  pub fn format(
+    &self,
+    uri: &Url,
+    range: Option<Range>,
+    config: &FormattingConfig,
+    documents: &Documents,
+  ) -> Result<Vec<TextEdit>, DocumentError> {
+    let document = documents.get_text_document(uri)?;
+    let source = document.get_text();
+    let line_index = LineIndex::new(source);
+    let span = range.map(|range| line_index.span(range));
+    Ok(
+      format_edits(source, span, config)
+        .into_iter()
+        .map(|(span, text)| TextEdit::new(line_index.range(span), text))
+        .collect(),
+    )
+  }
+}
+
+/// The replacements for the bodies of all blocks, or for the top-level entries overlapping
+/// `range` when given. Blocks with syntax errors are left alone.
+pub fn format_edits(
+  source: &str,
+  range: Option<Span>,
+  config: &FormattingConfig,
+) -> Vec<(Span, String)> {
+  let document = parse_document(source);
+  let mut edits = vec![];
+  for block in &document.blocks {
+    let overlaps = range.is_none_or(|range| range.start <= block.span.end && block.span.start <= range.end);
+    let has_errors = document
+      .errors
+      .iter()
+      .any(|error| error.span.start <= block.span.end && block.span.start <= error.span.end);
+    if !overlaps || has_errors {
+      continue;
+    }
+    // Replace everything between the delimiters of `live_design! { ... }`.
+    let Some(open) = source[block.span.start..block.span.end].find(['{', '(', '[']) else {
+      continue;
+    };
+    let inner = Span::new(block.span.start + open + 1, block.span.end - 1);
+
+    let line_start = source[..block.span.start].rfind('\n').map_or(0, |index| index + 1);
+    let base: String = source[line_start..block.span.start]
+      .chars()
+      .take_while(|c| c.is_whitespace())
+      .collect();
+    let formatter = Formatter { source, config, base: &base };
+    let tokens: Vec<&Token> = block.tokens.iter().collect();
+    let mut pos = 0;
+    let mut items = formatter.items(&tokens, &mut pos);
+    if let Some(range) = range {
+      edits.extend(formatter.item_edits(&items, inner, range));
+      continue;
+    }
+    sort_imports(source, &mut items);
+
+    let mut text = String::from("\n");
+    formatter.render(&items, 1, false, &mut text);
+    text.push_str(&base);
+    if items.is_empty() {
+      text.clear();
+    }
+    if inner.text(source) != text {
+      edits.push((inner, text));
+    }
+  }
+  edits
+}
+
+/// Formats every block of `source`.
+pub fn format_source(source: &str, config: &FormattingConfig) -> String {
+  let mut formatted = source.to_string();
+  for (span, text) in format_edits(source, None, config).into_iter().rev() {
+    formatted.replace_range(span.start..span.end, &text);
+  }
+  formatted
+}
+
+#[derive(Debug)]
+enum Item<'a> {
+  Comment {
+    token: &'a Token,
+    /// Written after code on the same line.
+    trailing: bool,
+    blank_before: bool,
+  },
+  Entry {
+    head: Vec<&'a Token>,
+    body: Option<Body<'a>>,
+    comma: bool,
+    blank_before: bool,
+    /// From the first token to the closing `}` or the comma.
+    span: Span,
+  },
+  /// A shader `fn`, kept as written.
+  Verbatim { span: Span, blank_before: bool },
+}
+
+#[derive(Debug)]
+struct Body<'a> {
+  items: Vec<Item<'a>>,
+  /// The braces were on the same line.
+  single_line: bool,
+}
+
+impl Item<'_> {
+  fn blank_before(&self) -> bool {
+    match self {
+      Item::Comment { blank_before, .. }
+      | Item::Entry { blank_before, .. }
+      | Item::Verbatim { blank_before, .. } => *blank_before,
+    }
+  }
+
+  fn span(&self) -> Span {
+    match self {
+      Item::Comment { token, .. } => token.span,
+      Item::Entry { span, .. } | Item::Verbatim { span, .. } => *span,
+    }
+  }
+
+  fn is_import(&self) -> bool {
+    matches!(self, Item::Entry { head, .. } if head.first().is_some_and(|token| token.is_ident("use")))
+  }
+}
+
+struct Formatter<'a> {
+  source: &'a str,
+  config: &'a FormattingConfig,
+  /// The indentation of the `live_design!` line.
+  base: &'a str,
+}
+
+impl<'a> Formatter<'a> {
+  fn newlines_between(&self, before: Option<&Token>, after: &Token) -> usize {
+    before.map_or(0, |before| {
+      self.source[before.span.end..after.span.start].matches('\n').count()
+    })
+  }
+
+  /// Reads the items of a body up to its closing `}` (left unconsumed) or the end.
+  fn items(&self, tokens: &[&'a Token], pos: &mut usize) -> Vec<Item<'a>> {
+    let mut items: Vec<Item<'a>> = vec![];
+    while let Some(&token) = tokens.get(*pos) {
+      let previous = pos.checked_sub(1).map(|index| tokens[index]);
+      let newlines = self.newlines_between(previous, token);
+      let blank_before = !items.is_empty() && newlines > 1;
+
+      if token.is_punct(Punct::RBrace) {
+        break;
+      }
+      if token.is_trivia() {
+        let trailing = !items.is_empty() && newlines == 0;
+        items.push(Item::Comment { token, trailing, blank_before });
+        *pos += 1;
+        continue;
+      }
+      if token.is_punct(Punct::Comma) {
+        if let Some(Item::Entry { comma, span, .. }) = items.last_mut() {
+          *comma = true;
+          span.end = token.span.end;
+        }
+        *pos += 1;
+        continue;
+      }
+      if token.is_ident("fn") {
+        let start = token.span.start;
+        let mut depth = 0;
+        let mut end = token.span.end;
+        while let Some(&token) = tokens.get(*pos) {
+          *pos += 1;
+          end = token.span.end;
+          if token.is_punct(Punct::LBrace) {
+            depth += 1;
+          } else if token.is_punct(Punct::RBrace) {
+            depth -= 1;
+            if depth == 0 {
+              break;
+            }
+          }
+        }
+        items.push(Item::Verbatim { span: Span::new(start, end), blank_before });
+        continue;
+      }
+      items.push(self.entry(tokens, pos, blank_before));
+    }
+    items
+  }
+
+  fn entry(&self, tokens: &[&'a Token], pos: &mut usize, blank_before: bool) -> Item<'a> {
+    let mut head: Vec<&Token> = vec![];
+    let mut body = None;
+    let mut comma = false;
+    let mut depth = 0usize;
+    // Whether the `:` or `=` before the value was read.
+    let mut has_value = false;
+    let start = tokens[*pos].span.start;
+
+    while let Some(&token) = tokens.get(*pos) {
+      if depth == 0 {
+        if token.is_punct(Punct::RBrace) {
+          break;
+        }
+        if token.is_trivia() {
+          let after = tokens.get(*pos + 1).copied();
+          if !self.is_inline_comment(head.last().copied(), token, after) {
+            break;
+          }
+          head.push(token);
+          *pos += 1;
+          continue;
+        }
+        if token.is_punct(Punct::Comma) {
+          comma = true;
+          *pos += 1;
+          break;
+        }
+        if token.is_punct(Punct::LBrace) {
+          *pos += 1;
+          let items = self.items(tokens, pos);
+          let close = tokens.get(*pos).copied();
+          let single_line = close.is_some_and(|close| {
+            !self.source[token.span.start..close.span.start].contains('\n')
+          });
+          *pos += 1;
+          body = Some(Body { items, single_line });
+          if tokens.get(*pos).is_some_and(|token| token.is_punct(Punct::Comma)) {
+            comma = true;
+            *pos += 1;
+          }
+          break;
+        }
+        let last = head.last().copied();
+        if let Some(last) = last {
+          if self.newlines_between(Some(last), token) > 0 && !continues(last, token) {
+            break;
+          }
+          // `width: Fill height: Fit` holds two entries.
+          if has_value && !open_ended(last) && starts_entry(&tokens[*pos..]) {
+            break;
+          }
+        }
+        if token.is_punct(Punct::Colon) || token.is_punct(Punct::Eq) {
+          has_value = true;
+        }
+      }
+
+      match token.kind {
+        TokenKind::Punct(Punct::LParen | Punct::LBracket) => depth += 1,
+        TokenKind::Punct(Punct::RParen | Punct::RBracket) => depth = depth.saturating_sub(1),
+        _ => {}
+      }
+      head.push(token);
+      *pos += 1;
+      if token.is_punct(Punct::Semi) && depth == 0 {
+        break;
+      }
+    }
+
+    let span = Span::new(start, tokens[*pos - 1].span.end);
+    Item::Entry { head, body, comma, blank_before, span }
+  }
+
+  /// The replacements for the top-level `items` of the block body `inner` that overlap
+  /// `range`, each with the comments trailing it. Imports are not sorted, as that would move
+  /// entries outside of the range.
+  fn item_edits(&self, items: &[Item], inner: Span, range: Span) -> Vec<(Span, String)> {
+    let mut edits = vec![];
+    let mut index = 0;
+    while index < items.len() {
+      let mut end = index + 1;
+      while matches!(items.get(end), Some(Item::Comment { trailing: true, .. })) {
+        end += 1;
+      }
+      let group = &items[index..end];
+      let span = group[0].span().join(group[group.len() - 1].span());
+      let previous_end = index.checked_sub(1).map_or(inner.start, |index| items[index].span().end);
+      index = end;
+      if span.end < range.start || range.end < span.start {
+        continue;
+      }
+
+      let mut text = String::new();
+      self.render(group, 1, false, &mut text);
+      text.pop();
+      // Entries sharing a line with the previous one move to their own line.
+      let start = match self.source[previous_end..span.start].rfind('\n') {
+        Some(newline) => previous_end + newline + 1,
+        None => {
+          text.insert(0, '\n');
+          previous_end
+        }
+      };
+      let span = Span::new(start, span.end);
+      if span.text(self.source) != text {
+        edits.push((span, text));
+      }
+    }
+    edits
+  }
+
+  /// Whether `token` is a block comment between two tokens of the same line, as in
+  /// `width: /* w */ Fill`, which stays in the entry.
+  fn is_inline_comment(
+    &self,
+    before: Option<&Token>,
+    token: &Token,
+    after: Option<&Token>,
+  ) -> bool {
+    matches!(token.kind, TokenKind::BlockComment(_))
+      && before.is_some_and(|before| self.newlines_between(Some(before), token) == 0)
+      && after.is_some_and(|after| {
+        !after.is_punct(Punct::RBrace) && self.newlines_between(Some(token), after) == 0
+      })
+  }
+
+  fn indent(&self, depth: usize) -> String {
+    format!("{}{}", self.base, " ".repeat(self.config.indent_width * depth))
+  }
+
+  fn render(&self, items: &[Item], depth: usize, in_widget: bool, out: &mut String) {
+    let indent = self.indent(depth);
+    let has_commas = items.iter().any(|item| matches!(item, Item::Entry { comma: true, .. }));
+    for (index, item) in items.iter().enumerate() {
+      if item.blank_before() && index > 0 {
+        out.push('\n');
+      }
+      match item {
+        Item::Comment { token, trailing: true, .. } if out.ends_with('\n') => {
+          out.pop();
+          out.push(' ');
+          out.push_str(token.span.text(self.source).trim_end());
+          out.push('\n');
+        }
+        Item::Comment { token, .. } => {
+          out.push_str(&indent);
+          out.push_str(&self.reindent(token.span, &indent));
+          out.push('\n');
+        }
+        Item::Verbatim { span, .. } => {
+          out.push_str(&indent);
+          out.push_str(&self.reindent(*span, &indent));
+          out.push('\n');
+        }
+        Item::Entry { head, body, .. } => {
+          out.push_str(&indent);
+          out.push_str(&self.head(head));
+          if let Some(body) = body {
+            if !head.is_empty() {
+              out.push(' ');
+            }
+            let line_width = out.len() - out.rfind('\n').map_or(0, |index| index + 1);
+            match self.inline_body(head, body) {
+              Some(inline) if line_width + inline.len() <= MAX_LINE_WIDTH => out.push_str(&inline),
+              _ => {
+                out.push_str("{\n");
+                self.render(&body.items, depth + 1, true, out);
+                out.push_str(&indent);
+                out.push('}');
+              }
+            }
+          }
+          let is_statement = head.last().is_some_and(|token| token.is_punct(Punct::Semi));
+          let comma = match self.config.trailing_commas {
+            _ if is_statement => false,
+            TrailingCommas::Always => in_widget,
+            TrailingCommas::Never => false,
+            TrailingCommas::Preserve => has_commas,
+          };
+          if comma {
+            out.push(',');
+          }
+          out.push('\n');
+        }
+      }
+    }
+  }
+
+  /// `{x: 0.5, y: 0.5}` for the value of a property written on one line that holds only
+  /// entries. Widget bodies get one property per line.
+  fn inline_body(&self, head: &[&Token], body: &Body) -> Option<String> {
+    if body.items.is_empty() {
+      return Some("{}".to_string());
+    }
+    let is_property = head.last().is_some_and(|token| token.is_punct(Punct::Colon));
+    if !body.single_line || !is_property {
+      return None;
+    }
+    let mut entries = vec![];
+    for item in &body.items {
+      let Item::Entry { head, body, .. } = item else {
+        return None;
+      };
+      let mut entry = self.head(head);
+      if let Some(body) = body {
+        if !head.is_empty() {
+          entry.push(' ');
+        }
+        entry.push_str(&self.inline_body(head, body)?);
+      }
+      entries.push(entry);
+    }
+    Some(format!("{{{}}}", entries.join(", ")))
+  }
+
+  /// Joins the tokens of an entry head with normalized spacing.
+  fn head(&self, head: &[&Token]) -> String {
+    let mut text = String::new();
+    for (index, token) in head.iter().enumerate() {
+      if let Some(previous) = index.checked_sub(1).map(|index| head[index]) {
+        if space_between(self.source, previous, token) {
+          text.push(' ');
+        }
+      }
+      text.push_str(token.span.text(self.source));
+    }
+    text
+  }
+
+  /// The text at `span` with its continuation lines moved by the same amount as its first
+  /// line, so that the inside of shader functions and block comments keeps its shape.
+  fn reindent(&self, span: Span, indent: &str) -> String {
+    let line_start = self.source[..span.start].rfind('\n').map_or(0, |index| index + 1);
+    let column = self.source[line_start..span.start].chars().count();
+    let mut lines = span.text(self.source).lines();
+    let mut text = lines.next().unwrap_or_default().trim_end().to_string();
+    for line in lines {
+      text.push('\n');
+      let leading = line.chars().take_while(|c| c.is_whitespace()).count();
+      let line = line.trim_end();
+      if !line.is_empty() {
+        text.push_str(indent);
+        text.push_str(&" ".repeat(leading.saturating_sub(column)));
+        text.push_str(line.trim_start());
+      }
+    }
+    text
+  }
+}
+
+/// Whether a line break between `last` and `next` continues the same entry.
+fn continues(last: &Token, next: &Token) -> bool {
+  let continuation = match next.kind {
+    TokenKind::Punct(punct) => !matches!(punct, Punct::LParen | Punct::LBracket | Punct::Lt | Punct::Bang | Punct::Minus),
+    _ => false,
+  };
+  open_ended(last) || continuation
+}
+
+/// Whether an entry can't end with `token`, e.g. `:` or `instance`.
+fn open_ended(token: &Token) -> bool {
+  match token.kind {
+    TokenKind::Punct(punct) => !matches!(punct, Punct::RParen | Punct::RBracket | Punct::Gt),
+    TokenKind::LineComment(_) | TokenKind::BlockComment(_) => false,
+    _ => token.is_ident("pub") || token.is_ident("instance") || token.is_ident("uniform"),
+  }
+}
+
+/// Whether `tokens` start with a key such as `height:` or `Name =`.
+fn starts_entry(tokens: &[&Token]) -> bool {
+  match tokens {
+    [key, separator, ..] => {
+      key.ident().is_some() && (separator.is_punct(Punct::Colon) || separator.is_punct(Punct::Eq))
+    }
+    _ => false,
+  }
+}
+
+fn space_between(source: &str, previous: &Token, token: &Token) -> bool {
+  use Punct::*;
+  let punct = |token: &Token| match token.kind {
+    TokenKind::Punct(punct) => Some(punct),
+    _ => None,
+  };
+  match (punct(previous), punct(token)) {
+    (_, Some(Comma | Semi | RParen | RBracket | Dot | Colon | PathSep)) => false,
+    (Some(LParen | LBracket | Dot | PathSep), _) => false,
+    (Some(Colon | Comma | Eq), _) | (_, Some(Eq)) => true,
+    (None, Some(LParen | LBracket)) if previous.ident().is_some() => false,
+    _ => previous.span.end < token.span.start && source[previous.span.end..token.span.start].chars().any(char::is_whitespace),
+  }
+}
+
+/// Sorts each run of `use` entries on consecutive lines.
+fn sort_imports(source: &str, items: &mut [Item]) {
+  let key = |item: &Item| match item {
+    Item::Entry { head, .. } => head.iter().map(|token| token.span.text(source)).collect::<Vec<_>>().join(" "),
+    _ => String::new(),
+  };
+  let mut start = 0;
+  while start < items.len() {
+    if !items[start].is_import() {
+      start += 1;
+      continue;
+    }
+    let mut end = start + 1;
+    while end < items.len() && items[end].is_import() && !items[end].blank_before() {
+      end += 1;
+    }
+    let blank_before = items[start].blank_before();
+    items[start..end].sort_by_cached_key(|item| key(item));
+    for (index, item) in items[start..end].iter_mut().enumerate() {
+      if let Item::Entry { blank_before: blank, .. } = item {
+        *blank = index == 0 && blank_before;
+      }
+    }
+    start = end;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_format_source() {
+    let source = r#"live_design!{
+  use link::widgets::*;
+  use link::theme::*;
+
+
+  use crate::shared::styles::*;
+  // The tab shown in the bottom bar.
+  AppTab   =   <RadioButton>{
+     width:Fit, height: Fill // fills the bar
+      align: {x: 0.5,y: 0.5}
+   draw_radio: {
+        radio_type: Tab,
+          fn pixel(self) -> vec4 {
+              let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+              return sdf.result;
+          }
+      }
+      label_walk: {
+          margin: 0.0
+      }
+  }
+}"#;
+    let expected = r#"live_design!{
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    // The tab shown in the bottom bar.
+    AppTab = <RadioButton> {
+        width: Fit,
+        height: Fill, // fills the bar
+        align: {x: 0.5, y: 0.5},
+        draw_radio: {
+            radio_type: Tab,
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                return sdf.result;
+            }
+        },
+        label_walk: {
+            margin: 0.0
+        },
+    }
+}"#;
+    let config = FormattingConfig::default();
+    let formatted = format_source(source, &config);
+    assert_eq!(formatted, expected);
+    assert_eq!(format_source(&formatted, &config), expected);
+  }
+
+  #[test]
+  fn test_format_config_and_range() {
+    let source = "live_design! {\nA = <View> {width: Fill\nheight: Fit}\n}\n\nlive_design! {\n      B = #fff\n}\n";
+    let config = FormattingConfig {
+      indent_width: 2,
+      trailing_commas: TrailingCommas::Always,
+    };
+    assert_eq!(
+      format_source(source, &config),
+      "live_design! {\n  A = <View> {\n    width: Fill,\n    height: Fit,\n  }\n}\n\nlive_design! {\n  B = #fff\n}\n"
+    );
+
+    let second = source.rfind("B =").unwrap();
+    let edits = format_edits(source, Some(Span::new(second, second)), &config);
+    assert_eq!(edits, vec![(Span::new(second - 6, second + 8), "  B = #fff".to_string())]);
+
+    // Only the entries overlapping the range change.
+    let source = "live_design! {\nA = <View> {width: Fill}\n  B = <View> {height:Fit}  C = 1 // c\n}\n";
+    let apply = |range: Span| {
+      let mut formatted = source.to_string();
+      for (span, text) in format_edits(source, Some(range), &config).into_iter().rev() {
+        formatted.replace_range(span.start..span.end, &text);
+      }
+      formatted
+    };
+    let b = source.find("B =").unwrap();
+    assert_eq!(
+      apply(Span::new(b, b)),
+      "live_design! {\nA = <View> {width: Fill}\n  B = <View> {\n    height: Fit,\n  }  C = 1 // c\n}\n"
+    );
+    let c = source.find("C =").unwrap();
+    assert_eq!(
+      apply(Span::new(c, c)),
+      "live_design! {\nA = <View> {width: Fill}\n  B = <View> {height:Fit}\n  C = 1 // c\n}\n"
+    );
+
+    // Entries without commas are split too, and commas follow the body's lead.
+    let source = concat!(
+      "live_design! {\n",
+      "  A = <View> {width: Fill height: Fit, margin: {top: 1 left: 2}}\n",
+      "  B = <View> {width: Fill height: Fit}\n",
+      "}\n",
+    );
+    assert_eq!(
+      format_source(source, &FormattingConfig { indent_width: 2, ..FormattingConfig::default() }),
+      concat!(
+        "live_design! {\n",
+        "  A = <View> {\n    width: Fill,\n    height: Fit,\n    margin: {top: 1, left: 2},\n  }\n",
+        "  B = <View> {\n    width: Fill\n    height: Fit\n  }\n",
+        "}\n",
+      )
+    );
+
+    // Block comments inside an entry stay in it.
+    let source = "live_design! {\n  A = <View> {width: /* w */ Fill,\n height: Fit /* h */}\n}\n";
+    assert_eq!(
+      format_source(source, &config),
+      "live_design! {\n  A = <View> {\n    width: /* w */ Fill,\n    height: Fit, /* h */\n  }\n}\n"
+    );
+
+    // Blocks that don't parse are left alone.
+    assert!(format_edits("live_design! { A = <View> { }", None, &config).is_empty());
+  }
+}
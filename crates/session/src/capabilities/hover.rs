@@ -0,0 +1,413 @@
+use std::path::{Path, PathBuf};
+
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{
+  parse_document, ConstantNode, Expression, LineIndex, ParsedDocument, PropertyNode,
+  ReferenceKind, Rgba, Span, TokenAstNode, WidgetKind, WidgetNode,
+};
+use makepad_analyzer_plugin_shader::field_hover;
+use url::Url;
+
+use crate::{
+  builtins::builtin_property,
+  resolve::{framework_module, symbol_at, SymbolAt},
+  shader::{self_field_at, shader_fn_at},
+  Session,
+};
+
+/// How deep `(A)` -> `(B)` constant and `<A>` -> `<B>` widget chains are followed.
+const MAX_RESOLVE_DEPTH: usize = 16;
+
+impl Session {
+  /// Describes the widget, constant or property at `position` in the document at the temp `uri`.
+  pub fn hover(
+    &self,
+    uri: &Url,
+    position: Position,
+    documents: &Documents,
+  ) -> Result<Option<Hover>, DocumentError> {
+    let text_document = documents.get_text_document(uri)?;
+    let source = text_document.get_text();
+    let document = parse_document(source);
+    let path = Path::new(uri.path());
+    let line_index = LineIndex::new(source);
+
+    let (markdown, span) = match symbol_at(self, path, source, &document, position) {
+      Some(SymbolAt::Reference(reference)) => {
+        let markdown = match reference.kind {
+          ReferenceKind::Widget => self.widget_hover(path, &document, &reference.name, documents),
+          ReferenceKind::Constant => {
+            self.constant_hover(path, &document, &reference.name, documents)
+          }
+          ReferenceKind::RustType => Some(format!(
+            "```rust\nstruct {}\n```\nA Rust type registered with `#[derive(Live)]`.",
+            reference.name
+          )),
+        };
+        (markdown, Some(reference.span))
+      }
+      Some(SymbolAt::Declaration(ident, token)) => {
+        let markdown = match &token.ast_node {
+          TokenAstNode::WidgetDefinition(widget) => {
+            Some(self.widget_definition_hover(path, widget, documents))
+          }
+          TokenAstNode::Constant(constant) => {
+            Some(self.constant_definition_hover(path, source, constant, documents))
+          }
+          TokenAstNode::NamedInstance(widget) => {
+            self.instance_hover(path, &document, widget, documents)
+          }
+          TokenAstNode::Import(_) => None,
+        };
+        (markdown, Some(line_index.span(ident.range)))
+      }
+      Some(SymbolAt::ImportSegment(import, index)) => {
+        let segment = &import.segments[index];
+        let markdown = (!import.is_glob && index == import.segments.len() - 1)
+          .then(|| self.name_hover(path, &document, &segment.name, documents))
+          .flatten();
+        (markdown, Some(segment.span))
+      }
+      None => {
+        let offset = line_index.offset(position);
+        if let Some((markdown, span)) =
+          self.shader_field_hover(path, source, &document, offset, documents)
+        {
+          (Some(markdown), Some(span))
+        } else {
+          match property_at(&document, offset) {
+            Some(property) => (property_hover(property), Some(property.name_span)),
+            None => (None, None),
+          }
+        }
+      }
+    };
+
+    Ok(markdown.map(|value| Hover {
+      contents: HoverContents::Markup(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value,
+      }),
+      range: span.map(|span| line_index.range(span)),
+    }))
+  }
+
+  /// Describes the `self.<field>` at `offset` of a shader `fn`, from the draw shader or
+  /// definition that declares it.
+  fn shader_field_hover(
+    &self,
+    path: &Path,
+    source: &str,
+    document: &ParsedDocument,
+    offset: usize,
+    documents: &Documents,
+  ) -> Option<(String, Span)> {
+    let (object, _) = shader_fn_at(document, offset)?;
+    let (start, name) = self_field_at(source, offset).filter(|(_, name)| !name.is_empty())?;
+    let scope = self.shader_scope(path, document, object, documents);
+    Some((field_hover(&scope, name)?, Span::new(start, start + name.len())))
+  }
+
+  /// Hover for a name that may be a widget or a constant, as in `use crate::app::AppTab`.
+  fn name_hover(
+    &self,
+    path: &Path,
+    document: &ParsedDocument,
+    name: &str,
+    documents: &Documents,
+  ) -> Option<String> {
+    let (ident, token) = self.resolve_name(path, document, name).into_iter().next()?;
+    let file = ident.path?;
+    match token.ast_node {
+      TokenAstNode::WidgetDefinition(widget) => {
+        Some(self.widget_definition_hover(&file, &widget, documents))
+      }
+      TokenAstNode::Constant(constant) => {
+        let (source, _) = self.parse_file(&file, documents)?;
+        Some(self.constant_definition_hover(&file, &source, &constant, documents))
+      }
+      _ => None,
+    }
+  }
+
+  fn widget_hover(
+    &self,
+    path: &Path,
+    document: &ParsedDocument,
+    name: &str,
+    documents: &Documents,
+  ) -> Option<String> {
+    let definition = self
+      .resolve_name(path, document, name)
+      .into_iter()
+      .find_map(|(ident, token)| match token.ast_node {
+        TokenAstNode::WidgetDefinition(widget) => Some((ident.path?, widget)),
+        _ => None,
+      });
+    match definition {
+      Some((file, widget)) => Some(self.widget_definition_hover(&file, &widget, documents)),
+      None => framework_module(document, name).map(|module| {
+        format!("```makepad\n{}\n```\nProvided by `{}`.", name, module)
+      }),
+    }
+  }
+
+  fn widget_definition_hover(&self, path: &Path, widget: &WidgetNode, documents: &Documents) -> String {
+    let mut chain = vec![widget.name.clone()];
+    chain.extend(self.base_types(path, widget, documents));
+    let chain = chain
+      .iter()
+      .map(|name| format!("`{}`", name))
+      .collect::<Vec<_>>()
+      .join(" → ");
+    format!(
+      "```makepad\n{}\n```\n{}\n\nDefined in `{}`",
+      widget_signature(widget),
+      chain,
+      self.relative_path(path)
+    )
+  }
+
+  fn instance_hover(
+    &self,
+    path: &Path,
+    document: &ParsedDocument,
+    widget: &WidgetNode,
+    documents: &Documents,
+  ) -> Option<String> {
+    if widget.widget_type.is_empty() {
+      return None;
+    }
+    let mut markdown = format!("```makepad\n{}\n```", widget_signature(widget));
+    if let Some(base) = self.widget_hover(path, document, &widget.widget_type, documents) {
+      markdown.push_str("\n---\n");
+      markdown.push_str(&base);
+    }
+    Some(markdown)
+  }
+
+  /// The chain of types `widget` derives from, nearest first.
+  pub(crate) fn base_types(&self, path: &Path, widget: &WidgetNode, documents: &Documents) -> Vec<String> {
+    let mut chain = vec![];
+    let mut file = path.to_path_buf();
+    let mut current = widget.clone();
+
+    while chain.len() < MAX_RESOLVE_DEPTH && !current.widget_type.is_empty() {
+      if current.kind == WidgetKind::RustBinding {
+        chain.push(format!("{{{{{}}}}}", current.widget_type));
+        break;
+      }
+      if current.widget_type == widget.name || chain.contains(&current.widget_type) {
+        break;
+      }
+      chain.push(current.widget_type.clone());
+
+      let Some((_, document)) = self.parse_file(&file, documents) else {
+        break;
+      };
+      let base = self
+        .resolve_name(&file, &document, &current.widget_type)
+        .into_iter()
+        .find_map(|(ident, token)| match token.ast_node {
+          TokenAstNode::WidgetDefinition(base) => Some((ident.path?, base)),
+          _ => None,
+        });
+      match base {
+        Some((base_file, base)) => {
+          file = base_file;
+          current = base;
+        }
+        None => break,
+      }
+    }
+
+    chain
+  }
+
+  fn constant_hover(
+    &self,
+    path: &Path,
+    document: &ParsedDocument,
+    name: &str,
+    documents: &Documents,
+  ) -> Option<String> {
+    let (file, constant) = self.resolve_constant(path, document, name)?;
+    let (source, _) = self.parse_file(&file, documents)?;
+    Some(self.constant_definition_hover(&file, &source, &constant, documents))
+  }
+
+  fn constant_definition_hover(
+    &self,
+    path: &Path,
+    source: &str,
+    constant: &ConstantNode,
+    documents: &Documents,
+  ) -> String {
+    let written = constant.value_span.text(source);
+    let mut markdown = format!("```makepad\n{} = {}\n```", constant.name, written);
+
+    let value = self.constant_value(path, constant, documents);
+    if let Expression::Constant(alias) = &constant.value {
+      if !matches!(value, Expression::Constant(_)) {
+        markdown.push_str(&format!("\n`({})` resolves to `{}`", alias, expression_text(&value)));
+      }
+    }
+    if let Expression::Color(digits) = &value {
+      if let Some(color) = Rgba::from_hex(digits) {
+        markdown.push_str(&format!("\n\nColor `{}`", color.to_hex()));
+      }
+    }
+    markdown
+  }
+
+  pub(crate) fn resolve_constant(
+    &self,
+    path: &Path,
+    document: &ParsedDocument,
+    name: &str,
+  ) -> Option<(PathBuf, ConstantNode)> {
+    self
+      .resolve_name(path, document, name)
+      .into_iter()
+      .find_map(|(ident, token)| match token.ast_node {
+        TokenAstNode::Constant(constant) => Some((ident.path?, constant)),
+        _ => None,
+      })
+  }
+
+  /// Follows `A = (B)` aliases to the value a constant ends up with.
+  pub(crate) fn constant_value(
+    &self,
+    path: &Path,
+    constant: &ConstantNode,
+    documents: &Documents,
+  ) -> Expression {
+    let mut file = path.to_path_buf();
+    let mut value = constant.value.clone();
+    for _ in 0..MAX_RESOLVE_DEPTH {
+      let Expression::Constant(name) = &value else {
+        break;
+      };
+      let Some((_, document)) = self.parse_file(&file, documents) else {
+        break;
+      };
+      let Some((next_file, next)) = self.resolve_constant(&file, &document, name) else {
+        break;
+      };
+      file = next_file;
+      value = next.value;
+    }
+    value
+  }
+}
+
+/// The property whose name is at `offset`, searching every widget tree of `document`.
+fn property_at(document: &ParsedDocument, offset: usize) -> Option<&PropertyNode> {
+  let mut found = None;
+  for widget in document.widgets() {
+    widget.walk(&mut |node| {
+      if let Some(property) = node.properties.iter().find(|p| p.name_span.contains(offset)) {
+        found = Some(property);
+      }
+    });
+  }
+  found
+}
+
+fn property_hover(property: &PropertyNode) -> Option<String> {
+  if property.modifier.is_some() {
+    return None;
+  }
+  let builtin = builtin_property(&property.name)?;
+  let values = builtin
+    .values
+    .iter()
+    .map(|value| format!("`{}`", value))
+    .collect::<Vec<_>>()
+    .join(", ");
+  Some(format!(
+    "```makepad\n{}: {}\n```\n{}\n\nAllowed values: {}",
+    builtin.name, builtin.ty, builtin.doc, values
+  ))
+}
+
+/// `pub AppTab = <RadioButton>` or `App = {{App}}`.
+fn widget_signature(widget: &WidgetNode) -> String {
+  let visibility = if widget.is_pub { "pub " } else { "" };
+  let ty = match widget.kind {
+    WidgetKind::Instance => format!("<{}>", widget.widget_type),
+    WidgetKind::RustBinding => format!("{{{{{}}}}}", widget.widget_type),
+    WidgetKind::Object => widget.widget_type.clone(),
+  };
+  format!("{}{} = {}", visibility, widget.name, ty)
+}
+
+pub(crate) fn expression_text(value: &Expression) -> String {
+  match value {
+    Expression::Color(digits) => Rgba::from_hex(digits)
+      .map(|color| color.to_hex())
+      .unwrap_or_else(|| format!("#{}", digits)),
+    Expression::Number(number) => number.to_string(),
+    Expression::Boolean(value) => value.to_string(),
+    Expression::String(value) => format!("{:?}", value),
+    Expression::Dep(path) => format!("dep({:?})", path),
+    Expression::Ident(ident) => ident.clone(),
+    Expression::Constant(name) => format!("({})", name),
+    Expression::Computed(text) => format!("({})", text),
+    Expression::Call(name, args) => format!(
+      "{}({})",
+      name,
+      args.iter().map(expression_text).collect::<Vec<_>>().join(", ")
+    ),
+    Expression::Array(items) => format!(
+      "[{}]",
+      items.iter().map(expression_text).collect::<Vec<_>>().join(", ")
+    ),
+    Expression::Object(_) => "{ ... }".to_string(),
+    Expression::Missing => String::new(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::capabilities::tests::{fixture_session, position_of};
+
+  use super::*;
+
+  async fn hover_text(file: &str, needle: &str, offset: u32) -> String {
+    let (session, documents, uri) = fixture_session(file).await;
+    let mut position = position_of(&documents, &uri, needle);
+    position.character += offset;
+    match session.hover(&uri, position, &documents).unwrap() {
+      Some(Hover { contents: HoverContents::Markup(markup), .. }) => markup.value,
+      other => panic!("expected markdown hover, got {:?}", other),
+    }
+  }
+
+  #[tokio::test]
+  async fn test_hover_widget_shows_base_chain_and_file() {
+    let markdown = hover_text("src/home/home_screen.rs", "<AppTab>", 2).await;
+    assert!(markdown.contains("pub AppTab = <RadioButton>"));
+    assert!(markdown.contains("`AppTab` → `RadioButton`"));
+    assert!(markdown.contains("Defined in `src/app.rs`"));
+  }
+
+  #[tokio::test]
+  async fn test_hover_constant_shows_hex() {
+    let markdown = hover_text("src/app.rs", "(APP_TAB_COLOR_SELECTED)", 2).await;
+    assert!(markdown.contains("APP_TAB_COLOR_SELECTED = #091"));
+    assert!(markdown.contains("Color `#009911`"));
+  }
+
+  #[tokio::test]
+  async fn test_hover_property_shows_allowed_values() {
+    let markdown = hover_text("src/app.rs", "flow: Down", 1).await;
+    assert!(markdown.contains("flow: Flow"));
+    assert!(markdown.contains("`Right`, `Down`, `Overlay`, `RightWrap`"));
+
+    let markdown = hover_text("src/app.rs", "align: {", 1).await;
+    assert!(markdown.contains("align: Align"));
+  }
+}
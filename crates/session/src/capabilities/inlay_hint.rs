@@ -0,0 +1,125 @@
+use std::path::Path;
+
+use lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, InlayHintTooltip, Range};
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{
+  parse_document, Expression, LineIndex, ReferenceKind, WidgetKind,
+};
+use url::Url;
+
+use crate::{capabilities::hover::expression_text, Session};
+
+/// The properties whose bare numbers are sizes in logical pixels.
+const SIZE_PROPERTIES: &[&str] = &["width", "height"];
+
+impl Session {
+  /// Hints for the document at the temp `uri` within `range`: the values of `(CONSTANT)`
+  /// references, the framework widget named instances derive from, and the unit of bare sizes.
+  pub fn inlay_hints(
+    &self,
+    uri: &Url,
+    range: Range,
+    documents: &Documents,
+  ) -> Result<Vec<InlayHint>, DocumentError> {
+    let text_document = documents.get_text_document(uri)?;
+    let source = text_document.get_text();
+    let line_index = LineIndex::new(source);
+    let document = parse_document(source);
+    let path = Path::new(uri.path());
+    let mut hints = vec![];
+    let hint = |offset: usize, label: String, kind, padding_left, tooltip: String| InlayHint {
+      position: line_index.position(offset),
+      label: InlayHintLabel::String(label),
+      kind,
+      text_edits: None,
+      tooltip: Some(InlayHintTooltip::String(tooltip)),
+      padding_left: Some(padding_left),
+      padding_right: None,
+      data: None,
+    };
+
+    let constants = document
+      .references()
+      .filter(|reference| reference.kind == ReferenceKind::Constant);
+    for reference in constants {
+      let Some((file, constant)) = self.resolve_constant(path, &document, &reference.name) else {
+        continue;
+      };
+      let value = self.constant_value(&file, &constant, documents);
+      if matches!(value, Expression::Object(_) | Expression::Missing | Expression::Constant(_)) {
+        continue;
+      }
+      // After the closing `)` of `(NAME)`.
+      let rest = &source[reference.span.end..];
+      let offset = match rest.trim_start().strip_prefix(')') {
+        Some(after) => source.len() - after.len(),
+        None => reference.span.end,
+      };
+      let tooltip = format!("`{}` in `{}`", reference.name, self.relative_path(&file));
+      hints.push(hint(offset, expression_text(&value), None, true, tooltip));
+    }
+
+    for widget in document.widgets() {
+      widget.walk(&mut |node| {
+        let is_named_instance = !node.name.is_empty() && node.kind == WidgetKind::Instance;
+        if let Some(type_span) = node.type_span.filter(|_| is_named_instance) {
+          let chain = self.base_types(path, node, documents);
+          if let Some(base) = chain.last().filter(|_| chain.len() > 1) {
+            let tooltip = format!("`{}` derives from {}", node.name, chain.join(" → "));
+            hints.push(hint(type_span.end, format!(": {}", base), Some(InlayHintKind::TYPE), false, tooltip));
+          }
+        }
+        for property in &node.properties {
+          let is_size = SIZE_PROPERTIES.contains(&property.name.as_str());
+          // Numbers written with a unit, such as `20px`, already say it.
+          let is_bare = property.value_span.text(source).ends_with(|c: char| c.is_ascii_digit());
+          if is_size && is_bare && matches!(property.value, Expression::Number(_)) {
+            let tooltip = "A fixed size in logical pixels".to_string();
+            hints.push(hint(property.value_span.end, "px".to_string(), None, false, tooltip));
+          }
+        }
+      });
+    }
+
+    hints.retain(|hint| range.start <= hint.position && hint.position <= range.end);
+    hints.sort_by_key(|hint| hint.position);
+    Ok(hints)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use lsp_types::Position;
+
+  use super::*;
+  use crate::capabilities::tests::fixture_session;
+
+  fn labels(hints: &[InlayHint]) -> Vec<(u32, String)> {
+    hints
+      .iter()
+      .map(|hint| match &hint.label {
+        InlayHintLabel::String(label) => (hint.position.line, label.clone()),
+        InlayHintLabel::LabelParts(_) => unreachable!(),
+      })
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn test_inlay_hints() {
+    let (session, documents, uri) = fixture_session("src/app.rs").await;
+    let everything = Range::new(Position::new(0, 0), Position::new(u32::MAX, 0));
+    let hints = labels(&session.inlay_hints(&uri, everything, &documents).unwrap());
+    assert!(hints.contains(&(43, "#636e82".to_string())));
+    assert!(hints.contains(&(103, ": View".to_string())));
+    assert!(hints.contains(&(105, ": RadioButton".to_string())));
+
+    let (session, documents, uri) = fixture_session("src/home/home_screen.rs").await;
+    let hints = labels(&session.inlay_hints(&uri, everything, &documents).unwrap());
+    assert!(hints.contains(&(18, "px".to_string())));
+    assert!(hints.contains(&(14, "#0f88fe".to_string())));
+
+    let first_lines = Range::new(Position::new(0, 0), Position::new(10, 0));
+    assert!(session.inlay_hints(&uri, first_lines, &documents).unwrap().is_empty());
+  }
+}
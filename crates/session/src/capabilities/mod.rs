@@ -0,0 +1,46 @@
+pub mod code_action;
+pub mod color;
+pub mod completion;
+pub mod definition;
+pub mod diagnostic;
+pub mod document_link;
+pub mod document_symbol;
+pub mod folding_range;
+pub mod formatting;
+pub mod hover;
+pub mod inlay_hint;
+pub mod references;
+pub mod rename;
+pub mod semantic_tokens;
+pub mod signature_help;
+pub mod workspace_symbol;
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use std::path::PathBuf;
+
+  use lsp_types::Position;
+  use makepad_analyzer_document::Documents;
+  use makepad_analyzer_parser::LineIndex;
+  use url::Url;
+
+  use crate::Session;
+
+  /// Opens a session on the fixture package, returning the temp URL of `file`.
+  pub(crate) async fn fixture_session(file: &str) -> (Session, Documents, Url) {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/robrix");
+    let workspace_uri = Url::from_file_path(fixture.join(file)).unwrap();
+    let session = Session::new();
+    let documents = Documents::new();
+    session.init(&workspace_uri, &documents).await.unwrap();
+    let uri = session.sync.workspace_to_temp_url(&workspace_uri).unwrap();
+    (session, documents, uri)
+  }
+
+  /// The position of the first occurrence of `needle` in the document at `uri`.
+  pub(crate) fn position_of(documents: &Documents, uri: &Url, needle: &str) -> Position {
+    let document = documents.get_text_document(uri).unwrap();
+    let source = document.get_text();
+    LineIndex::new(source).position(source.find(needle).unwrap())
+  }
+}
@@ -0,0 +1,272 @@
+use std::{
+  collections::HashMap,
+  path::{Path, PathBuf},
+};
+
+use lsp_types::{DocumentHighlight, DocumentHighlightKind, Location, Position, Range};
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{
+  parse_document, LineIndex, ParsedDocument, ReferenceKind, TokenAstNode, TokenIdent,
+};
+use url::Url;
+
+use crate::{
+  resolve::{is_definition, symbol_at, SymbolAt},
+  Session,
+};
+
+/// An occurrence of a symbol in one of the session's files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolUse {
+  /// The temp path of the file.
+  pub path: PathBuf,
+  pub range: Range,
+  pub kind: SymbolUseKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolUseKind {
+  /// `AppTab = <RadioButton> {}`
+  Declaration,
+  /// `use crate::app::AppTab;`
+  Import,
+  /// `<AppTab>`, `(APP_TAB_COLOR)` or `{{App}}`
+  Reference,
+}
+
+/// What the cursor points at, once resolved.
+pub(crate) struct SymbolTarget {
+  pub name: String,
+  /// The `live_design!` declaration, `None` for names the package does not declare, such as
+  /// framework widgets or Rust types.
+  pub definition: Option<TokenIdent>,
+  pub kind: ReferenceKind,
+  /// The range of the name under the cursor.
+  pub range: Range,
+}
+
+impl Session {
+  /// Lists every use of the symbol at `position` across the package.
+  pub fn references(
+    &self,
+    uri: &Url,
+    position: Position,
+    include_declaration: bool,
+    documents: &Documents,
+  ) -> Result<Option<Vec<Location>>, DocumentError> {
+    let Some(target) = self.symbol_target(uri, position, documents)? else {
+      return Ok(None);
+    };
+    let locations: Vec<Location> = self
+      .symbol_uses(&target, documents)
+      .into_iter()
+      .filter(|symbol| include_declaration || symbol.kind != SymbolUseKind::Declaration)
+      .filter_map(|symbol| Some(Location::new(self.workspace_url(&symbol.path)?, symbol.range)))
+      .collect();
+    Ok(Some(locations))
+  }
+
+  /// Highlights the uses of the symbol at `position` within its own document.
+  pub fn document_highlights(
+    &self,
+    uri: &Url,
+    position: Position,
+    documents: &Documents,
+  ) -> Result<Option<Vec<DocumentHighlight>>, DocumentError> {
+    let Some(target) = self.symbol_target(uri, position, documents)? else {
+      return Ok(None);
+    };
+    let path = Path::new(uri.path());
+    let highlights: Vec<DocumentHighlight> = self
+      .symbol_uses(&target, documents)
+      .into_iter()
+      .filter(|symbol| symbol.path == path)
+      .map(|symbol| DocumentHighlight {
+        range: symbol.range,
+        kind: Some(match symbol.kind {
+          SymbolUseKind::Declaration => DocumentHighlightKind::WRITE,
+          _ => DocumentHighlightKind::READ,
+        }),
+      })
+      .collect();
+    Ok(Some(highlights))
+  }
+
+  /// Resolves the symbol at `position` in the document at the temp `uri`.
+  pub(crate) fn symbol_target(
+    &self,
+    uri: &Url,
+    position: Position,
+    documents: &Documents,
+  ) -> Result<Option<SymbolTarget>, DocumentError> {
+    let text_document = documents.get_text_document(uri)?;
+    let source = text_document.get_text();
+    let document = parse_document(source);
+    let path = Path::new(uri.path());
+    let line_index = LineIndex::new(source);
+
+    let target = match symbol_at(self, path, source, &document, position) {
+      Some(SymbolAt::Reference(reference)) => Some(SymbolTarget {
+        definition: self.definition_of(path, &document, &reference.name),
+        name: reference.name,
+        kind: reference.kind,
+        range: line_index.range(reference.span),
+      }),
+      Some(SymbolAt::Declaration(ident, token)) if is_definition(&token) => Some(SymbolTarget {
+        name: ident.name.clone(),
+        range: ident.range,
+        kind: match token.ast_node {
+          TokenAstNode::Constant(_) => ReferenceKind::Constant,
+          _ => ReferenceKind::Widget,
+        },
+        definition: Some(ident),
+      }),
+      Some(SymbolAt::ImportSegment(import, index))
+        if !import.is_glob && index == import.segments.len() - 1 =>
+      {
+        let segment = &import.segments[index];
+        let name = segment.name.clone();
+        let definition = self.definition_of(path, &document, &name);
+        let kind = match definition
+          .as_ref()
+          .and_then(|ident| self.token_map().get(ident))
+          .map(|token| token.ast_node.clone())
+        {
+          Some(TokenAstNode::Constant(_)) => ReferenceKind::Constant,
+          _ => ReferenceKind::Widget,
+        };
+        Some(SymbolTarget {
+          name,
+          definition,
+          kind,
+          range: line_index.range(segment.span),
+        })
+      }
+      _ => None,
+    };
+    Ok(target)
+  }
+
+  fn definition_of(&self, path: &Path, document: &ParsedDocument, name: &str) -> Option<TokenIdent> {
+    self
+      .resolve_name(path, document, name)
+      .into_iter()
+      .map(|(ident, _)| ident)
+      .next()
+  }
+
+  /// Collects the declaration, imports and uses of `target` across the session's files.
+  ///
+  /// A use only counts when it resolves to the same declaration, so two files declaring
+  /// their own `AppTab` keep their references apart.
+  pub(crate) fn symbol_uses(&self, target: &SymbolTarget, documents: &Documents) -> Vec<SymbolUse> {
+    let mut parsed: HashMap<PathBuf, Option<ParsedDocument>> = HashMap::new();
+    let mut resolves_to_target = |file: &Path| -> bool {
+      let document = parsed
+        .entry(file.to_path_buf())
+        .or_insert_with(|| self.parse_file(file, documents).map(|(_, document)| document));
+      let Some(document) = document else {
+        return false;
+      };
+      match &target.definition {
+        Some(definition) => self
+          .resolve_name(file, document, &target.name)
+          .iter()
+          .any(|(ident, _)| ident == definition),
+        None => true,
+      }
+    };
+
+    let mut uses = vec![];
+    if let Some(definition) = &target.definition {
+      if let Some(path) = &definition.path {
+        uses.push(SymbolUse {
+          path: path.clone(),
+          range: definition.range,
+          kind: SymbolUseKind::Declaration,
+        });
+      }
+    }
+
+    let mut imports: Vec<(PathBuf, Range)> = self
+      .token_map()
+      .tokens_for_name(&target.name)
+      .into_iter()
+      .filter(|(_, token)| matches!(token.ast_node, TokenAstNode::Import(_)))
+      .filter_map(|(ident, _)| Some((ident.path?, ident.range)))
+      .collect();
+    imports.sort_by_key(|(path, range)| (path.clone(), range.start));
+    for (path, range) in imports {
+      if resolves_to_target(&path) {
+        uses.push(SymbolUse { path, range, kind: SymbolUseKind::Import });
+      }
+    }
+
+    let mut references = self.token_map().references_for_name(&target.name);
+    references.sort_by_key(|(path, reference)| (path.clone(), reference.range.start));
+    for (path, reference) in references {
+      if reference.kind == target.kind && resolves_to_target(&path) {
+        uses.push(SymbolUse {
+          path,
+          range: reference.range,
+          kind: SymbolUseKind::Reference,
+        });
+      }
+    }
+
+    uses
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::capabilities::tests::{fixture_session, position_of};
+
+  use super::*;
+
+  fn files(locations: &[Location]) -> Vec<(String, u32)> {
+    locations
+      .iter()
+      .map(|location| {
+        let path = location.uri.path();
+        let file = &path[path.find("/src/").unwrap() + 1..];
+        (file.to_string(), location.range.start.line)
+      })
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn test_widget_references_across_package() {
+    let (session, documents, uri) = fixture_session("src/app.rs").await;
+    let position = position_of(&documents, &uri, "<AppTab>");
+    let position = Position::new(position.line, position.character + 2);
+
+    let locations = session.references(&uri, position, true, &documents).unwrap().unwrap();
+    assert_eq!(files(&locations), vec![
+      ("src/app.rs".to_string(), 17),
+      ("src/home/home_screen.rs".to_string(), 7),
+      ("src/app.rs".to_string(), 105),
+      ("src/home/home_screen.rs".to_string(), 19),
+      ("src/home/home_screen.rs".to_string(), 23),
+    ]);
+
+    let locations = session.references(&uri, position, false, &documents).unwrap().unwrap();
+    assert_eq!(locations.len(), 4);
+  }
+
+  #[tokio::test]
+  async fn test_constant_references_include_shader_bodies() {
+    let (session, documents, uri) = fixture_session("src/app.rs").await;
+    let position = position_of(&documents, &uri, "APP_TAB_COLOR_HOVER =");
+
+    let locations = session.references(&uri, position, false, &documents).unwrap().unwrap();
+    let lines: Vec<u32> = files(&locations).into_iter().map(|(_, line)| line).collect();
+    // One use in `fn pixel`, one in `draw_text` and one in `draw_icon`.
+    assert_eq!(lines, vec![43, 56, 74]);
+
+    let highlights = session.document_highlights(&uri, position, &documents).unwrap().unwrap();
+    assert_eq!(highlights.len(), 4);
+    assert_eq!(highlights[0].kind, Some(DocumentHighlightKind::WRITE));
+  }
+}
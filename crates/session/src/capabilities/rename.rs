@@ -0,0 +1,276 @@
+use std::{collections::HashMap, path::{Path, PathBuf}};
+
+use lsp_types::{Position, PrepareRenameResponse, Range, TextEdit, WorkspaceEdit};
+use makepad_analyzer_core::errors::{MakepadAnalyzerError, RenameError};
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{
+  lex_live_design_blocks, parse_document, LineIndex, ReferenceKind, TokenAstNode, WidgetKind,
+};
+use url::Url;
+
+use crate::{
+  capabilities::references::SymbolTarget,
+  resolve::{framework_module, rust_item_occurrences},
+  Session,
+};
+
+impl Session {
+  /// Checks that the symbol at `position` can be renamed, returning the range of its name.
+  pub fn prepare_rename(
+    &self,
+    uri: &Url,
+    position: Position,
+    documents: &Documents,
+  ) -> Result<Option<PrepareRenameResponse>, MakepadAnalyzerError> {
+    let target = self.rename_target(uri, position, documents)?;
+    Ok(Some(PrepareRenameResponse::Range(target.range)))
+  }
+
+  /// Renames the `live_design!` widget or constant at `position` across the package.
+  ///
+  /// The edit covers the declaration, every `<Name>` or `(NAME)` use and `use crate::...::Name`
+  /// import. A widget bound to a Rust struct of the same name (`App = {{App}}`) also renames
+  /// the `{{App}}` bindings and, when the package declares the struct, its uses in the Rust
+  /// files of the package, such as `use crate::app::App;` or `impl App` where it is imported.
+  pub fn rename(
+    &self,
+    uri: &Url,
+    position: Position,
+    new_name: &str,
+    documents: &Documents,
+  ) -> Result<Option<WorkspaceEdit>, MakepadAnalyzerError> {
+    let target = self.rename_target(uri, position, documents)?;
+    validate_name(new_name, target.kind)?;
+
+    let mut edits: Vec<(PathBuf, Range)> = self
+      .symbol_uses(&target, documents)
+      .into_iter()
+      .map(|symbol| (symbol.path, symbol.range))
+      .collect();
+
+    if self.is_rust_binding(&target) {
+      for (path, reference) in self.token_map().references_for_name(&target.name) {
+        if reference.kind == ReferenceKind::RustType {
+          edits.push((path, reference.range));
+        }
+      }
+      let path = Path::new(uri.path());
+      let home = self.find_rust_struct(path, &target.name, documents).map(|(file, _)| file);
+      let item_path = home.as_ref().and_then(|home| self.import_path(home, &target.name));
+      let module = item_path.as_deref().and_then(|path| path.rsplit_once("::"));
+      if let (Some(home), Some((module, _)), Some(src_dir)) = (&home, module, self.src_dir()) {
+        for entry in documents.iter() {
+          let file = Path::new(entry.key());
+          if !file.starts_with(&src_dir) {
+            continue;
+          }
+          let source = entry.value().get_text();
+          let blocks: Vec<_> =
+            lex_live_design_blocks(source).iter().map(|block| block.span).collect();
+          let line_index = LineIndex::new(source);
+          let is_home = file == home;
+          for span in rust_item_occurrences(source, module, &target.name, is_home, &blocks) {
+            edits.push((file.to_path_buf(), line_index.range(span)));
+          }
+        }
+      }
+    }
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for (path, range) in edits {
+      let Some(url) = self.workspace_url(&path) else {
+        continue;
+      };
+      let file_edits = changes.entry(url).or_default();
+      if !file_edits.iter().any(|edit| edit.range == range) {
+        file_edits.push(TextEdit::new(range, new_name.to_string()));
+      }
+    }
+    for file_edits in changes.values_mut() {
+      file_edits.sort_by_key(|edit| edit.range.start);
+    }
+
+    Ok(Some(WorkspaceEdit::new(changes)))
+  }
+
+  /// The symbol at `position`, provided the package declares it.
+  fn rename_target(
+    &self,
+    uri: &Url,
+    position: Position,
+    documents: &Documents,
+  ) -> Result<SymbolTarget, MakepadAnalyzerError> {
+    let target = self
+      .symbol_target(uri, position, documents)?
+      .ok_or(RenameError::NoSymbol)?;
+    if target.definition.is_some() {
+      return Ok(target);
+    }
+
+    let document = parse_document(documents.get_text_document(uri)?.get_text());
+    Err(match framework_module(&document, &target.name) {
+      Some(module) => RenameError::FrameworkSymbol {
+        name: target.name,
+        module,
+      },
+      None => RenameError::SymbolNotDeclared { name: target.name },
+    }
+    .into())
+  }
+
+  /// Whether the target is declared as `Name = {{Name}} { ... }`.
+  fn is_rust_binding(&self, target: &SymbolTarget) -> bool {
+    let Some(definition) = &target.definition else {
+      return false;
+    };
+    self.token_map().get(definition).is_some_and(|token| match &token.ast_node {
+      TokenAstNode::WidgetDefinition(widget) => {
+        widget.kind == WidgetKind::RustBinding && widget.widget_type == target.name
+      }
+      _ => false,
+    })
+  }
+}
+
+/// Widgets are used as `<Name>`, which the DSL only accepts for names starting in uppercase.
+fn validate_name(name: &str, kind: ReferenceKind) -> Result<(), RenameError> {
+  let mut chars = name.chars();
+  let valid = chars
+    .next()
+    .is_some_and(|first| match kind {
+      ReferenceKind::Constant => first == '_' || first.is_alphabetic(),
+      _ => first.is_uppercase(),
+    })
+    && chars.all(|c| c == '_' || c.is_alphanumeric());
+  if valid {
+    Ok(())
+  } else {
+    Err(RenameError::InvalidName {
+      name: name.to_string(),
+      kind: match kind {
+        ReferenceKind::Constant => "constant",
+        _ => "widget",
+      }
+      .to_string(),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use lsp_types::TextDocumentContentChangeEvent;
+
+  use crate::capabilities::tests::{fixture_session, position_of};
+
+  use super::*;
+
+  fn edited(edit: &WorkspaceEdit) -> Vec<(String, u32, u32)> {
+    let mut edits: Vec<(String, u32, u32)> = edit
+      .changes
+      .as_ref()
+      .unwrap()
+      .iter()
+      .flat_map(|(url, edits)| {
+        let path = url.path();
+        let file = path[path.find("/src/").unwrap() + 1..].to_string();
+        edits
+          .iter()
+          .map(move |edit| (file.clone(), edit.range.start.line, edit.range.start.character))
+      })
+      .collect();
+    edits.sort();
+    edits
+  }
+
+  #[tokio::test]
+  async fn test_rename_widget_across_package() {
+    let (session, documents, uri) = fixture_session("src/home/home_screen.rs").await;
+    let position = position_of(&documents, &uri, "<AppTab>");
+    let position = Position::new(position.line, position.character + 1);
+
+    let range = match session.prepare_rename(&uri, position, &documents).unwrap() {
+      Some(PrepareRenameResponse::Range(range)) => range,
+      other => panic!("expected a range, got {:?}", other),
+    };
+    assert_eq!(range.start, position);
+
+    let edit = session.rename(&uri, position, "MainTab", &documents).unwrap().unwrap();
+    assert_eq!(edited(&edit), vec![
+      ("src/app.rs".to_string(), 17, 8),
+      ("src/app.rs".to_string(), 105, 32),
+      ("src/home/home_screen.rs".to_string(), 7, 20),
+      ("src/home/home_screen.rs".to_string(), 19, 24),
+      ("src/home/home_screen.rs".to_string(), 23, 28),
+    ]);
+    assert!(edit.changes.unwrap().keys().all(|url| url.path().contains("tests/fixtures/robrix")));
+  }
+
+  #[tokio::test]
+  async fn test_rename_rust_binding() {
+    let (session, documents, uri) = fixture_session("src/app.rs").await;
+    let position = position_of(&documents, &uri, "App = {{App}}");
+
+    // Another module naming the struct, in code, a char literal and a raw string.
+    let lib = Url::parse(&uri.as_str().replace("/src/app.rs", "/src/lib.rs")).unwrap();
+    let text = [
+      "pub mod app;",
+      "use crate::app::App;",
+      "const Q: char = '\"';",
+      "impl App {}",
+      "const S: &str = r#\"App\"#;",
+      "fn screen() -> Screen { Screen::App }",
+      "fn app(app: crate::app::App) -> makepad_widgets::App { todo!() }",
+    ]
+    .join("\n");
+    let change = TextDocumentContentChangeEvent { range: None, range_length: None, text };
+    documents.update_text_document(&lib, &[change]).unwrap();
+
+    // A module using another `App` without importing this one.
+    let other = Url::parse(&uri.as_str().replace("/src/app.rs", "/src/shared/mod.rs")).unwrap();
+    let text = "pub mod popup_list;\nuse other_crate::App;\nstruct Other(App);".to_string();
+    let change = TextDocumentContentChangeEvent { range: None, range_length: None, text };
+    documents.update_text_document(&other, &[change]).unwrap();
+
+    let edit = session.rename(&uri, position, "Robrix", &documents).unwrap().unwrap();
+    let lines: Vec<(String, u32)> =
+      edited(&edit).into_iter().map(|(file, line, _)| (file, line)).collect();
+    // The declaration, `{{App}}`, `app_main!(App)`, `struct App` and `impl LiveRegister for App`,
+    // then the `use`, the `impl` and the `crate::app::App` path of `lib.rs`.
+    let app = |line: u32| ("src/app.rs".to_string(), line);
+    let lib = |line: u32| ("src/lib.rs".to_string(), line);
+    assert_eq!(lines, vec![
+      app(90),
+      app(90),
+      app(121),
+      app(124),
+      app(128),
+      lib(1),
+      lib(3),
+      lib(6)
+    ]);
+  }
+
+  #[tokio::test]
+  async fn test_rename_refuses_framework_widgets() {
+    let (session, documents, uri) = fixture_session("src/app.rs").await;
+    let position = position_of(&documents, &uri, "<RadioButton>");
+    let position = Position::new(position.line, position.character + 1);
+
+    let err = session.rename(&uri, position, "Radio", &documents).unwrap_err();
+    assert!(matches!(
+      err,
+      MakepadAnalyzerError::RenameError(RenameError::FrameworkSymbol { ref module, .. })
+        if module == "link::widgets"
+    ));
+    assert!(session.prepare_rename(&uri, position, &documents).is_err());
+  }
+
+  #[tokio::test]
+  async fn test_rename_rejects_invalid_names() {
+    let (session, documents, uri) = fixture_session("src/app.rs").await;
+    let position = position_of(&documents, &uri, "AppTab = <");
+
+    let err = session.rename(&uri, position, "app_tab", &documents).unwrap_err();
+    assert!(matches!(err, MakepadAnalyzerError::RenameError(RenameError::InvalidName { .. })));
+  }
+}
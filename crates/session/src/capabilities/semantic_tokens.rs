@@ -0,0 +1,393 @@
+use std::{
+  collections::BTreeMap,
+  path::Path,
+  sync::atomic::{AtomicU64, Ordering::Relaxed},
+};
+
+use lsp_types::{
+  SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensDelta,
+  SemanticTokensEdit, SemanticTokensFullDeltaResult, SemanticTokensLegend,
+};
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{
+  parse_document, LineIndex, LiveDSLASTNode, Punct, ReferenceKind, Span, Token, TokenKind,
+  WidgetNode,
+};
+use url::Url;
+
+use crate::Session;
+
+/// The token types, in legend order. `color` and `unit` are not standard LSP types; clients
+/// that don't know them keep their TextMate colors for those tokens.
+pub const TOKEN_TYPES: &[SemanticTokenType] = &[
+  SemanticTokenType::NAMESPACE,
+  SemanticTokenType::TYPE,
+  SemanticTokenType::CLASS,
+  SemanticTokenType::STRUCT,
+  SemanticTokenType::VARIABLE,
+  SemanticTokenType::PROPERTY,
+  SemanticTokenType::ENUM_MEMBER,
+  SemanticTokenType::FUNCTION,
+  SemanticTokenType::KEYWORD,
+  SemanticTokenType::NUMBER,
+  SemanticTokenType::STRING,
+  SemanticTokenType::COMMENT,
+  SemanticTokenType::new("color"),
+  SemanticTokenType::new("unit"),
+];
+
+pub const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[
+  SemanticTokenModifier::DECLARATION,
+  SemanticTokenModifier::READONLY,
+  SemanticTokenModifier::DEFAULT_LIBRARY,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenType {
+  Namespace,
+  Type,
+  Class,
+  Struct,
+  Variable,
+  Property,
+  EnumMember,
+  Function,
+  Keyword,
+  Number,
+  String,
+  Comment,
+  Color,
+  Unit,
+}
+
+const DECLARATION: u32 = 1;
+const READONLY: u32 = 1 << 1;
+const DEFAULT_LIBRARY: u32 = 1 << 2;
+
+const KEYWORDS: &[&str] = &[
+  "use", "pub", "fn", "let", "var", "return", "if", "else", "for", "in", "while", "break",
+  "continue", "self", "true", "false", "instance", "uniform", "varying", "texture", "const",
+];
+
+const SHADER_TYPES: &[&str] = &[
+  "float", "vec2", "vec3", "vec4", "mat2", "mat3", "mat4", "bool", "int", "ivec2", "ivec3",
+  "ivec4", "bvec2", "bvec3", "bvec4", "texture2D",
+];
+
+static RESULT_ID: AtomicU64 = AtomicU64::new(0);
+
+pub fn legend() -> SemanticTokensLegend {
+  SemanticTokensLegend {
+    token_types: TOKEN_TYPES.to_vec(),
+    token_modifiers: TOKEN_MODIFIERS.to_vec(),
+  }
+}
+
+impl Session {
+  /// Classifies the `live_design!` tokens of the document at the temp `uri`.
+  pub fn semantic_tokens_full(
+    &self,
+    uri: &Url,
+    documents: &Documents,
+  ) -> Result<SemanticTokens, DocumentError> {
+    let document = documents.get_text_document(uri)?;
+    let tokens = SemanticTokens {
+      result_id: Some(RESULT_ID.fetch_add(1, Relaxed).to_string()),
+      data: semantic_tokens(document.get_text()),
+    };
+    self
+      .semantic_tokens
+      .insert(Path::new(uri.path()).to_path_buf(), tokens.clone());
+    Ok(tokens)
+  }
+
+  /// Like [`Session::semantic_tokens_full`], but answers with the edits to the tokens sent as
+  /// `previous_result_id` when the session still has them.
+  pub fn semantic_tokens_delta(
+    &self,
+    uri: &Url,
+    previous_result_id: &str,
+    documents: &Documents,
+  ) -> Result<SemanticTokensFullDeltaResult, DocumentError> {
+    let previous = self
+      .semantic_tokens
+      .get(Path::new(uri.path()))
+      .filter(|previous| previous.result_id.as_deref() == Some(previous_result_id))
+      .map(|previous| previous.data.clone());
+    let tokens = self.semantic_tokens_full(uri, documents)?;
+
+    Ok(match previous {
+      Some(previous) => SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+        result_id: tokens.result_id,
+        edits: diff_tokens(&previous, &tokens.data).into_iter().collect(),
+      }),
+      None => SemanticTokensFullDeltaResult::Tokens(tokens),
+    })
+  }
+}
+
+/// Encodes the classified tokens of every `live_design!` block in `source`.
+pub fn semantic_tokens(source: &str) -> Vec<SemanticToken> {
+  let document = parse_document(source);
+  let mut classified: BTreeMap<usize, (Span, TokenType, u32)> = BTreeMap::new();
+
+  for block in &document.blocks {
+    // What the AST knows takes precedence over what the token stream suggests.
+    for node in &block.nodes {
+      match node {
+        LiveDSLASTNode::Import(import) => {
+          let last = import.segments.len().saturating_sub(1);
+          for (index, segment) in import.segments.iter().enumerate() {
+            let ty = if index == last && !import.is_glob {
+              TokenType::Type
+            } else {
+              TokenType::Namespace
+            };
+            classified.insert(segment.span.start, (segment.span, ty, 0));
+          }
+        }
+        LiveDSLASTNode::Constant(constant) => {
+          classified.insert(
+            constant.name_span.start,
+            (constant.name_span, TokenType::Variable, DECLARATION | READONLY),
+          );
+        }
+        LiveDSLASTNode::Widget(widget) => {
+          if let Some(name_span) = widget.name_span {
+            classified.insert(name_span.start, (name_span, TokenType::Class, DECLARATION));
+          }
+          widget.walk(&mut |node| classify_widget(node, &mut classified));
+        }
+      }
+    }
+    for reference in &block.references {
+      let (ty, modifiers) = match reference.kind {
+        ReferenceKind::Widget => (TokenType::Type, 0),
+        ReferenceKind::Constant => (TokenType::Variable, READONLY),
+        ReferenceKind::RustType => (TokenType::Struct, 0),
+      };
+      classified.entry(reference.span.start).or_insert((reference.span, ty, modifiers));
+    }
+
+    let tokens: Vec<&Token> = block.tokens.iter().filter(|token| !token.is_trivia()).collect();
+    for comment in block.tokens.iter().filter(|token| token.is_trivia()) {
+      classified.insert(comment.span.start, (comment.span, TokenType::Comment, 0));
+    }
+    for (index, token) in tokens.iter().enumerate() {
+      let Some((span, ty, modifiers)) = classify_token(index, source, &tokens) else {
+        continue;
+      };
+      classified.entry(span.start).or_insert((span, ty, modifiers));
+      if let TokenKind::Number { unit: Some(unit), .. } = &token.kind {
+        let unit_span = Span::new(token.span.end - unit.len(), token.span.end);
+        classified.insert(unit_span.start, (unit_span, TokenType::Unit, 0));
+      }
+    }
+  }
+
+  encode(source, classified.into_values())
+}
+
+fn classify_widget(widget: &WidgetNode, classified: &mut BTreeMap<usize, (Span, TokenType, u32)>) {
+  for child in &widget.children {
+    if let Some(name_span) = child.name_span.filter(|_| !child.name.is_empty()) {
+      classified.insert(name_span.start, (name_span, TokenType::Variable, DECLARATION));
+    }
+  }
+  for property in &widget.properties {
+    let ty = if property.modifier.is_some() {
+      TokenType::Variable
+    } else {
+      TokenType::Property
+    };
+    classified.insert(property.name_span.start, (property.name_span, ty, DECLARATION));
+  }
+  for shader_fn in &widget.shader_fns {
+    classified.insert(
+      shader_fn.name_span.start,
+      (shader_fn.name_span, TokenType::Function, DECLARATION),
+    );
+  }
+}
+
+/// Classifies a token by its kind and neighbours, for tokens the AST doesn't cover such as
+/// those in shader bodies.
+fn classify_token(index: usize, source: &str, tokens: &[&Token]) -> Option<(Span, TokenType, u32)> {
+  let token = tokens[index];
+  let span = token.span;
+  let classified = match &token.kind {
+    TokenKind::String(_) | TokenKind::Dep(_) => (span, TokenType::String, 0),
+    TokenKind::Color(_) => (span, TokenType::Color, 0),
+    TokenKind::Number { unit, .. } => {
+      let end = span.end - unit.as_ref().map_or(0, |unit| unit.len());
+      (Span::new(span.start, end), TokenType::Number, 0)
+    }
+    TokenKind::WidgetRef(_) => (token.name_span(source), TokenType::Type, 0),
+    TokenKind::RustBinding(_) => (token.name_span(source), TokenType::Struct, 0),
+    TokenKind::Ident(ident) => {
+      let previous = index.checked_sub(1).map(|i| &tokens[i].kind);
+      let next = tokens.get(index + 1).map(|token| &token.kind);
+      if KEYWORDS.contains(&ident.as_str()) {
+        (span, TokenType::Keyword, 0)
+      } else if SHADER_TYPES.contains(&ident.as_str()) {
+        (span, TokenType::Type, DEFAULT_LIBRARY)
+      } else if matches!(previous, Some(TokenKind::Ident(keyword)) if keyword == "fn") {
+        (span, TokenType::Function, DECLARATION)
+      } else if matches!(next, Some(TokenKind::Punct(Punct::LParen))) {
+        (span, TokenType::Function, 0)
+      } else if matches!(next, Some(TokenKind::Punct(Punct::Colon)))
+        || matches!(previous, Some(TokenKind::Punct(Punct::Dot)))
+      {
+        (span, TokenType::Property, 0)
+      } else if ident.starts_with(char::is_uppercase) {
+        (span, TokenType::EnumMember, 0)
+      } else {
+        return None;
+      }
+    }
+    _ => return None,
+  };
+  Some(classified)
+}
+
+/// Delta-encodes classified spans, splitting the ones that cross lines since not every client
+/// supports multiline tokens.
+fn encode(source: &str, spans: impl Iterator<Item = (Span, TokenType, u32)>) -> Vec<SemanticToken> {
+  let line_index = LineIndex::new(source);
+  let mut data = vec![];
+  let (mut last_line, mut last_start) = (0, 0);
+  let mut push = |start: usize, end: usize, ty: TokenType, modifiers: u32| {
+    let from = line_index.position(start);
+    let to = line_index.position(end);
+    if to.character <= from.character {
+      return;
+    }
+    let delta_line = from.line - last_line;
+    let delta_start = if delta_line == 0 { from.character - last_start } else { from.character };
+    data.push(SemanticToken {
+      delta_line,
+      delta_start,
+      length: to.character - from.character,
+      token_type: ty as u32,
+      token_modifiers_bitset: modifiers,
+    });
+    (last_line, last_start) = (from.line, from.character);
+  };
+
+  let mut covered_until = 0;
+  for (span, ty, modifiers) in spans {
+    if span.start < covered_until {
+      continue;
+    }
+    covered_until = span.end;
+    let mut start = span.start;
+    for (offset, _) in span.text(source).match_indices('\n') {
+      push(start, span.start + offset, ty, modifiers);
+      start = span.start + offset + 1;
+    }
+    push(start, span.end, ty, modifiers);
+  }
+  data
+}
+
+/// A single edit turning `previous` into `current`, `None` when they are equal.
+fn diff_tokens(previous: &[SemanticToken], current: &[SemanticToken]) -> Option<SemanticTokensEdit> {
+  let prefix = previous
+    .iter()
+    .zip(current)
+    .take_while(|(a, b)| a == b)
+    .count();
+  if prefix == previous.len() && prefix == current.len() {
+    return None;
+  }
+  let suffix = previous[prefix..]
+    .iter()
+    .rev()
+    .zip(current[prefix..].iter().rev())
+    .take_while(|(a, b)| a == b)
+    .count();
+  // Edits count the flattened `u32` array, five per token.
+  Some(SemanticTokensEdit {
+    start: (prefix * 5) as u32,
+    delete_count: ((previous.len() - prefix - suffix) * 5) as u32,
+    data: Some(current[prefix..current.len() - suffix].to_vec()),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Decodes the tokens back into `(text, type, modifiers)` for readable assertions.
+  fn decode<'a>(source: &'a str, data: &[SemanticToken]) -> Vec<(&'a str, String, u32)> {
+    let line_index = LineIndex::new(source);
+    let (mut line, mut character) = (0, 0);
+    data
+      .iter()
+      .map(|token| {
+        line += token.delta_line;
+        character = if token.delta_line == 0 { character + token.delta_start } else { token.delta_start };
+        let start = line_index.offset(lsp_types::Position::new(line, character));
+        let end = line_index.offset(lsp_types::Position::new(line, character + token.length));
+        let ty = TOKEN_TYPES[token.token_type as usize].as_str().to_string();
+        (&source[start..end], ty, token.token_modifiers_bitset)
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_semantic_tokens_classification() {
+    let source = r#"live_design! {
+    use link::widgets::*;
+    COLOR = #2A
+    AppTab = <RadioButton> {
+        width: 20.5px, flow: Down
+        draw_radio: {
+            instance hover: 0.0
+            fn pixel(self) -> vec4 { return mix(#0000, (COLOR), self.hover); }
+        }
+    }
+    App = {{App}} { tab = <AppTab> {} }
+}"#;
+    let tokens = decode(source, &semantic_tokens(source));
+    let find = |text: &str| {
+      tokens
+        .iter()
+        .find(|(t, _, _)| *t == text)
+        .map(|(_, ty, modifiers)| (ty.as_str(), *modifiers))
+        .unwrap_or_else(|| panic!("`{}` was not classified", text))
+    };
+
+    assert_eq!(find("link"), ("namespace", 0));
+    assert_eq!(find("COLOR"), ("variable", DECLARATION | READONLY));
+    assert_eq!(find("#2A"), ("color", 0));
+    assert_eq!(find("AppTab"), ("class", DECLARATION));
+    assert_eq!(find("RadioButton"), ("type", 0));
+    assert_eq!(find("width"), ("property", DECLARATION));
+    assert_eq!(find("20.5"), ("number", 0));
+    assert_eq!(find("px"), ("unit", 0));
+    assert_eq!(find("Down"), ("enumMember", 0));
+    assert_eq!(find("instance"), ("keyword", 0));
+    assert_eq!(find("hover"), ("variable", DECLARATION));
+    assert_eq!(find("fn"), ("keyword", 0));
+    assert_eq!(find("pixel"), ("function", DECLARATION));
+    assert_eq!(find("vec4"), ("type", DEFAULT_LIBRARY));
+    assert_eq!(find("mix"), ("function", 0));
+    assert_eq!(find("tab"), ("variable", DECLARATION));
+    assert!(tokens.contains(&("App", "struct".to_string(), 0)));
+    // `(COLOR)` inside the shader body is a constant reference.
+    assert_eq!(tokens.iter().filter(|(t, ty, _)| *t == "COLOR" && ty == "variable").count(), 2);
+  }
+
+  #[test]
+  fn test_semantic_tokens_delta() {
+    let before = semantic_tokens("live_design! { A = <View> { width: Fill } }");
+    let after = semantic_tokens("live_design! { A = <View> { width: Fill, height: Fit } }");
+    let edit = diff_tokens(&before, &after).unwrap();
+    assert_eq!(edit.start, 5 * 4);
+    assert_eq!(edit.delete_count, 0);
+    assert_eq!(edit.data.unwrap().len(), 2);
+    assert!(diff_tokens(&after, &after).is_none());
+  }
+}
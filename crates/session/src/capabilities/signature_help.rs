@@ -0,0 +1,51 @@
+use lsp_types::{Position, SignatureHelp};
+use makepad_analyzer_core::errors::DocumentError;
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{parse_document, LineIndex};
+use url::Url;
+
+use crate::{shader::shader_fn_at, Session};
+
+impl Session {
+  /// The signature of the shader builtin or `Sdf2d` method called at `position` of the
+  /// document at the temp `uri`, `None` outside shader `fn` bodies.
+  pub fn signature_help(
+    &self,
+    uri: &Url,
+    position: Position,
+    documents: &Documents,
+  ) -> Result<Option<SignatureHelp>, DocumentError> {
+    let text_document = documents.get_text_document(uri)?;
+    let source = text_document.get_text();
+    let offset = LineIndex::new(source).offset(position);
+    let document = parse_document(source);
+    Ok(shader_fn_at(&document, offset).and_then(|(_, shader_fn)| {
+      let body = shader_fn.body_span.text(source);
+      makepad_analyzer_plugin_shader::signature_help(body, offset - shader_fn.body_span.start)
+    }))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use lsp_types::ParameterLabel;
+
+  use crate::capabilities::tests::{fixture_session, position_of};
+
+  #[tokio::test]
+  async fn test_signature_help() {
+    let (session, documents, uri) = fixture_session("src/app.rs").await;
+    let position = position_of(&documents, &uri, "self.hover\n                        ),");
+    let help = session.signature_help(&uri, position, &documents).unwrap().unwrap();
+    let signature = &help.signatures[0];
+    assert_eq!(signature.label, "mix(x: T, y: T, a: float) -> T");
+    assert_eq!(help.active_parameter, Some(2));
+    assert_eq!(
+      signature.parameters.as_ref().unwrap()[2].label,
+      ParameterLabel::LabelOffsets([16, 24])
+    );
+
+    let position = position_of(&documents, &uri, "flow: Overlay");
+    assert_eq!(session.signature_help(&uri, position, &documents).unwrap(), None);
+  }
+}
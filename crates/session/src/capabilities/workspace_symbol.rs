@@ -0,0 +1,151 @@
+use lsp_types::{SymbolInformation, SymbolKind};
+use makepad_analyzer_parser::TokenAstNode;
+
+use crate::{Session, SessionManager};
+
+/// Caps the answer so an empty or one-letter query doesn't flood the client.
+const MAX_WORKSPACE_SYMBOLS: usize = 256;
+
+impl SessionManager {
+  /// Searches the widget and constant definitions of every cached session, best matches first.
+  pub fn workspace_symbols(&self, query: &str) -> Vec<SymbolInformation> {
+    let mut matches: Vec<(i64, SymbolInformation)> = self
+      .cache()
+      .iter()
+      .flat_map(|entry| entry.value().workspace_symbols(query))
+      .collect();
+    matches.sort_by(|(a_score, a), (b_score, b)| {
+      b_score
+        .cmp(a_score)
+        .then_with(|| a.name.len().cmp(&b.name.len()))
+        .then_with(|| a.name.cmp(&b.name))
+    });
+    matches
+      .into_iter()
+      .take(MAX_WORKSPACE_SYMBOLS)
+      .map(|(_, symbol)| symbol)
+      .collect()
+  }
+}
+
+impl Session {
+  /// The definitions of this session matching `query`, with their fuzzy score.
+  pub fn workspace_symbols(&self, query: &str) -> Vec<(i64, SymbolInformation)> {
+    let package = self
+      .sync
+      .manifest_path()
+      .and_then(|path| Some(path.parent()?.file_name()?.to_string_lossy().to_string()));
+
+    self
+      .token_map()
+      .iter()
+      .filter_map(|entry| {
+        let (ident, token) = (entry.key(), entry.value());
+        let kind = match token.ast_node {
+          TokenAstNode::WidgetDefinition(_) => SymbolKind::CLASS,
+          TokenAstNode::Constant(_) => SymbolKind::CONSTANT,
+          _ => return None,
+        };
+        let score = fuzzy_score(query, &ident.name)?;
+        let location = self.workspace_location(ident)?;
+        let container_name = match (&package, &ident.path) {
+          (Some(package), Some(path)) => Some(format!("{} {}", package, self.relative_path(path))),
+          _ => None,
+        };
+        #[allow(deprecated)]
+        let symbol = SymbolInformation {
+          name: ident.name.clone(),
+          kind,
+          tags: None,
+          deprecated: None,
+          location,
+          container_name,
+        };
+        Some((score, symbol))
+      })
+      .collect()
+  }
+}
+
+/// Scores `candidate` against `query` when the query's characters appear in it in order,
+/// ignoring case. Exact and prefix matches rank first, then matches on word starts
+/// (`at` in `AppTab`, `atc` in `APP_TAB_COLOR`) and consecutive runs.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+  if query.is_empty() {
+    return Some(0);
+  }
+  let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+  let chars: Vec<char> = candidate.chars().collect();
+  let lower: Vec<char> = chars.iter().flat_map(|c| c.to_lowercase()).collect();
+  if lower.len() != chars.len() {
+    return None;
+  }
+
+  let mut score = 0;
+  let mut next = 0;
+  let mut previous: Option<usize> = None;
+  for &wanted in &query {
+    let index = (next..lower.len()).find(|&i| lower[i] == wanted)?;
+    let is_word_start = index == 0
+      || chars[index - 1] == '_'
+      || (chars[index].is_uppercase() && chars[index - 1].is_lowercase());
+    score += 1;
+    if is_word_start {
+      score += 8;
+    }
+    if let Some(previous) = previous {
+      if previous + 1 == index {
+        score += 4;
+      }
+      // Skipping over separators is free, skipping over letters is not.
+      score -= chars[previous + 1..index].iter().filter(|c| **c != '_').count() as i64;
+    }
+    previous = Some(index);
+    next = index + 1;
+  }
+
+  if lower.len() == query.len() && lower == query {
+    score += 100;
+  } else if lower.starts_with(&query) {
+    score += 50;
+  }
+  // Prefer tighter matches.
+  score -= (lower.len() - query.len()) as i64 / 4;
+  Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ranked<'a>(query: &str, candidates: &[&'a str]) -> Vec<&'a str> {
+    let mut scored: Vec<(i64, &str)> = candidates
+      .iter()
+      .filter_map(|candidate| Some((fuzzy_score(query, candidate)?, *candidate)))
+      .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, candidate)| candidate).collect()
+  }
+
+  #[test]
+  fn test_fuzzy_ranking() {
+    let candidates = ["AppTab", "APP_TAB_COLOR", "HomeScreen", "PopupList", "ApplyTabs"];
+    assert_eq!(ranked("apptab", &candidates), vec!["AppTab", "APP_TAB_COLOR", "ApplyTabs"]);
+    assert_eq!(ranked("hs", &candidates), vec!["HomeScreen"]);
+    assert_eq!(ranked("atc", &candidates), vec!["APP_TAB_COLOR"]);
+    assert_eq!(ranked("pl", &candidates)[0], "PopupList");
+    assert!(fuzzy_score("xyz", "AppTab").is_none());
+  }
+
+  #[tokio::test]
+  async fn test_workspace_symbols_search_sessions() {
+    let (session, _documents, _) = crate::capabilities::tests::fixture_session("src/app.rs").await;
+    let symbols = session.workspace_symbols("homescr");
+    assert_eq!(symbols.len(), 1);
+    let symbol = &symbols[0].1;
+    assert_eq!(symbol.name, "HomeScreen");
+    assert_eq!(symbol.kind, SymbolKind::CLASS);
+    assert_eq!(symbol.container_name.as_deref(), Some("robrix src/home/home_screen.rs"));
+    assert!(symbol.location.uri.path().ends_with("fixtures/robrix/src/home/home_screen.rs"));
+  }
+}
@@ -1,6 +1,10 @@
 mod session;
 mod lru_session_cache;
 mod sync;
+pub mod resolve;
+pub mod builtins;
+pub mod capabilities;
+pub mod shader;
 
 use dashmap::DashMap;
 use lsp_types::Url;
@@ -9,7 +13,7 @@ use makepad_analyzer_document::Documents;
 pub use session::*;
 pub use sync::*;
 
-use std::{path::{Path, PathBuf}, sync::Arc};
+use std::{path::PathBuf, sync::Arc};
 
 use lru_session_cache::LRUSessionCache;
 use tokio::{sync::Notify, time::{sleep, Duration}};
@@ -139,6 +143,12 @@ pub struct SessionManagerBuilder {
   auto_cleanup_interval: Duration,
 }
 
+impl Default for SessionManagerBuilder {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl SessionManagerBuilder {
 
   pub fn new() -> Self {
@@ -0,0 +1,677 @@
+use std::{collections::HashMap, path::{Path, PathBuf}};
+
+use lsp_types::{Location, Position, Range, Url};
+use makepad_analyzer_core::manifest::MakepadManifestFile;
+use makepad_analyzer_document::{utils::get_url_from_path, Documents};
+use makepad_analyzer_parser::{
+  parse_document, token_map::Token, ImportNode, LineIndex, ParsedDocument, PathSegment, Reference, ReferenceKind, Span,
+  TokenAstNode, TokenIdent, WidgetKind, WidgetNode,
+};
+
+use crate::{builtins::FRAMEWORK_WIDGETS, Session};
+
+/// How deep the `<A>` -> `<B>` chain is followed when collecting base definitions.
+const MAX_BASE_DEPTH: usize = 16;
+
+/// The thing under the cursor in a `live_design!` block.
+#[derive(Debug, Clone)]
+pub enum SymbolAt {
+  /// `<HomeScreen>`, `(APP_TAB_COLOR)` or `{{App}}`.
+  Reference(Reference),
+  /// The name of a declaration, e.g. `AppTab` in `AppTab = <RadioButton> {}`.
+  Declaration(TokenIdent, Box<Token>),
+  /// A segment of a `use` path, `index` being its position in the path.
+  ImportSegment(ImportNode, usize),
+}
+
+impl SymbolAt {
+  pub fn name(&self) -> &str {
+    match self {
+      SymbolAt::Reference(reference) => &reference.name,
+      SymbolAt::Declaration(ident, _) => &ident.name,
+      SymbolAt::ImportSegment(import, index) => &import.segments[*index].name,
+    }
+  }
+}
+
+/// Finds the symbol at `position` in `source`, a document of the session stored at `path`.
+pub fn symbol_at(
+  session: &Session,
+  path: &Path,
+  source: &str,
+  document: &ParsedDocument,
+  position: Position,
+) -> Option<SymbolAt> {
+  let offset = LineIndex::new(source).offset(position);
+
+  if let Some(reference) = document.references().find(|r| r.span.contains(offset)) {
+    return Some(SymbolAt::Reference(reference.clone()));
+  }
+
+  for import in document.imports() {
+    if let Some(index) = import.segments.iter().position(|s| s.span.contains(offset)) {
+      return Some(SymbolAt::ImportSegment(import.clone(), index));
+    }
+  }
+
+  session
+    .token_map()
+    .token_at_position(path, position)
+    .filter(|(_, token)| !matches!(token.ast_node, TokenAstNode::Import(_)))
+    .map(|(ident, token)| SymbolAt::Declaration(ident, Box::new(token)))
+}
+
+/// Whether a token declares a name that `<Name>` or `(NAME)` can refer to.
+pub fn is_definition(token: &Token) -> bool {
+  matches!(
+    token.ast_node,
+    TokenAstNode::WidgetDefinition(_) | TokenAstNode::Constant(_)
+  )
+}
+
+/// Whether the definition `token` is declared `pub`, so other files can import it.
+pub fn is_pub_definition(token: &Token) -> bool {
+  match &token.ast_node {
+    TokenAstNode::WidgetDefinition(widget) => widget.is_pub,
+    TokenAstNode::Constant(constant) => constant.is_pub,
+    _ => false,
+  }
+}
+
+/// A `<Widget>` or `(CONSTANT)` of the package used without a matching `use`.
+#[derive(Debug, Clone)]
+pub struct MissingImport {
+  pub reference: Reference,
+  /// The files declaring the name, sorted.
+  pub candidates: Vec<PathBuf>,
+}
+
+impl Session {
+  /// The `src` directory of the session's copy of the package.
+  pub(crate) fn src_dir(&self) -> Option<PathBuf> {
+    self.sync.temp_dir().ok().map(|dir| dir.join("src"))
+  }
+
+  /// Resolves a `use` module path such as `crate::home::home_screen` to its source file, the
+  /// same way rustc does: `home/home_screen.rs` or `home/home_screen/mod.rs`.
+  pub(crate) fn module_file(&self, segments: &[PathSegment]) -> Option<PathBuf> {
+    let (first, rest) = segments.split_first()?;
+    if first.name != "crate" {
+      return None;
+    }
+    let names: Vec<&str> = rest.iter().map(|segment| segment.name.as_str()).collect();
+    module_file(&self.src_dir()?, &names)
+  }
+
+  /// Finds the declarations a `name` used in `document` (stored at `path`) refers to.
+  ///
+  /// Declarations in the same file win, then the ones brought in by a matching `use`, then
+  /// the ones from glob imports. Names that no import accounts for fall back to every
+  /// declaration in the package; names imported from `link::` belong to the framework.
+  pub fn resolve_name(
+    &self,
+    path: &Path,
+    document: &ParsedDocument,
+    name: &str,
+  ) -> Vec<(TokenIdent, Token)> {
+    let candidates: Vec<(TokenIdent, Token)> = self
+      .token_map()
+      .tokens_for_name(name)
+      .into_iter()
+      .filter(|(_, token)| is_definition(token))
+      .collect();
+    let in_file = |file: &Path| -> Vec<(TokenIdent, Token)> {
+      candidates
+        .iter()
+        .filter(|(ident, _)| ident.path.as_deref() == Some(file))
+        .cloned()
+        .collect()
+    };
+
+    let local = in_file(path);
+    if !local.is_empty() {
+      return local;
+    }
+
+    let named_import = document
+      .imports()
+      .find(|import| import.imported_name().is_some_and(|segment| segment.name == name));
+    if let Some(import) = named_import {
+      return self
+        .module_file(import.module_segments())
+        .map(|file| in_file(&file))
+        .unwrap_or_default();
+    }
+
+    for import in document.imports().filter(|import| import.is_glob) {
+      if let Some(file) = self.module_file(import.module_segments()) {
+        let found = in_file(&file);
+        if !found.is_empty() {
+          return found;
+        }
+      }
+    }
+
+    candidates
+  }
+
+  /// The references of `document` (stored at `path`) to `pub` package declarations of other
+  /// files that neither the file itself nor any of its imports provide. Names the package
+  /// doesn't declare are left alone, as they may come from the framework, and so are framework
+  /// widgets a `use link::widgets::*` provides even if the package declares one too.
+  pub fn missing_imports(&self, path: &Path, document: &ParsedDocument) -> Vec<MissingImport> {
+    let globs: Vec<PathBuf> = document
+      .imports()
+      .filter(|import| import.is_glob)
+      .filter_map(|import| self.module_file(import.module_segments()))
+      .collect();
+
+    document
+      .references()
+      .filter(|reference| reference.kind != ReferenceKind::RustType)
+      .filter(|reference| {
+        let is_framework_widget =
+          FRAMEWORK_WIDGETS.iter().any(|(widget, _)| *widget == reference.name);
+        !(is_framework_widget && framework_module(document, &reference.name).is_some())
+      })
+      .filter_map(|reference| {
+        let definitions: Vec<(PathBuf, Token)> = self
+          .token_map()
+          .tokens_for_name(&reference.name)
+          .into_iter()
+          .filter(|(_, token)| is_definition(token))
+          .filter_map(|(ident, token)| Some((ident.path?, token)))
+          .collect();
+        let mut candidates: Vec<PathBuf> = definitions
+          .iter()
+          .filter(|(file, token)| file != path && is_pub_definition(token))
+          .map(|(file, _)| file.clone())
+          .collect();
+        candidates.sort();
+        candidates.dedup();
+
+        let is_named_import = document
+          .imports()
+          .any(|import| import.imported_name().is_some_and(|segment| segment.name == reference.name));
+        let in_scope = is_named_import
+          || definitions.iter().any(|(file, _)| file == path)
+          || candidates.iter().any(|file| globs.contains(file));
+        (!in_scope && !candidates.is_empty()).then(|| MissingImport {
+          reference: reference.clone(),
+          candidates,
+        })
+      })
+      .collect()
+  }
+
+  /// The `crate::` path importing `name` from `file`, e.g. `crate::shared::popup_list::PopupList`.
+  pub fn import_path(&self, file: &Path, name: &str) -> Option<String> {
+    let relative = file.strip_prefix(self.src_dir()?).ok()?.with_extension("");
+    let mut segments = vec!["crate".to_string()];
+    segments.extend(relative.iter().map(|segment| segment.to_string_lossy().to_string()));
+    if matches!(segments.last().map(String::as_str), Some("mod" | "lib" | "main")) {
+      segments.pop();
+    }
+    segments.push(name.to_string());
+    Some(segments.join("::"))
+  }
+
+  /// Finds `struct name` in the package sources, preferring the file at `path`.
+  pub fn find_rust_struct(
+    &self,
+    path: &Path,
+    name: &str,
+    documents: &Documents,
+  ) -> Option<(PathBuf, Range)> {
+    let src_dir = self.src_dir()?;
+    let mut files: Vec<(PathBuf, String)> = documents
+      .iter()
+      .filter(|entry| Path::new(entry.key()).starts_with(&src_dir))
+      .map(|entry| (PathBuf::from(entry.key()), entry.value().get_text().to_string()))
+      .collect();
+    files.sort_by_key(|(file, _)| file != path);
+
+    files.into_iter().find_map(|(file, source)| {
+      let offset = find_rust_struct(&source, name)?;
+      let range = LineIndex::new(&source).range(Span::new(offset, offset + name.len()));
+      Some((file, range))
+    })
+  }
+
+  /// The definitions `widget` (found in `document` at `path`) derives from through
+  /// `<Base>`, nearest first, with their files.
+  pub fn base_definitions(
+    &self,
+    path: &Path,
+    document: &ParsedDocument,
+    widget: &WidgetNode,
+    documents: &Documents,
+  ) -> Vec<(PathBuf, WidgetNode)> {
+    let mut found: Vec<(PathBuf, WidgetNode)> = vec![];
+    if widget.kind != WidgetKind::Instance {
+      return found;
+    }
+    let mut file = path.to_path_buf();
+    let mut document = document.clone();
+    let mut widget_type = widget.widget_type.clone();
+
+    while found.len() < MAX_BASE_DEPTH && !widget_type.is_empty() {
+      let base = self
+        .resolve_name(&file, &document, &widget_type)
+        .into_iter()
+        .find_map(|(ident, token)| match token.ast_node {
+          TokenAstNode::WidgetDefinition(base) => Some((ident.path?, base)),
+          _ => None,
+        });
+      let Some((base_file, base)) = base else {
+        break;
+      };
+      if found.iter().any(|(_, known)| known.name == base.name) {
+        break;
+      }
+      found.push((base_file.clone(), base.clone()));
+      if base.kind != WidgetKind::Instance {
+        break;
+      }
+      let Some((_, base_document)) = self.parse_file(&base_file, documents) else {
+        break;
+      };
+      file = base_file;
+      document = base_document;
+      widget_type = base.widget_type;
+    }
+    found
+  }
+
+  /// Reads and parses a file of the session from `documents`.
+  pub fn parse_file(&self, path: &Path, documents: &Documents) -> Option<(String, ParsedDocument)> {
+    let url = get_url_from_path(&path.to_path_buf()).ok()?;
+    let source = documents.get_text_document(&url).ok()?.get_text().to_string();
+    let document = parse_document(&source);
+    Some((source, document))
+  }
+
+  /// The path of a file of the session relative to the package root, e.g. `src/app.rs`.
+  pub fn relative_path(&self, path: &Path) -> String {
+    self
+      .sync
+      .temp_dir()
+      .ok()
+      .and_then(|dir| path.strip_prefix(dir).ok().map(Path::to_path_buf))
+      .unwrap_or_else(|| path.to_path_buf())
+      .to_string_lossy()
+      .replace('\\', "/")
+  }
+
+  /// Maps a file of the session's temp copy back to its workspace URL.
+  pub fn workspace_url(&self, path: &Path) -> Option<Url> {
+    let temp_url = get_url_from_path(&path.to_path_buf()).ok()?;
+    self.sync.temp_to_workspace_url(&temp_url).ok()
+  }
+
+  /// The workspace location of an indexed token.
+  pub fn workspace_location(&self, ident: &TokenIdent) -> Option<Location> {
+    let url = self.workspace_url(ident.path.as_deref()?)?;
+    Some(Location::new(url, ident.range))
+  }
+
+  /// The directory `crate://<name>/` paths start from: the package directory for `self`,
+  /// the source directory of the dependency called `name` otherwise.
+  pub fn dependency_root(&self, name: &str) -> Option<PathBuf> {
+    if name == "self" {
+      let manifest = MakepadManifestFile::from_dir(self.sync.manifest_dir().ok()?).ok()?;
+      return Some(manifest.dir().to_path_buf());
+    }
+    self
+      .dependency_dirs()?
+      .iter()
+      .find(|(package, _)| same_crate_name(package, name))
+      .map(|(_, dir)| dir.clone())
+  }
+
+  /// Resolves `crate://<name>/<path>` to the file it points at on disk, which may not exist.
+  pub fn resolve_dependency(&self, dependency: &str) -> Option<PathBuf> {
+    let (name, path) = split_dependency(dependency)?;
+    Some(self.dependency_root(name)?.join(path))
+  }
+
+  /// The package names `crate://<name>/` can refer to, `None` while `cargo metadata` runs or
+  /// when it failed.
+  pub fn dependency_dirs(&self) -> Option<&HashMap<String, PathBuf>> {
+    self.dependency_dirs.get()?.as_ref()
+  }
+
+  /// Whether `cargo metadata` is done, successfully or not.
+  pub fn dependency_dirs_loaded(&self) -> bool {
+    self.dependency_dirs.get().is_some()
+  }
+
+  /// Waits until `cargo metadata` is done, so the diagnostics that depend on it can be redone.
+  pub async fn wait_for_dependency_dirs(&self) {
+    let loaded = self.dependency_dirs_loaded.notified();
+    if !self.dependency_dirs_loaded() {
+      loaded.await;
+    }
+  }
+
+  /// Runs `cargo metadata` on a blocking thread to read the dependency directories, so that
+  /// no request waits for cargo.
+  pub(crate) fn load_dependency_dirs(&self) {
+    let manifest = self.sync.manifest_dir().ok().and_then(|dir| MakepadManifestFile::from_dir(dir).ok());
+    let dependency_dirs = self.dependency_dirs.clone();
+    let loaded = self.dependency_dirs_loaded.clone();
+    let Some(manifest) = manifest else {
+      let _ = dependency_dirs.set(None);
+      loaded.notify_waiters();
+      return;
+    };
+    tokio::task::spawn_blocking(move || {
+      let dirs = manifest.dependency_dirs().map_err(|err| tracing::warn!("{}", err)).ok();
+      let _ = dependency_dirs.set(dirs);
+      loaded.notify_waiters();
+    });
+  }
+}
+
+/// Splits `crate://makepad-widgets/resources/icon.svg` into the crate name and the path.
+pub fn split_dependency(dependency: &str) -> Option<(&str, &str)> {
+  let rest = dependency.strip_prefix("crate://")?;
+  Some(rest.split_once('/').unwrap_or((rest, "")))
+}
+
+/// The span of the path string inside a `dep("...")` token spanning `span`.
+pub fn dependency_span(source: &str, span: Span, dependency: &str) -> Span {
+  span
+    .text(source)
+    .find(dependency)
+    .map(|start| Span::new(span.start + start, span.start + start + dependency.len()))
+    .unwrap_or(span)
+}
+
+/// Package names may be written with `-` or `_` interchangeably.
+fn same_crate_name(a: &str, b: &str) -> bool {
+  a.replace('-', "_") == b.replace('-', "_")
+}
+
+/// The `link::` module that provides `name` to `document`, either through a named import or,
+/// for names the package doesn't declare, through a `use link::widgets::*` glob.
+pub fn framework_module(document: &ParsedDocument, name: &str) -> Option<String> {
+  let named = document.imports().find(|import| {
+    import.imported_name().is_some_and(|segment| segment.name == name)
+  });
+  if let Some(import) = named {
+    let module = import.module_segments();
+    return (module.first()?.name == "link")
+      .then(|| module.iter().map(|segment| segment.name.as_str()).collect::<Vec<_>>().join("::"));
+  }
+  document
+    .imports()
+    .filter(|import| import.is_glob)
+    .map(|import| import.path())
+    .find(|path| path.starts_with("link::widgets"))
+    .map(|path| path.trim_end_matches("::*").to_string())
+}
+
+/// Resolves the module `names` (without the leading `crate`) against `src_dir`.
+pub fn module_file(src_dir: &Path, names: &[&str]) -> Option<PathBuf> {
+  if names.is_empty() {
+    return ["lib.rs", "main.rs"]
+      .iter()
+      .map(|root| src_dir.join(root))
+      .find(|file| file.is_file());
+  }
+
+  let mut dir = src_dir.to_path_buf();
+  for name in &names[..names.len() - 1] {
+    dir.push(name);
+  }
+  let last = names[names.len() - 1];
+  let file = dir.join(format!("{}.rs", last));
+  if file.is_file() {
+    return Some(file);
+  }
+  let mod_file = dir.join(last).join("mod.rs");
+  mod_file.is_file().then_some(mod_file)
+}
+
+/// Finds `struct name` in Rust `source`, returning the byte offset of the name.
+pub fn find_rust_struct(source: &str, name: &str) -> Option<usize> {
+  let is_ident = |c: char| c == '_' || c.is_alphanumeric();
+  source.match_indices("struct").find_map(|(i, _)| {
+    let before = source[..i].chars().next_back();
+    let line_start = source[..i].rfind('\n').map_or(0, |start| start + 1);
+    if before.is_some_and(is_ident) || source[line_start..i].contains("//") {
+      return None;
+    }
+    let rest = &source[i + "struct".len()..];
+    let trimmed = rest.trim_start();
+    if trimmed.len() == rest.len() || !trimmed.starts_with(name) {
+      return None;
+    }
+    let after = trimmed[name.len()..].chars().next();
+    if after.is_some_and(is_ident) {
+      return None;
+    }
+    Some(i + "struct".len() + (rest.len() - trimmed.len()))
+  })
+}
+
+/// Finds the whole-word occurrences of `name` in Rust `source`, skipping comments, string and
+/// char literals and the `excluded` spans (usually the `live_design!` blocks).
+pub fn rust_ident_occurrences(source: &str, name: &str, excluded: &[Span]) -> Vec<Span> {
+  let bytes = source.as_bytes();
+  let is_ident = |b: u8| b == b'_' || b.is_ascii_alphanumeric();
+  let mut occurrences = vec![];
+  let mut i = 0;
+  while i < bytes.len() {
+    if let Some(span) = excluded.iter().find(|span| span.start <= i && i < span.end) {
+      i = span.end;
+      continue;
+    }
+    match bytes[i] {
+      b'/' if bytes.get(i + 1) == Some(&b'/') => {
+        i = source[i..].find('\n').map_or(bytes.len(), |end| i + end);
+      }
+      b'/' if bytes.get(i + 1) == Some(&b'*') => {
+        i = source[i + 2..].find("*/").map_or(bytes.len(), |end| i + 2 + end + 2);
+      }
+      b'"' => {
+        i += 1;
+        while i < bytes.len() && bytes[i] != b'"' {
+          i += if bytes[i] == b'\\' { 2 } else { 1 };
+        }
+        i += 1;
+      }
+      b'\'' => i = char_literal_end(source, i).unwrap_or(i + 1),
+      b if is_ident(b) => {
+        if let Some(end) = raw_string_end(source, i) {
+          i = end;
+          continue;
+        }
+        let start = i;
+        while i < bytes.len() && is_ident(bytes[i]) {
+          i += 1;
+        }
+        if &source[start..i] == name {
+          occurrences.push(Span::new(start, i));
+        }
+      }
+      _ => i += 1,
+    }
+  }
+  occurrences
+}
+
+/// Finds the occurrences of the item `name` of `module` (such as `crate::app`) in Rust
+/// `source`, the way `rust_ident_occurrences` does. Paths only count when they go through
+/// `module`, which leaves out other crates' items and enum variants such as `Screen::App`, and
+/// bare names only where the item is in scope: in its own module (`is_home`) or once imported.
+pub fn rust_item_occurrences(
+  source: &str,
+  module: &str,
+  name: &str,
+  is_home: bool,
+  excluded: &[Span],
+) -> Vec<Span> {
+  let compact: String = source.chars().filter(|c| !c.is_whitespace()).collect();
+  let mut in_scope = is_home || compact.contains(&format!("use{}::*;", module));
+  let mut found = vec![];
+  let mut bare = vec![];
+  for span in rust_ident_occurrences(source, name, excluded) {
+    if let Some(path) = use_path(source, span.start) {
+      if path == module {
+        in_scope = true;
+        found.push(span);
+      }
+      continue;
+    }
+    match path_qualifier(source, span.start) {
+      Some(path) if path == module || (is_home && path == "self") => found.push(span),
+      Some(_) => {}
+      None => bare.push(span),
+    }
+  }
+  if in_scope {
+    found.extend(bare);
+  }
+  found.sort_by_key(|span| span.start);
+  found
+}
+
+/// The path a `use` statement imports the name at `offset` from, such as `crate::app` for
+/// `use crate::app::App;` or `use crate::app::{AppTab, App};`.
+fn use_path(source: &str, offset: usize) -> Option<String> {
+  let is_ident = |c: char| c == '_' || c.is_alphanumeric();
+  let before = &source[..offset];
+  let statement = before.rfind(';').map_or(0, |end| end + 1);
+  let start = before[statement..]
+    .match_indices("use")
+    .map(|(index, _)| statement + index)
+    .filter(|&index| {
+      !source[..index].chars().next_back().is_some_and(is_ident)
+        && source[index + "use".len()..].starts_with(char::is_whitespace)
+    })
+    .last()?;
+  let path: String = source[start + "use".len()..offset]
+    .chars()
+    .filter(|c| !c.is_whitespace())
+    .collect();
+  if !path.chars().all(|c| is_ident(c) || matches!(c, ':' | '{' | ',')) {
+    return None;
+  }
+  let path = path.rsplit_once('{').map_or(path.as_str(), |(prefix, _)| prefix);
+  Some(path.strip_suffix("::")?.to_string())
+}
+
+/// The path before the name at `offset`, such as `crate::app` for `crate::app::App`.
+fn path_qualifier(source: &str, offset: usize) -> Option<String> {
+  let is_ident = |c: char| c == '_' || c.is_alphanumeric();
+  let mut segments = vec![];
+  let mut before = &source[..offset];
+  while let Some(rest) = before.trim_end().strip_suffix("::") {
+    let rest = rest.trim_end();
+    let stripped = rest.trim_end_matches(is_ident);
+    if stripped.len() == rest.len() {
+      break;
+    }
+    segments.push(&rest[stripped.len()..]);
+    before = stripped;
+  }
+  segments.reverse();
+  (!segments.is_empty()).then(|| segments.join("::"))
+}
+
+/// The end of the char literal starting at the `'` at `start`, `None` for a lifetime.
+fn char_literal_end(source: &str, start: usize) -> Option<usize> {
+  let rest = &source[start + 1..];
+  if let Some(escaped) = rest.strip_prefix('\\') {
+    let close = escaped.get(1..)?.find('\'')?;
+    return Some(start + 1 + 2 + close + 1);
+  }
+  let c = rest.chars().next()?;
+  rest[c.len_utf8()..].starts_with('\'').then_some(start + 1 + c.len_utf8() + 1)
+}
+
+/// The end of the raw string literal such as `r#"..."#` or `br"..."` at `start`, if any.
+fn raw_string_end(source: &str, start: usize) -> Option<usize> {
+  let rest = &source[start..];
+  let rest = rest.strip_prefix("br").or_else(|| rest.strip_prefix('r'))?;
+  let hashes = rest.len() - rest.trim_start_matches('#').len();
+  let body = rest[hashes..].strip_prefix('"')?;
+  let closing = format!("\"{}", "#".repeat(hashes));
+  let body_start = source.len() - body.len();
+  Some(body.find(&closing).map_or(source.len(), |end| body_start + end + closing.len()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_find_rust_struct() {
+    let source = "// struct App\n#[derive(Live)]\npub struct AppTab {}\npub struct App {\n}";
+    let offset = find_rust_struct(source, "App").unwrap();
+    assert_eq!(&source[offset..offset + 3], "App");
+    assert!(source[..offset].ends_with("pub struct "));
+    assert_eq!(find_rust_struct(source, "Missing"), None);
+  }
+
+  #[test]
+  fn test_rust_ident_occurrences() {
+    let source = "live_design! { App = {{App}} {} }\n// App\napp_main!(App);\nlet s = \"App\";\nimpl Apps for App {}";
+    let block = Span::new(0, source.find('\n').unwrap());
+    let spans = rust_ident_occurrences(source, "App", &[block]);
+    let lines: Vec<Position> = spans
+      .iter()
+      .map(|span| LineIndex::new(source).position(span.start))
+      .collect();
+    assert_eq!(lines, vec![Position::new(2, 10), Position::new(4, 14)]);
+  }
+
+  #[test]
+  fn test_rust_ident_occurrences_skip_literals() {
+    let source = [
+      "let quote = '\"'; let app = App::new();",
+      "let raw = r#\"App \"App\" \\\"#; let escaped = '\\'';",
+      "fn show<'a>(app: &'a App) -> char { 'A' }",
+    ]
+    .join("\n");
+    let spans = rust_ident_occurrences(&source, "App", &[]);
+    let lines: Vec<Position> = spans
+      .iter()
+      .map(|span| LineIndex::new(&source).position(span.start))
+      .collect();
+    assert_eq!(lines, vec![Position::new(0, 27), Position::new(2, 21)]);
+  }
+
+  #[tokio::test]
+  async fn test_wait_for_dependency_dirs() {
+    let (session, _, _) = crate::capabilities::tests::fixture_session("src/app.rs").await;
+    let wait = session.wait_for_dependency_dirs();
+    tokio::time::timeout(std::time::Duration::from_secs(60), wait).await.unwrap();
+    assert!(session.dependency_dirs_loaded());
+  }
+
+  #[test]
+  fn test_rust_item_occurrences() {
+    let lines = |source: &str, is_home: bool| -> Vec<u32> {
+      rust_item_occurrences(source, "crate::app", "App", is_home, &[])
+        .iter()
+        .map(|span| LineIndex::new(source).position(span.start).line)
+        .collect()
+    };
+    let source = [
+      "use crate::app::{AppTab,\n App};",
+      "impl App {}",
+      "let s = Screen::App;",
+      "let a: crate :: app :: App = makepad_widgets::App::new();",
+    ]
+    .join("\n");
+    assert_eq!(lines(&source, false), vec![1, 2, 4]);
+    let source = "use crate::app::*;\nimpl App {}";
+    assert_eq!(lines(source, false), vec![1]);
+    let source = "use other::App;\nimpl App {}\nfn app() -> self::App {}";
+    assert_eq!(lines(source, false), Vec::<u32>::new());
+    assert_eq!(lines(source, true), vec![1, 2]);
+  }
+}
@@ -1,9 +1,17 @@
-use std::{ffi::OsStr, fs, path::{Path, PathBuf}, sync::atomic::{AtomicBool, Ordering::Relaxed}};
-
-use lsp_types::{CompletionItem, CompletionItemKind, Position};
-use makepad_analyzer_core::errors::MakepadAnalyzerError;
+use std::{
+  collections::HashMap,
+  ffi::OsStr,
+  fs,
+  path::{Path, PathBuf},
+  sync::{atomic::{AtomicBool, Ordering::Relaxed}, Arc, OnceLock},
+};
+
+use dashmap::DashMap;
+use lsp_types::SemanticTokens;
+use makepad_analyzer_core::errors::{DocumentError, MakepadAnalyzerError};
 use makepad_analyzer_document::{Documents, TextDocument};
-// use makepad_analyzer_parser::TokenMap;
+use makepad_analyzer_parser::TokenMap;
+use tokio::sync::Notify;
 use url::Url;
 
 use crate::SyncWorkspace;
@@ -12,15 +20,31 @@ pub type ProjectDirectory = PathBuf;
 
 #[derive(Debug)]
 pub struct Session {
-  // token_map: TokenMap,
+  token_map: TokenMap,
+  /// The last semantic tokens sent for each file, to answer delta requests.
+  pub(crate) semantic_tokens: DashMap<PathBuf, SemanticTokens>,
+  /// The source directories of the package's dependencies, read from `cargo metadata` in the
+  /// background once the session starts. `None` when cargo couldn't tell.
+  pub(crate) dependency_dirs: Arc<OnceLock<Option<HashMap<String, PathBuf>>>>,
+  /// Wakes whoever waits for `dependency_dirs` once it is set.
+  pub(crate) dependency_dirs_loaded: Arc<Notify>,
   pub sync: SyncWorkspace,
   pub is_active: AtomicBool,
 }
 
+impl Default for Session {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl Session {
   pub fn new () -> Self {
     Session {
-      // token_map: TokenMap::new(),
+      token_map: TokenMap::new(),
+      semantic_tokens: DashMap::new(),
+      dependency_dirs: Arc::new(OnceLock::new()),
+      dependency_dirs_loaded: Arc::new(Notify::new()),
       sync: SyncWorkspace::new(),
       is_active: AtomicBool::new(true),
     }
@@ -39,7 +63,8 @@ impl Session {
     self.sync.clone_manifest_dir_to_temp()?;
 
     // store all project files in the documents (workspace)
-    let _ = self.store_project_files(documents).await?;
+    self.store_project_files(documents).await?;
+    self.load_dependency_dirs();
     // self.sync.watch_and_sync_manifest();
 
     // return the manifest directory
@@ -54,9 +79,16 @@ impl Session {
     self.is_active.load(Relaxed)
   }
 
-  // pub fn token_map(&self) -> &TokenMap {
-  //   &self.token_map
-  // }
+  pub fn token_map(&self) -> &TokenMap {
+    &self.token_map
+  }
+
+  /// Re-indexes the `live_design!` declarations of the document at the temp `uri`.
+  pub fn update_token_map(&self, uri: &Url, documents: &Documents) -> Result<(), DocumentError> {
+    let document = documents.get_text_document(uri)?;
+    self.token_map.update_file(Path::new(uri.path()), document.get_text());
+    Ok(())
+  }
 
   async fn store_project_files(
     &self,
@@ -64,40 +96,13 @@ impl Session {
   ) -> Result<(), MakepadAnalyzerError> {
     let temp_dir = self.sync.temp_dir()?;
     for path in get_project_files(temp_dir).iter().filter_map(|fp| fp.to_str()) {
-      documents.store_document(TextDocument::build_from_path(path).await?)?;
+      let text_document = TextDocument::build_from_path(path).await?;
+      self.token_map.update_file(Path::new(path), text_document.get_text());
+      documents.store_document(text_document)?;
     }
 
     Ok(())
   }
-
-  pub fn completion_items(
-    &self,
-    uri: &Url,
-    position: Position,
-    trigger_char: &str,
-  ) -> Option<Vec<CompletionItem>> {
-    let _shifted_position = Position {
-      line: position.line,
-      character: position.character - trigger_char.len() as u32 - 1,
-    };
-    tracing::info!("uri: {:?}", uri);
-    let completion_items = vec![
-      CompletionItem {
-        label: "Hello".to_string(),
-        kind: Some(CompletionItemKind::KEYWORD),
-        detail: Some("Hello".to_string()),
-        ..CompletionItem::default()
-      },
-      CompletionItem {
-        label: "World".to_string(),
-        kind: Some(CompletionItemKind::KEYWORD),
-        detail: Some("World".to_string()),
-        ..CompletionItem::default()
-      },
-    ];
-
-    Some(completion_items)
-  }
 }
 
 fn get_project_files(path: PathBuf) -> Vec<PathBuf> {
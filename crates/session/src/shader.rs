@@ -0,0 +1,228 @@
+use std::path::Path;
+
+use makepad_analyzer_document::Documents;
+use makepad_analyzer_parser::{Expression, ParsedDocument, ShaderFnNode, WidgetKind, WidgetNode};
+use makepad_analyzer_plugin_shader::{inherited_scope, ShaderLayer, ShaderScope, ShaderType};
+
+use crate::Session;
+
+/// An object with shader `fn`s, such as `draw_bg: { ... }`, and the widget it is a property of.
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderObject<'a> {
+  /// `None` for top-level definitions such as `DrawTab = {{DrawTab}} { ... }`.
+  pub owner: Option<&'a WidgetNode>,
+  pub object: &'a WidgetNode,
+}
+
+impl Session {
+  /// What `self.` reaches in the shader `fn`s of `object` from `document` at `path`: the draw
+  /// shader of the framework widget its owner derives from, then the declarations of the
+  /// same `draw_*` property in each definition along the way, and finally its own.
+  pub fn shader_scope(
+    &self,
+    path: &Path,
+    document: &ParsedDocument,
+    object: ShaderObject,
+    documents: &Documents,
+  ) -> ShaderScope {
+    let property = object.object.name.as_str();
+    let Some(owner) = object.owner else {
+      let layer = self.shader_layer(path, document, property, object.object, documents);
+      return inherited_scope(None, &[layer]);
+    };
+
+    let bases = self.base_definitions(path, document, owner, documents);
+    let mut layers: Vec<ShaderLayer> = bases
+      .iter()
+      .rev()
+      .filter_map(|(file, base)| {
+        let base_object = base.property(property)?.value.as_object()?;
+        let (_, base_document) = self.parse_file(file, documents)?;
+        Some(self.shader_layer(file, &base_document, &base.name, base_object, documents))
+      })
+      .collect();
+    let origin = if owner.name.is_empty() { &owner.widget_type } else { &owner.name };
+    layers.push(self.shader_layer(path, document, origin, object.object, documents));
+
+    // The type of the last definition is the framework widget, unless it is a Rust type. The
+    // fields of a `draw_bg: {{DrawTab}} { ... }` come from Rust too.
+    let root = bases.last().map_or(owner, |(_, base)| base);
+    let framework = (root.kind == WidgetKind::Instance
+      && object.object.kind != WidgetKind::RustBinding)
+      .then_some(root.widget_type.as_str());
+    inherited_scope(framework.map(|widget| (widget, property)), &layers)
+  }
+
+  /// The declarations and `fn`s of `object` in `document` at `path`, declared by `origin`.
+  fn shader_layer(
+    &self,
+    path: &Path,
+    document: &ParsedDocument,
+    origin: &str,
+    object: &WidgetNode,
+    documents: &Documents,
+  ) -> ShaderLayer {
+    ShaderLayer {
+      origin: origin.to_string(),
+      fields: object
+        .properties
+        .iter()
+        .filter(|property| property.value.as_object().is_none())
+        .map(|property| {
+          let ty = self.value_shader_type(path, document, &property.value, documents);
+          (property.name.clone(), ty)
+        })
+        .collect(),
+      methods: object
+        .shader_fns
+        .iter()
+        .map(|shader_fn| {
+          let return_type = shader_fn.return_type.as_deref().unwrap_or("void");
+          let ty = ShaderType::from_name(return_type).unwrap_or(ShaderType::Unknown);
+          (shader_fn.name.clone(), ty)
+        })
+        .collect(),
+    }
+  }
+
+  /// The shader type of `value`, following constants such as `(APP_TAB_COLOR)`.
+  fn value_shader_type(
+    &self,
+    path: &Path,
+    document: &ParsedDocument,
+    value: &Expression,
+    documents: &Documents,
+  ) -> ShaderType {
+    let Expression::Constant(name) = value else {
+      return expression_shader_type(value);
+    };
+    self
+      .resolve_constant(path, document, name)
+      .map_or(ShaderType::Unknown, |(file, constant)| {
+        expression_shader_type(&self.constant_value(&file, &constant, documents))
+      })
+  }
+}
+
+/// Every object of `document` with shader `fn`s.
+pub fn shader_objects(document: &ParsedDocument) -> Vec<ShaderObject<'_>> {
+  let mut objects = vec![];
+  for widget in document.widgets() {
+    if !widget.shader_fns.is_empty() {
+      objects.push(ShaderObject { owner: None, object: widget });
+    }
+    collect_shader_objects(widget, &mut objects);
+  }
+  objects
+}
+
+fn collect_shader_objects<'a>(node: &'a WidgetNode, objects: &mut Vec<ShaderObject<'a>>) {
+  for property in &node.properties {
+    if let Some(object) = property.value.as_object() {
+      if !object.shader_fns.is_empty() {
+        objects.push(ShaderObject { owner: Some(node), object });
+      }
+      collect_shader_objects(object, objects);
+    }
+  }
+  for child in &node.children {
+    collect_shader_objects(child, objects);
+  }
+}
+
+/// The shader `fn` whose body contains `offset`, with the object it belongs to.
+pub fn shader_fn_at(
+  document: &ParsedDocument,
+  offset: usize,
+) -> Option<(ShaderObject<'_>, &ShaderFnNode)> {
+  shader_objects(document).into_iter().find_map(|object| {
+    let shader_fn = object
+      .object
+      .shader_fns
+      .iter()
+      .find(|shader_fn| shader_fn.body_span.contains(offset))?;
+    Some((object, shader_fn))
+  })
+}
+
+/// The name after the `self.` around `offset` in `source` with where it starts, empty right
+/// after the `.`.
+pub fn self_field_at(source: &str, offset: usize) -> Option<(usize, &str)> {
+  let is_ident = |c: char| c == '_' || c.is_alphanumeric();
+  let start = source[..offset].rfind(|c: char| !is_ident(c)).map_or(0, |index| index + 1);
+  let end = source[offset..]
+    .find(|c: char| !is_ident(c))
+    .map_or(source.len(), |index| offset + index);
+  let before = source[..start].strip_suffix("self.")?;
+  if before.ends_with(is_ident) {
+    return None;
+  }
+  Some((start, &source[start..end]))
+}
+
+/// The shader type of the value of a declaration such as `instance hover: 0.0`.
+fn expression_shader_type(value: &Expression) -> ShaderType {
+  match value {
+    Expression::Ident(name) | Expression::Call(name, _) => {
+      ShaderType::from_name(name).unwrap_or(ShaderType::Unknown)
+    }
+    Expression::Number(_) => ShaderType::Float,
+    Expression::Color(_) => ShaderType::Vec4,
+    Expression::Boolean(_) => ShaderType::Bool,
+    _ => ShaderType::Unknown,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::Path;
+
+  use lsp_types::{HoverContents, Position};
+  use makepad_analyzer_parser::LineIndex;
+
+  use crate::capabilities::{diagnostic::shader_diagnostics, tests::fixture_session};
+
+  #[tokio::test]
+  async fn test_inherited_shader_fields() {
+    let (session, documents, uri) = fixture_session("src/app.rs").await;
+    let path = Path::new(uri.path());
+    let source = documents.get_text_document(&uri).unwrap().get_text().to_string();
+    let diagnostics = |source: &str| -> Vec<String> {
+      shader_diagnostics(source, |document, object| {
+        session.shader_scope(path, document, object, &documents)
+      })
+      .into_iter()
+      .map(|diagnostic| diagnostic.message)
+      .collect()
+    };
+    assert_eq!(diagnostics(&source), Vec::<String>::new());
+    let pressed = source.replacen("self.hover", "self.pressed", 1);
+    assert_eq!(diagnostics(&pressed), vec!["no field `pressed` on `self`"]);
+
+    // The `self.` of `self.color_unselected` in `draw_icon`.
+    let offset = source.find("instance color_selected").unwrap();
+    let offset = offset + source[offset..].find("self.").unwrap() + "self.".len();
+    let position = LineIndex::new(&source).position(offset);
+    let items = session.completion_items(&uri, position, &documents).unwrap().unwrap();
+    let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    for field in ["hover", "selected", "color_selected", "rect_size", "get_color"] {
+      assert!(labels.contains(&field), "missing {}", field);
+    }
+
+    let position = Position::new(position.line, position.character + 2);
+    let hover = session.hover(&uri, position, &documents).unwrap().unwrap();
+    let HoverContents::Markup(markup) = hover.contents else {
+      panic!("expected markdown");
+    };
+    assert_eq!(
+      markup.value,
+      "```makepad\nself.color_unselected: vec4\n```\nDeclared by `AppTab`."
+    );
+    let position = LineIndex::new(&source).position(source.find("self.hover").unwrap() + 6);
+    let hover = session.hover(&uri, position, &documents).unwrap().unwrap();
+    let HoverContents::Markup(markup) = hover.contents else {
+      panic!("expected markdown");
+    };
+    assert!(markup.value.ends_with("Declared by `DrawRadioButton`."), "{}", markup.value);
+  }
+}
@@ -3,9 +3,7 @@ use std::{fs, path::{Path, PathBuf}};
 use dashmap::DashMap;
 use makepad_analyzer_core::{errors::{DirectoryError, DocumentError, MakepadAnalyzerError}, manifest::MakepadManifestFile};
 use makepad_analyzer_document::utils::get_url_from_path;
-use parking_lot::RwLock;
 use tempfile::Builder;
-use tokio::task::JoinHandle;
 use lsp_types::Url;
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -17,14 +15,12 @@ pub enum Directory {
 #[derive(Debug)]
 pub struct SyncWorkspace {
   pub directories: DashMap<Directory, PathBuf>,
-  notify_handle: RwLock<Option<JoinHandle<()>>>,
 }
 
 impl SyncWorkspace {
   pub(crate) fn new() -> Self {
     Self {
       directories: DashMap::new(),
-      notify_handle: RwLock::new(None),
     }
   }
 
@@ -81,19 +77,13 @@ impl SyncWorkspace {
   }
 
   pub fn workspace_to_temp_url(&self, uri: &Url) -> Result<Url, DirectoryError> {
-    convert_url(&uri, &self.temp_dir()?, &self.manifest_dir()?)
+    convert_url(uri, &self.temp_dir()?, &self.manifest_dir()?)
   }
 
   pub(crate) fn temp_to_workspace_url(&self, uri: &Url) -> Result<Url, DirectoryError> {
     convert_url(uri, &self.manifest_dir()?, &self.temp_dir()?)
   }
 
-  pub(crate) fn temp_manifest_path(&self) -> Option<PathBuf> {
-    self.temp_dir()
-      .map(|dir| dir.join("Cargo.toml"))
-      .ok()
-  }
-
   pub fn manifest_path(&self) -> Option<PathBuf> {
     self.manifest_dir()
       .map(|dir| dir.join("Cargo.toml"))
@@ -0,0 +1,133 @@
+use makepad_widgets::*;
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::home::home_screen::HomeScreen;
+    use crate::shared::popup_list::PopupList;
+
+    ICON_CHAT = dep("crate://self/resources/icons/chat.svg")
+
+    APP_TAB_COLOR = #344054
+    APP_TAB_COLOR_HOVER = #636e82
+    APP_TAB_COLOR_SELECTED = #091
+
+    pub AppTab = <RadioButton> {
+        width: Fit,
+        height: Fill,
+        flow: Down,
+        align: {x: 0.5, y: 0.5},
+
+        icon_walk: {width: 20, height: 20, margin: 0.0}
+        label_walk: {margin: 0.0}
+
+        draw_radio: {
+            radio_type: Tab,
+
+            // Draws a horizontal line under the tab when selected or hovered.
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(
+                    20.0,
+                    self.rect_size.y - 2.5,
+                    self.rect_size.x - 40,
+                    self.rect_size.y - 4,
+                    0.5
+                );
+                sdf.fill(
+                    mix(
+                        mix(
+                            #0000,
+                            (APP_TAB_COLOR_HOVER),
+                            self.hover
+                        ),
+                        (APP_TAB_COLOR_SELECTED),
+                        self.selected
+                    )
+                );
+                return sdf.result;
+            }
+        }
+
+        draw_text: {
+            color_unselected: (APP_TAB_COLOR)
+            color_unselected_hover: (APP_TAB_COLOR_HOVER)
+            color_selected: (APP_TAB_COLOR_SELECTED)
+
+            fn get_color(self) -> vec4 {
+                return mix(
+                    mix(
+                        self.color_unselected,
+                        self.color_unselected_hover,
+                        self.hover
+                    ),
+                    self.color_selected,
+                    self.selected
+                )
+            }
+        }
+
+        draw_icon: {
+            instance color_unselected: (APP_TAB_COLOR)
+            instance color_unselected_hover: (APP_TAB_COLOR_HOVER)
+            instance color_selected: (APP_TAB_COLOR_SELECTED)
+            fn get_color(self) -> vec4 {
+                return mix(
+                    mix(
+                        self.color_unselected,
+                        self.color_unselected_hover,
+                        self.hover
+                    ),
+                    self.color_selected,
+                    self.selected
+                )
+            }
+        }
+    }
+
+    App = {{App}} {
+        ui: <Window> {
+            window: {inner_size: vec2(1280, 800)},
+            pass: {clear_color: #2A}
+
+            body = {
+                // A wrapper view for showing top-level app modals/dialogs/popups
+                <View> {
+                    width: Fill, height: Fill,
+                    flow: Overlay,
+
+                    home_screen_view = <View> {
+                        visible: true
+                        home_screen = <HomeScreen> {}
+                    }
+                    chat_tab = <AppTab> {
+                        icon_walk: {width: 24}
+                        draw_icon: {svg_file: (ICON_CHAT)}
+                    }
+                    popup = <PopupNotification> {
+                        margin: {top: 45, right: 13},
+                        content: {
+                            <PopupList> {}
+                        }
+                    }
+                }
+            } // end of body
+        }
+    }
+}
+
+app_main!(App);
+
+#[derive(Live, LiveHook)]
+pub struct App {
+    #[live] ui: WidgetRef,
+}
+
+impl LiveRegister for App {
+    fn live_register(cx: &mut Cx) {
+        crate::makepad_widgets::live_design(cx);
+    }
+}
@@ -0,0 +1,29 @@
+use makepad_widgets::*;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::app::AppTab;
+
+    pub HomeScreen = <View> {
+        width: Fill, height: Fill
+        flow: Down
+        show_bg: true
+        draw_bg: {
+            color: (COLOR_PRIMARY)
+        }
+
+        tabs = <View> {
+            height: 60
+            home_tab = <AppTab> {
+                text: "Home"
+                animator: { selected = { default: on } }
+            }
+            settings_tab = <AppTab> {
+                text: "Settings"
+            }
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod popup_list;
+pub mod styles;
@@ -0,0 +1,22 @@
+use makepad_widgets::*;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+
+    pub PopupList = {{PopupList}} {
+        width: 275, height: Fit
+        flow: Down
+        show_bg: true
+        draw_bg: {
+            color: (COLOR_PRIMARY)
+        }
+    }
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct PopupList {
+    #[deref] view: View,
+}
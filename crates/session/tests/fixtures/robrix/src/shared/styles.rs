@@ -0,0 +1,9 @@
+use makepad_widgets::*;
+
+live_design! {
+    pub COLOR_PRIMARY = #0f88fe
+    pub COLOR_DIVIDER = #x1e3a5f
+    pub TITLE_TEXT = {
+        font_size: 13.0
+    }
+}
@@ -0,0 +1,205 @@
+/// A builtin shader function or `Sdf2d` method.
+///
+/// Parameter and return types are shader types, plus `T` for "`float` or any `vecN`, the same
+/// everywhere it appears" as GLSL's `genType`.
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderFunction {
+  pub name: &'static str,
+  pub params: &'static [(&'static str, &'static str)],
+  pub return_type: &'static str,
+  pub doc: &'static str,
+}
+
+impl ShaderFunction {
+  /// `mix(x: T, y: T, a: float) -> T`
+  pub fn label(&self) -> String {
+    let params: Vec<String> = self
+      .params
+      .iter()
+      .map(|(name, ty)| format!("{}: {}", name, ty))
+      .collect();
+    match self.return_type {
+      "void" => format!("{}({})", self.name, params.join(", ")),
+      return_type => format!("{}({}) -> {}", self.name, params.join(", "), return_type),
+    }
+  }
+}
+
+const fn function(
+  name: &'static str,
+  params: &'static [(&'static str, &'static str)],
+  return_type: &'static str,
+  doc: &'static str,
+) -> ShaderFunction {
+  ShaderFunction { name, params, return_type, doc }
+}
+
+/// The builtin functions available in every shader.
+pub const BUILTINS: &[ShaderFunction] = &[
+  function("abs", &[("x", "T")], "T", "The absolute value of `x`."),
+  function("sign", &[("x", "T")], "T", "`-1.0`, `0.0` or `1.0` depending on the sign of `x`."),
+  function("floor", &[("x", "T")], "T", "The largest integer not greater than `x`."),
+  function("ceil", &[("x", "T")], "T", "The smallest integer not less than `x`."),
+  function("fract", &[("x", "T")], "T", "`x - floor(x)`."),
+  function("mod", &[("x", "T"), ("y", "float")], "T", "`x` modulo `y`."),
+  function("min", &[("x", "T"), ("y", "T")], "T", "The smaller of `x` and `y`."),
+  function("max", &[("x", "T"), ("y", "T")], "T", "The larger of `x` and `y`."),
+  function(
+    "clamp",
+    &[("x", "T"), ("min_value", "T"), ("max_value", "T")],
+    "T",
+    "`x` constrained to lie between `min_value` and `max_value`.",
+  ),
+  function(
+    "mix",
+    &[("x", "T"), ("y", "T"), ("a", "float")],
+    "T",
+    "The linear blend of `x` and `y`: `x * (1.0 - a) + y * a`.",
+  ),
+  function("step", &[("edge", "T"), ("x", "T")], "T", "`0.0` if `x < edge`, otherwise `1.0`."),
+  function(
+    "smoothstep",
+    &[("edge0", "float"), ("edge1", "float"), ("x", "T")],
+    "T",
+    "Hermite interpolation between `0.0` and `1.0` as `x` goes from `edge0` to `edge1`.",
+  ),
+  function("sqrt", &[("x", "T")], "T", "The square root of `x`."),
+  function("inversesqrt", &[("x", "T")], "T", "`1.0 / sqrt(x)`."),
+  function("pow", &[("x", "T"), ("y", "T")], "T", "`x` raised to the power `y`."),
+  function("exp", &[("x", "T")], "T", "The natural exponentiation of `x`."),
+  function("log", &[("x", "T")], "T", "The natural logarithm of `x`."),
+  function("sin", &[("angle", "T")], "T", "The sine of `angle` in radians."),
+  function("cos", &[("angle", "T")], "T", "The cosine of `angle` in radians."),
+  function("tan", &[("angle", "T")], "T", "The tangent of `angle` in radians."),
+  function("atan", &[("y_over_x", "T")], "T", "The arc tangent of `y_over_x`."),
+  function("atan", &[("y", "T"), ("x", "T")], "T", "The arc tangent of `y / x`."),
+  function("length", &[("x", "T")], "float", "The length of the vector `x`."),
+  function("distance", &[("p0", "T"), ("p1", "T")], "float", "The distance between `p0` and `p1`."),
+  function("dot", &[("x", "T"), ("y", "T")], "float", "The dot product of `x` and `y`."),
+  function("cross", &[("x", "vec3"), ("y", "vec3")], "vec3", "The cross product of `x` and `y`."),
+  function("normalize", &[("x", "T")], "T", "`x` scaled to a length of `1.0`."),
+  function(
+    "sample2d",
+    &[("texture", "texture2D"), ("uv", "vec2")],
+    "vec4",
+    "Samples `texture` at `uv`.",
+  ),
+  function("vec2", &[("x", "float"), ("y", "float")], "vec2", "Builds a `vec2`."),
+  function("vec3", &[("x", "float"), ("y", "float"), ("z", "float")], "vec3", "Builds a `vec3`."),
+  function(
+    "vec4",
+    &[("x", "float"), ("y", "float"), ("z", "float"), ("w", "float")],
+    "vec4",
+    "Builds a `vec4`.",
+  ),
+];
+
+/// The common methods of `Sdf2d`, the signed distance field helper of `link::shaders`.
+pub const SDF2D_METHODS: &[ShaderFunction] = &[
+  function(
+    "viewport",
+    &[("pos", "vec2")],
+    "Sdf2d",
+    "Creates a distance field over `pos`, usually `Sdf2d::viewport(self.pos * self.rect_size)`.",
+  ),
+  function(
+    "box",
+    &[("x", "float"), ("y", "float"), ("w", "float"), ("h", "float"), ("radius", "float")],
+    "void",
+    "Adds a rectangle with rounded corners to the current shape.",
+  ),
+  function(
+    "rect",
+    &[("x", "float"), ("y", "float"), ("w", "float"), ("h", "float")],
+    "void",
+    "Adds a rectangle to the current shape.",
+  ),
+  function(
+    "circle",
+    &[("x", "float"), ("y", "float"), ("radius", "float")],
+    "void",
+    "Adds a circle centered on `x`, `y` to the current shape.",
+  ),
+  function(
+    "hexagon",
+    &[("x", "float"), ("y", "float"), ("radius", "float")],
+    "void",
+    "Adds a hexagon centered on `x`, `y` to the current shape.",
+  ),
+  function("move_to", &[("x", "float"), ("y", "float")], "void", "Starts a new path at `x`, `y`."),
+  function(
+    "line_to",
+    &[("x", "float"), ("y", "float")],
+    "void",
+    "Adds a line from the current point to `x`, `y`.",
+  ),
+  function("close_path", &[], "void", "Closes the current path."),
+  function(
+    "fill",
+    &[("color", "vec4")],
+    "vec4",
+    "Fills the current shape with `color` and starts a new one.",
+  ),
+  function(
+    "fill_keep",
+    &[("color", "vec4")],
+    "vec4",
+    "Fills the current shape with `color` and keeps it.",
+  ),
+  function(
+    "stroke",
+    &[("color", "vec4"), ("width", "float")],
+    "vec4",
+    "Strokes the outline of the current shape and starts a new one.",
+  ),
+  function(
+    "stroke_keep",
+    &[("color", "vec4"), ("width", "float")],
+    "vec4",
+    "Strokes the outline of the current shape and keeps it.",
+  ),
+  function(
+    "glow",
+    &[("color", "vec4"), ("width", "float")],
+    "vec4",
+    "Adds a glow around the current shape and starts a new one.",
+  ),
+  function("union", &[], "void", "Merges the current shape with the previous one."),
+  function(
+    "intersect",
+    &[],
+    "void",
+    "Keeps the overlap of the current shape and the previous one.",
+  ),
+  function("subtract", &[], "void", "Cuts the current shape out of the previous one."),
+  function("translate", &[("x", "float"), ("y", "float")], "void", "Moves the coordinate system."),
+  function(
+    "rotate",
+    &[("angle", "float"), ("x", "float"), ("y", "float")],
+    "void",
+    "Rotates the coordinate system around `x`, `y`.",
+  ),
+  function(
+    "scale",
+    &[("factor", "float"), ("x", "float"), ("y", "float")],
+    "void",
+    "Scales the coordinate system around `x`, `y`.",
+  ),
+  function("clear", &[("color", "vec4")], "void", "Sets the result to `color`."),
+];
+
+pub fn builtin(name: &str) -> Option<&'static ShaderFunction> {
+  BUILTINS.iter().find(|function| function.name == name)
+}
+
+/// The overload of the builtin `name` taking `arity` arguments, or its first one if none does.
+pub fn builtin_overload(name: &str, arity: usize) -> Option<&'static ShaderFunction> {
+  BUILTINS
+    .iter()
+    .find(|function| function.name == name && function.params.len() == arity)
+    .or_else(|| builtin(name))
+}
+
+pub fn sdf2d_method(name: &str) -> Option<&'static ShaderFunction> {
+  SDF2D_METHODS.iter().find(|function| function.name == name)
+}
@@ -0,0 +1,730 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::{
+  builtins::{builtin_overload, sdf2d_method, ShaderFunction},
+  lexer::{tokenize_shader, ShaderToken, ShaderTokenKind},
+  scope::ShaderScope,
+  types::ShaderType,
+};
+
+/// A shader `fn` as written in `live_design!`.
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderFn<'a> {
+  pub name: &'a str,
+  /// `self, pos: vec2`
+  pub params: &'a str,
+  pub return_type: Option<&'a str>,
+  /// The body, braces included.
+  pub body: &'a str,
+}
+
+/// A problem found by the checker, `span` being relative to the checked body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderDiagnostic {
+  pub message: String,
+  pub span: Range<usize>,
+  /// Set for what may come from the draw shader tables being incomplete rather than the code.
+  pub is_warning: bool,
+}
+
+/// The `fn`s makepad calls itself, with the type they must return.
+pub fn entry_point_return_type(name: &str) -> Option<ShaderType> {
+  match name {
+    "pixel" | "vertex" | "get_color" => Some(ShaderType::Vec4),
+    _ => None,
+  }
+}
+
+/// The problem with the signature of `shader_fn`, if any.
+pub fn check_signature(shader_fn: &ShaderFn) -> Option<String> {
+  let declared = shader_fn.return_type.unwrap_or("void");
+  let Some(found) = ShaderType::from_name(declared) else {
+    return Some(format!("unknown shader type `{}`", declared));
+  };
+  match entry_point_return_type(shader_fn.name) {
+    Some(expected) if expected != found => Some(format!(
+      "`fn {}` must return `{}`, found `{}`",
+      shader_fn.name, expected, found
+    )),
+    _ => None,
+  }
+}
+
+/// Type checks the body of `shader_fn`: the arithmetic between vectors, the arguments of
+/// builtins and `Sdf2d` methods, the `self.` fields against `scope` and the returned values.
+pub fn check_shader_fn(shader_fn: &ShaderFn, scope: &ShaderScope) -> Vec<ShaderDiagnostic> {
+  let mut params = HashMap::new();
+  for param in shader_fn.params.split(',') {
+    if let Some((name, ty)) = param.split_once(':') {
+      let ty = ShaderType::from_name(ty.trim()).unwrap_or(ShaderType::Unknown);
+      params.insert(name.trim().to_string(), ty);
+    }
+  }
+  let return_type = shader_fn
+    .return_type
+    .map_or(Some(ShaderType::Void), ShaderType::from_name)
+    .unwrap_or(ShaderType::Unknown);
+
+  let mut checker = Checker {
+    tokens: tokenize_shader(shader_fn.body),
+    index: 0,
+    scope,
+    locals: vec![params],
+    return_type,
+    has_return: false,
+    diagnostics: vec![],
+  };
+  checker.block();
+  if !checker.has_return && !matches!(return_type, ShaderType::Void | ShaderType::Unknown) {
+    let message = format!("missing `return` of a `{}`", return_type);
+    checker.error(message, 0..1.min(shader_fn.body.len()));
+  }
+  checker.diagnostics
+}
+
+#[derive(Debug, Clone)]
+struct Value {
+  ty: ShaderType,
+  span: Range<usize>,
+}
+
+const BINARY_LEVELS: &[&[&str]] = &[
+  &["||"],
+  &["&&"],
+  &["==", "!=", "<", ">", "<=", ">="],
+  &["+", "-"],
+  &["*", "/", "%"],
+];
+
+const ASSIGNMENTS: &[&str] = &["=", "+=", "-=", "*=", "/="];
+
+struct Checker<'a> {
+  tokens: Vec<ShaderToken>,
+  index: usize,
+  scope: &'a ShaderScope,
+  locals: Vec<HashMap<String, ShaderType>>,
+  return_type: ShaderType,
+  has_return: bool,
+  diagnostics: Vec<ShaderDiagnostic>,
+}
+
+impl Checker<'_> {
+  fn peek(&self) -> Option<&ShaderToken> {
+    self.tokens.get(self.index)
+  }
+
+  fn peek_nth(&self, n: usize) -> Option<&ShaderToken> {
+    self.tokens.get(self.index + n)
+  }
+
+  fn bump(&mut self) -> Option<ShaderToken> {
+    let token = self.tokens.get(self.index).cloned();
+    self.index += usize::from(token.is_some());
+    token
+  }
+
+  fn at_punct(&self, punct: &str) -> bool {
+    self.peek().is_some_and(|token| token.is_punct(punct))
+  }
+
+  fn at_ident(&self, name: &str) -> bool {
+    self.peek().and_then(ShaderToken::ident) == Some(name)
+  }
+
+  fn eat_punct(&mut self, punct: &str) -> bool {
+    let found = self.at_punct(punct);
+    self.index += usize::from(found);
+    found
+  }
+
+  fn eat_ident(&mut self) -> Option<ShaderToken> {
+    self.peek().and_then(ShaderToken::ident)?;
+    self.bump()
+  }
+
+  /// The end of the last consumed token.
+  fn prev_end(&self) -> usize {
+    self.index.checked_sub(1).map_or(0, |index| self.tokens[index].span.end)
+  }
+
+  fn error(&mut self, message: String, span: Range<usize>) {
+    self.diagnostics.push(ShaderDiagnostic { message, span, is_warning: false });
+  }
+
+  fn warning(&mut self, message: String, span: Range<usize>) {
+    self.diagnostics.push(ShaderDiagnostic { message, span, is_warning: true });
+  }
+
+  fn mismatch(&mut self, expected: ShaderType, found: &Value) {
+    if !expected.accepts(found.ty) {
+      self.error(format!("expected `{}`, found `{}`", expected, found.ty), found.span.clone());
+    }
+  }
+
+  fn lookup(&self, name: &str) -> Option<ShaderType> {
+    self.locals.iter().rev().find_map(|locals| locals.get(name).copied())
+  }
+
+  fn declare(&mut self, name: String, ty: ShaderType) {
+    if let Some(locals) = self.locals.last_mut() {
+      locals.insert(name, ty);
+    }
+  }
+
+  /// `{ ... }` with its own locals, or a single statement.
+  fn block(&mut self) {
+    if !self.eat_punct("{") {
+      self.statement();
+      return;
+    }
+    self.locals.push(HashMap::new());
+    while self.peek().is_some() && !self.eat_punct("}") {
+      self.statement();
+    }
+    self.locals.pop();
+  }
+
+  fn statement(&mut self) {
+    let start = self.index;
+    match self.peek().and_then(ShaderToken::ident) {
+      Some("let" | "var") => {
+        self.bump();
+        let name = self.eat_ident().and_then(|token| token.ident().map(str::to_string));
+        let mut declared = None;
+        if self.eat_punct(":") {
+          if let Some(token) = self.eat_ident() {
+            let name = token.ident().unwrap_or_default();
+            let ty = ShaderType::from_name(name);
+            if ty.is_none() {
+              self.error(format!("unknown shader type `{}`", name), token.span.clone());
+            }
+            declared = Some(ty.unwrap_or(ShaderType::Unknown));
+          }
+        }
+        let value = self.eat_punct("=").then(|| self.expression());
+        if let (Some(declared), Some(value)) = (declared, &value) {
+          self.mismatch(declared, value);
+        }
+        let ty = declared.or(value.map(|value| value.ty)).unwrap_or(ShaderType::Unknown);
+        if let Some(name) = name {
+          self.declare(name, ty);
+        }
+      }
+      Some("return") => {
+        let keyword = self.bump().map(|token| token.span).unwrap_or_default();
+        self.has_return = true;
+        let value = match self.at_punct(";") || self.at_punct("}") || self.peek().is_none() {
+          true => Value { ty: ShaderType::Void, span: keyword },
+          false => self.expression(),
+        };
+        if !self.return_type.accepts(value.ty) {
+          let message =
+            format!("expected `{}` to be returned, found `{}`", self.return_type, value.ty);
+          self.error(message, value.span);
+        }
+      }
+      Some("if") => {
+        self.bump();
+        self.expression();
+        self.block();
+        if self.at_ident("else") {
+          self.bump();
+          self.block();
+        }
+      }
+      Some("for") => {
+        self.bump();
+        let name = self.eat_ident().and_then(|token| token.ident().map(str::to_string));
+        if self.at_ident("in") {
+          self.bump();
+        }
+        self.expression();
+        if self.eat_punct("..") {
+          self.expression();
+        }
+        if self.at_ident("step") {
+          self.bump();
+          self.expression();
+        }
+        self.locals.push(HashMap::from_iter(name.map(|name| (name, ShaderType::Int))));
+        self.block();
+        self.locals.pop();
+      }
+      Some("while") => {
+        self.bump();
+        self.expression();
+        self.block();
+      }
+      Some("break" | "continue") => {
+        self.bump();
+      }
+      _ if self.at_punct("{") => self.block(),
+      _ => {
+        let target = self.expression();
+        let operator = ASSIGNMENTS.iter().copied().find(|operator| self.at_punct(operator));
+        if let Some(operator) = operator {
+          self.bump();
+          let mut value = self.expression();
+          let arithmetic = operator.strip_suffix('=').filter(|operator| !operator.is_empty());
+          if let Some(operator) = arithmetic {
+            let span = target.span.start..value.span.end;
+            value = Value { ty: self.arithmetic(operator, &target, &value), span: value.span };
+            if value.ty == ShaderType::Unknown {
+              value.span = span;
+            }
+          }
+          self.mismatch(target.ty, &value);
+        }
+      }
+    }
+    self.eat_punct(";");
+    if self.index == start {
+      self.bump();
+    }
+  }
+
+  fn expression(&mut self) -> Value {
+    let condition = self.binary(0);
+    if !self.eat_punct("?") {
+      return condition;
+    }
+    let then = self.expression();
+    self.eat_punct(":");
+    let otherwise = self.expression();
+    self.mismatch(then.ty, &otherwise);
+    Value { ty: then.ty, span: condition.span.start..otherwise.span.end }
+  }
+
+  fn binary(&mut self, level: usize) -> Value {
+    let Some(operators) = BINARY_LEVELS.get(level) else {
+      return self.unary();
+    };
+    let mut left = self.binary(level + 1);
+    while let Some(operator) = operators.iter().copied().find(|operator| self.at_punct(operator)) {
+      self.bump();
+      let right = self.binary(level + 1);
+      let ty = match level {
+        0..=2 => ShaderType::Bool,
+        _ => self.arithmetic(operator, &left, &right),
+      };
+      left = Value { ty, span: left.span.start..right.span.end };
+    }
+    left
+  }
+
+  /// The type of `left operator right`, reporting vectors of different sizes.
+  fn arithmetic(&mut self, operator: &str, left: &Value, right: &Value) -> ShaderType {
+    use ShaderType::*;
+    match (left.ty, right.ty) {
+      (Unknown, _) | (_, Unknown) => Unknown,
+      (l, r) if l == r => l,
+      (l, r) if l.is_scalar() && r.is_scalar() => Float,
+      (l, r) if l.is_scalar() => r,
+      (l, r) if r.is_scalar() => l,
+      (Mat2, Vec2) | (Vec2, Mat2) => Vec2,
+      (Mat3, Vec3) | (Vec3, Mat3) => Vec3,
+      (Mat4, Vec4) | (Vec4, Mat4) => Vec4,
+      (l, r) => {
+        let message = format!("mismatched types `{}` and `{}` in `{}`", l, r, operator);
+        self.error(message, left.span.start..right.span.end);
+        Unknown
+      }
+    }
+  }
+
+  fn unary(&mut self) -> Value {
+    let Some(start) = self.peek().map(|token| token.span.start) else {
+      return Value { ty: ShaderType::Unknown, span: self.prev_end()..self.prev_end() };
+    };
+    if self.eat_punct("-") || self.eat_punct("!") {
+      let value = self.unary();
+      return Value { ty: value.ty, span: start..value.span.end };
+    }
+    let value = self.primary();
+    self.postfix(value)
+  }
+
+  fn primary(&mut self) -> Value {
+    let Some(token) = self.peek().cloned() else {
+      return Value { ty: ShaderType::Unknown, span: self.prev_end()..self.prev_end() };
+    };
+    let unknown = |span| Value { ty: ShaderType::Unknown, span };
+    match &token.kind {
+      ShaderTokenKind::Number(text) => {
+        self.bump();
+        let is_float = text.contains(['.', 'e', 'E']);
+        let ty = if is_float { ShaderType::Float } else { ShaderType::Int };
+        Value { ty, span: token.span }
+      }
+      ShaderTokenKind::Color(_) => {
+        self.bump();
+        Value { ty: ShaderType::Vec4, span: token.span }
+      }
+      ShaderTokenKind::Punct("(") => {
+        self.bump();
+        let value = self.expression();
+        self.eat_punct(")");
+        Value { ty: value.ty, span: token.span.start..self.prev_end() }
+      }
+      ShaderTokenKind::Ident(name) => {
+        self.bump();
+        match name.as_str() {
+          "true" | "false" => Value { ty: ShaderType::Bool, span: token.span },
+          "self" => self.self_access(token.span),
+          _ if self.at_punct("::") => {
+            let mut path = vec![name.clone()];
+            while self.eat_punct("::") {
+              if let Some(segment) = self.eat_ident() {
+                path.extend(segment.ident().map(str::to_string));
+              }
+            }
+            if !self.at_punct("(") {
+              return unknown(token.span.start..self.prev_end());
+            }
+            let arguments = self.arguments();
+            let span = token.span.start..self.prev_end();
+            match path.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+              ["Sdf2d", method] => match sdf2d_method(method) {
+                Some(function) => self.call(function, &arguments, span),
+                None => unknown(span),
+              },
+              _ => unknown(span),
+            }
+          }
+          _ if self.at_punct("(") => {
+            let arguments = self.arguments();
+            let span = token.span.start..self.prev_end();
+            match ShaderType::from_name(name) {
+              Some(ty) => {
+                self.construct(ty, &arguments, span.clone());
+                Value { ty, span }
+              }
+              None => match builtin_overload(name, arguments.len()) {
+                Some(function) => self.call(function, &arguments, span),
+                None => unknown(span),
+              },
+            }
+          }
+          _ => Value { ty: self.lookup(name).unwrap_or(ShaderType::Unknown), span: token.span },
+        }
+      }
+      ShaderTokenKind::Punct(punct) => {
+        // Leave closing delimiters to whoever opened them.
+        if !matches!(*punct, ")" | "}" | "]" | ";" | ",") {
+          self.bump();
+        }
+        unknown(token.span)
+      }
+    }
+  }
+
+  /// `self.field` or `self.method(...)`, `self` spanning `span`.
+  fn self_access(&mut self, span: Range<usize>) -> Value {
+    if !self.eat_punct(".") {
+      return Value { ty: ShaderType::Unknown, span };
+    }
+    let Some(member) = self.eat_ident() else {
+      return Value { ty: ShaderType::Unknown, span: span.start..self.prev_end() };
+    };
+    let name = member.ident().unwrap_or_default();
+    if self.at_punct("(") {
+      self.arguments();
+      let ty = self.scope.methods.get(name).copied().unwrap_or(ShaderType::Unknown);
+      return Value { ty, span: span.start..self.prev_end() };
+    }
+    let ty = match self.scope.fields.get(name) {
+      Some(field) => field.ty,
+      None => {
+        if self.scope.is_complete {
+          self.warning(format!("no field `{}` on `self`", name), member.span.clone());
+        }
+        ShaderType::Unknown
+      }
+    };
+    Value { ty, span: span.start..member.span.end }
+  }
+
+  /// Swizzles, `Sdf2d` fields and methods, and indexing after `value`.
+  fn postfix(&mut self, mut value: Value) -> Value {
+    loop {
+      if self.at_punct(".") && self.peek_nth(1).and_then(ShaderToken::ident).is_some() {
+        self.bump();
+        let Some(member) = self.bump() else {
+          return value;
+        };
+        let name = member.ident().unwrap_or_default().to_string();
+        if self.at_punct("(") {
+          let arguments = self.arguments();
+          let span = value.span.start..self.prev_end();
+          // The method table covers the common shapes only, so anything else goes unchecked.
+          value = match (value.ty, sdf2d_method(&name)) {
+            (ShaderType::Sdf2d, Some(function)) => self.call(function, &arguments, span),
+            _ => Value { ty: ShaderType::Unknown, span },
+          };
+          continue;
+        }
+        let span = value.span.start..member.span.end;
+        let ty = match value.ty {
+          ShaderType::Unknown => ShaderType::Unknown,
+          ShaderType::Sdf2d => sdf2d_field(&name),
+          ty => match swizzle(ty, &name) {
+            Some(ty) => ty,
+            None => {
+              self.error(format!("no field `{}` on `{}`", name, ty), member.span);
+              ShaderType::Unknown
+            }
+          },
+        };
+        value = Value { ty, span };
+      } else if self.eat_punct("[") {
+        self.expression();
+        self.eat_punct("]");
+        let ty = match value.ty {
+          ty if ty.is_vector() => ty.element(),
+          ShaderType::Mat2 => ShaderType::Vec2,
+          ShaderType::Mat3 => ShaderType::Vec3,
+          ShaderType::Mat4 => ShaderType::Vec4,
+          _ => ShaderType::Unknown,
+        };
+        value = Value { ty, span: value.span.start..self.prev_end() };
+      } else {
+        return value;
+      }
+    }
+  }
+
+  /// The arguments of a call, from `(` to `)`.
+  fn arguments(&mut self) -> Vec<Value> {
+    let mut arguments = vec![];
+    self.eat_punct("(");
+    while self.peek().is_some() && !self.eat_punct(")") {
+      let start = self.index;
+      arguments.push(self.expression());
+      self.eat_punct(",");
+      if self.index == start {
+        self.bump();
+      }
+    }
+    arguments
+  }
+
+  /// Checks the arguments of a builtin or `Sdf2d` method and returns what it returns.
+  fn call(&mut self, function: &ShaderFunction, arguments: &[Value], span: Range<usize>) -> Value {
+    if arguments.len() != function.params.len() {
+      let message = format!(
+        "`{}` takes {} argument{}, found {}",
+        function.name,
+        function.params.len(),
+        if function.params.len() == 1 { "" } else { "s" },
+        arguments.len()
+      );
+      self.error(message, span.clone());
+    }
+
+    // `T` parameters take the same vector type, or floats.
+    let mut generic = None;
+    let mut is_known = true;
+    for (argument, (_, ty)) in arguments.iter().zip(function.params) {
+      if *ty != "T" {
+        let expected = ShaderType::from_name(ty).unwrap_or(ShaderType::Unknown);
+        self.mismatch(expected, argument);
+        continue;
+      }
+      match (argument.ty, generic) {
+        (ShaderType::Unknown, _) => is_known = false,
+        (ty, _) if ty.is_scalar() => {}
+        (ty, None) if ty.is_vector() => generic = Some(ty),
+        (ty, Some(generic)) if ty == generic => {}
+        (ty, Some(generic)) if ty.is_vector() => {
+          let message = format!(
+            "mismatched types `{}` and `{}` in `{}`",
+            generic, argument.ty, function.name
+          );
+          self.error(message, argument.span.clone());
+        }
+        (ty, _) => {
+          let message = format!("expected `float` or a vector, found `{}`", ty);
+          self.error(message, argument.span.clone());
+        }
+      }
+    }
+
+    let ty = match function.return_type {
+      "T" => generic.unwrap_or(if is_known { ShaderType::Float } else { ShaderType::Unknown }),
+      name => ShaderType::from_name(name).unwrap_or(ShaderType::Unknown),
+    };
+    Value { ty, span }
+  }
+
+  /// Checks that `vec4(...)` and the like get the right number of components.
+  fn construct(&mut self, ty: ShaderType, arguments: &[Value], span: Range<usize>) {
+    let Some(expected) = ty.components().filter(|components| *components > 1) else {
+      return;
+    };
+    let components = arguments.iter().map(|argument| argument.ty.components());
+    let Some(found) = components.sum::<Option<usize>>() else {
+      return;
+    };
+    let is_splat = arguments.len() == 1 && found == 1;
+    if !is_splat && found != expected {
+      let message = format!("`{}` takes {} components, found {}", ty, expected, found);
+      self.error(message, span);
+    }
+  }
+}
+
+/// The type of `name` picked out of a vector, as in `pos.xy` or `color.rgb`.
+fn swizzle(ty: ShaderType, name: &str) -> Option<ShaderType> {
+  let components = ty.components().filter(|_| ty.is_vector())?;
+  let in_set = |set: &str| name.chars().all(|c| set[..components].contains(c));
+  let is_swizzle = (1..=4).contains(&name.len()) && (in_set("xyzw") || in_set("rgba"));
+  is_swizzle.then(|| ShaderType::vector(ty.element(), name.len()))
+}
+
+fn sdf2d_field(name: &str) -> ShaderType {
+  match name {
+    "result" => ShaderType::Vec4,
+    "pos" => ShaderType::Vec2,
+    "shape" | "dist" | "aa" | "field" => ShaderType::Float,
+    _ => ShaderType::Unknown,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::scope::ShaderField;
+
+  fn check(body: &str, scope: &ShaderScope) -> Vec<(String, String)> {
+    let shader_fn = ShaderFn { name: "pixel", params: "self", return_type: Some("vec4"), body };
+    check_shader_fn(&shader_fn, scope)
+      .into_iter()
+      .map(|diagnostic| (diagnostic.message, body[diagnostic.span].to_string()))
+      .collect()
+  }
+
+  fn draw_scope() -> ShaderScope {
+    ShaderScope {
+      fields: [
+        ("pos", ShaderType::Vec2),
+        ("rect_size", ShaderType::Vec2),
+        ("color", ShaderType::Vec4),
+        ("hover", ShaderType::Float),
+      ]
+      .into_iter()
+      .map(|(name, ty)| (name.to_string(), ShaderField { ty, origin: "DrawTest".to_string() }))
+      .collect(),
+      methods: HashMap::from([("get_color".to_string(), ShaderType::Vec4)]),
+      is_complete: true,
+    }
+  }
+
+  #[test]
+  fn test_well_typed_shader() {
+    let body = r#"{
+        let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+        let sz = 3.;
+        let c = self.rect_size * 0.5;
+        let angle = atan(c.y, c.x) + atan(c.y / c.x);
+        sdf.circle(c.x, c.y, sz);
+        sdf.box(1.0, 1.0, self.rect_size.x - 2.0, self.rect_size.y - 2.0, 4.0);
+        var color = mix(self.color, #f00, self.hover);
+        if self.hover > 0.5 {
+            color = vec4(color.rgb * 0.5, 1.0);
+        }
+        // Comments are fine: vec2(1.0) + vec4(1.0)
+        for i in 0..4 {
+            color.a *= 0.9;
+        }
+        sdf.fill(max(color, 0.0));
+        sdf.stroke(self.get_color(), 1.0);
+        sdf.box_all(0.0, 0.0, 10.0, 10.0, 1.0, 2.0, 3.0, 4.0);
+        sdf.gloop(2.0);
+        return sdf.result;
+    }"#;
+    assert_eq!(check(body, &draw_scope()), vec![]);
+  }
+
+  #[test]
+  fn test_type_errors() {
+    let body = r#"{
+        let a = self.pos + self.color;
+        let b: vec2 = self.color.xyz;
+        let c = vec4(self.pos, 1.0);
+        let d = mix(self.pos, self.color, 0.5);
+        let e = self.pos.z;
+        let sdf = Sdf2d::viewport(self.pos);
+        sdf.fill(1.0, 2.0);
+        return self.color.rgb;
+    }"#;
+    assert_eq!(check(body, &draw_scope()), vec![
+      (
+        "mismatched types `vec2` and `vec4` in `+`".to_string(),
+        "self.pos + self.color".to_string()
+      ),
+      ("expected `vec2`, found `vec3`".to_string(), "self.color.xyz".to_string()),
+      ("`vec4` takes 4 components, found 3".to_string(), "vec4(self.pos, 1.0)".to_string()),
+      ("mismatched types `vec2` and `vec4` in `mix`".to_string(), "self.color".to_string()),
+      ("no field `z` on `vec2`".to_string(), "z".to_string()),
+      ("`fill` takes 1 argument, found 2".to_string(), "sdf.fill(1.0, 2.0)".to_string()),
+      ("expected `vec4`, found `float`".to_string(), "1.0".to_string()),
+      (
+        "expected `vec4` to be returned, found `vec3`".to_string(),
+        "self.color.rgb".to_string()
+      ),
+    ]);
+  }
+
+  #[test]
+  fn test_integer_and_boolean_vectors() {
+    let body = r#"{
+        let cell: ivec2 = ivec2(self.pos * 8.0);
+        let x: int = cell.x;
+        let mask: bvec3 = bvec3(true, false, true);
+        let flip: bvec2 = mask.xy;
+        let count: uint = 4;
+        let p = abs(cell - ivec2(1, 2));
+        return vec4(self.pos + self.rect_size, 0.0, 1.0);
+    }"#;
+    assert_eq!(check(body, &draw_scope()), vec![]);
+    let body = "{ let c: ivec2 = self.pos.yx; return self.color; }";
+    assert_eq!(check(body, &draw_scope()), vec![(
+      "expected `ivec2`, found `vec2`".to_string(),
+      "self.pos.yx".to_string()
+    )]);
+  }
+
+  #[test]
+  fn test_unknown_fields() {
+    let body = "{ return mix(self.color, #f00, self.hover + self.pressed); }";
+    assert_eq!(check(body, &draw_scope()), vec![(
+      "no field `pressed` on `self`".to_string(),
+      "pressed".to_string()
+    )]);
+    let shader_fn = ShaderFn { name: "pixel", params: "self", return_type: Some("vec4"), body };
+    assert!(check_shader_fn(&shader_fn, &draw_scope())[0].is_warning);
+    let partial = ShaderScope { is_complete: false, ..draw_scope() };
+    assert_eq!(check(body, &partial), vec![]);
+  }
+
+  #[test]
+  fn test_signature() {
+    let shader_fn = |name, return_type| ShaderFn { name, params: "self", return_type, body: "{}" };
+    assert_eq!(check_signature(&shader_fn("pixel", Some("vec4"))), None);
+    assert_eq!(check_signature(&shader_fn("helper", None)), None);
+    assert_eq!(
+      check_signature(&shader_fn("get_color", Some("vec3"))),
+      Some("`fn get_color` must return `vec4`, found `vec3`".to_string())
+    );
+    assert_eq!(
+      check_signature(&shader_fn("pixel", Some("vec5"))),
+      Some("unknown shader type `vec5`".to_string())
+    );
+    let missing = ShaderFn { name: "pixel", params: "self", return_type: Some("vec4"), body: "{ }" };
+    assert_eq!(check_shader_fn(&missing, &ShaderScope::default()), vec![ShaderDiagnostic {
+      message: "missing `return` of a `vec4`".to_string(),
+      span: 0..1,
+      is_warning: false,
+    }]);
+  }
+}
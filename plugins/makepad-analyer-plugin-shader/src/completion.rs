@@ -0,0 +1,97 @@
+use makepad_analyzer_plugin_types::{
+  CompletionItem, CompletionItemKind, Documentation, MarkupContent, MarkupKind,
+};
+
+use crate::builtins::{ShaderFunction, BUILTINS, SDF2D_METHODS};
+
+/// The builtins, or the `Sdf2d` methods after `sdf.` when `sdf` holds an `Sdf2d::viewport(..)`,
+/// to complete at `offset` of the shader `source`. Nothing after `self.`, whose fields depend
+/// on the draw shader.
+pub fn shader_completion_items(source: &str, offset: usize) -> Vec<CompletionItem> {
+  let Some(prefix) = source.get(..offset) else {
+    return vec![];
+  };
+  let word_start = prefix
+    .rfind(|c: char| !c.is_alphanumeric() && c != '_')
+    .map_or(0, |index| index + 1);
+  let before = &prefix[..word_start];
+
+  if let Some(receiver) = before.strip_suffix('.') {
+    let receiver_start = receiver
+      .rfind(|c: char| !c.is_alphanumeric() && c != '_')
+      .map_or(0, |index| index + 1);
+    let receiver = &receiver[receiver_start..];
+    if receiver == "self" || !is_sdf2d(prefix, receiver) {
+      return vec![];
+    }
+    return SDF2D_METHODS
+      .iter()
+      .filter(|method| method.name != "viewport")
+      .map(|method| function_item(method, CompletionItemKind::METHOD))
+      .collect();
+  }
+  if before.trim_end().ends_with("Sdf2d::") {
+    return SDF2D_METHODS
+      .iter()
+      .filter(|method| method.name == "viewport")
+      .map(|method| function_item(method, CompletionItemKind::FUNCTION))
+      .collect();
+  }
+  BUILTINS
+    .iter()
+    .map(|builtin| function_item(builtin, CompletionItemKind::FUNCTION))
+    .collect()
+}
+
+/// Whether `source` binds `name` with `let name = Sdf2d::viewport(...)`.
+fn is_sdf2d(source: &str, name: &str) -> bool {
+  source.split([';', '{', '}']).any(|statement| {
+    let Some(binding) = statement.trim().strip_prefix("let ") else {
+      return false;
+    };
+    let Some((pattern, value)) = binding.split_once('=') else {
+      return false;
+    };
+    pattern.trim().trim_start_matches("mut ").trim() == name
+      && value.trim_start().starts_with("Sdf2d::viewport")
+  })
+}
+
+fn function_item(function: &ShaderFunction, kind: CompletionItemKind) -> CompletionItem {
+  CompletionItem {
+    label: function.name.to_string(),
+    kind: Some(kind),
+    detail: Some(function.label()),
+    documentation: Some(Documentation::MarkupContent(MarkupContent {
+      kind: MarkupKind::Markdown,
+      value: function.doc.to_string(),
+    })),
+    ..Default::default()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn labels(source: &str) -> Vec<String> {
+    let offset = source.find('|').unwrap();
+    let source = source.replace('|', "");
+    shader_completion_items(&source, offset).into_iter().map(|item| item.label).collect()
+  }
+
+  #[test]
+  fn test_shader_completion_items() {
+    let builtins = labels("{ return mi| }");
+    assert!(builtins.contains(&"mix".to_string()));
+    assert!(builtins.contains(&"smoothstep".to_string()));
+
+    let methods = labels("{ let sdf = Sdf2d::viewport(self.pos * self.rect_size);\n sdf.| }");
+    assert!(methods.contains(&"circle".to_string()));
+    assert!(!methods.contains(&"viewport".to_string()));
+    assert_eq!(labels("{ let sdf = Sdf2d::|"), ["viewport"]);
+
+    assert!(labels("{ return self.| }").is_empty());
+    assert!(labels("{ let c = color.| }").is_empty());
+  }
+}
@@ -0,0 +1,194 @@
+/// A draw shader of the framework, such as `DrawQuad`, with the fields and `fn`s its shader
+/// code reaches through `self.`.
+#[derive(Debug, Clone, Copy)]
+pub struct DrawShader {
+  pub name: &'static str,
+  pub base: Option<&'static str>,
+  pub fields: &'static [(&'static str, &'static str)],
+  pub methods: &'static [(&'static str, &'static str)],
+}
+
+/// The draw shader behind a `draw_*` property of a framework widget, plus the `instance`
+/// fields the theme declares on it, e.g. the `hover` of a `Button`'s `draw_bg`.
+#[derive(Debug, Clone, Copy)]
+pub struct WidgetDrawShader {
+  pub widget: &'static str,
+  pub property: &'static str,
+  pub draw_shader: &'static str,
+  pub instances: &'static [(&'static str, &'static str)],
+}
+
+/// Fields every draw shader gets from the draw list it is drawn into.
+pub(crate) const DRAW_LIST_UNIFORMS: &[(&str, &str)] = &[
+  ("camera_projection", "mat4"),
+  ("camera_view", "mat4"),
+  ("view_transform", "mat4"),
+  ("dpi_factor", "float"),
+];
+
+pub const DRAW_SHADERS: &[DrawShader] = &[
+  DrawShader {
+    name: "DrawQuad",
+    base: None,
+    fields: &[
+      ("pos", "vec2"),
+      ("geom_pos", "vec2"),
+      ("rect_pos", "vec2"),
+      ("rect_size", "vec2"),
+      ("draw_clip", "vec4"),
+      ("draw_depth", "float"),
+      ("draw_zbias", "float"),
+    ],
+    methods: &[("vertex", "vec4"), ("pixel", "vec4"), ("clip_and_transform_vertex", "vec4")],
+  },
+  DrawShader {
+    name: "DrawColor",
+    base: Some("DrawQuad"),
+    fields: &[("color", "vec4")],
+    methods: &[],
+  },
+  DrawShader {
+    name: "DrawIcon",
+    base: Some("DrawQuad"),
+    fields: &[
+      ("color", "vec4"),
+      ("brightness", "float"),
+      ("curve", "float"),
+      ("linearize", "float"),
+      ("svg_scale", "vec2"),
+      ("svg_offset", "vec2"),
+    ],
+    methods: &[("get_color", "vec4")],
+  },
+  DrawShader {
+    name: "DrawText",
+    base: None,
+    fields: &[
+      ("pos", "vec2"),
+      ("rect_pos", "vec2"),
+      ("rect_size", "vec2"),
+      ("draw_clip", "vec4"),
+      ("draw_depth", "float"),
+      ("char_depth", "float"),
+      ("color", "vec4"),
+      ("font_t1", "vec2"),
+      ("font_t2", "vec2"),
+      ("tex_coord1", "vec2"),
+      ("tex_coord2", "vec2"),
+      ("tex_coord3", "vec2"),
+      ("clipped", "vec2"),
+      ("brightness", "float"),
+      ("curve", "float"),
+      ("sdf_radius", "float"),
+      ("sdf_cutoff", "float"),
+    ],
+    methods: &[("vertex", "vec4"), ("pixel", "vec4"), ("get_color", "vec4")],
+  },
+  DrawShader {
+    name: "DrawRadioButton",
+    base: Some("DrawQuad"),
+    fields: &[
+      ("hover", "float"),
+      ("focus", "float"),
+      ("selected", "float"),
+      ("radio_type", "float"),
+    ],
+    methods: &[],
+  },
+  DrawShader {
+    name: "DrawCheckBox",
+    base: Some("DrawQuad"),
+    fields: &[
+      ("hover", "float"),
+      ("focus", "float"),
+      ("selected", "float"),
+      ("check_type", "float"),
+    ],
+    methods: &[],
+  },
+];
+
+const HOVER_FOCUS: &[(&str, &str)] = &[("hover", "float"), ("focus", "float")];
+const PRESSABLE: &[(&str, &str)] = &[("hover", "float"), ("pressed", "float"), ("focus", "float")];
+const SELECTABLE: &[(&str, &str)] =
+  &[("hover", "float"), ("focus", "float"), ("selected", "float")];
+const BORDERED: &[(&str, &str)] = &[
+  ("border_width", "float"),
+  ("border_color", "vec4"),
+  ("inset", "vec4"),
+  ("radius", "float"),
+];
+
+const fn widget(
+  widget: &'static str,
+  property: &'static str,
+  draw_shader: &'static str,
+  instances: &'static [(&'static str, &'static str)],
+) -> WidgetDrawShader {
+  WidgetDrawShader { widget, property, draw_shader, instances }
+}
+
+pub const WIDGET_DRAW_SHADERS: &[WidgetDrawShader] = &[
+  widget("View", "draw_bg", "DrawColor", &[]),
+  widget("SolidView", "draw_bg", "DrawColor", &[]),
+  widget("RoundedView", "draw_bg", "DrawColor", BORDERED),
+  widget("ScrollXView", "draw_bg", "DrawColor", &[]),
+  widget("ScrollYView", "draw_bg", "DrawColor", &[]),
+  widget("ScrollXYView", "draw_bg", "DrawColor", &[]),
+  widget("Label", "draw_text", "DrawText", &[]),
+  widget("Button", "draw_bg", "DrawQuad", PRESSABLE),
+  widget("Button", "draw_text", "DrawText", PRESSABLE),
+  widget("Button", "draw_icon", "DrawIcon", PRESSABLE),
+  widget("LinkLabel", "draw_bg", "DrawQuad", PRESSABLE),
+  widget("LinkLabel", "draw_text", "DrawText", PRESSABLE),
+  widget("LinkLabel", "draw_icon", "DrawIcon", PRESSABLE),
+  widget("RadioButton", "draw_radio", "DrawRadioButton", &[]),
+  widget("RadioButton", "draw_text", "DrawText", SELECTABLE),
+  widget("RadioButton", "draw_icon", "DrawIcon", SELECTABLE),
+  widget("CheckBox", "draw_check", "DrawCheckBox", &[]),
+  widget("CheckBox", "draw_text", "DrawText", SELECTABLE),
+  widget("CheckBox", "draw_icon", "DrawIcon", SELECTABLE),
+  widget("TextInput", "draw_bg", "DrawColor", HOVER_FOCUS),
+  widget("TextInput", "draw_text", "DrawText", HOVER_FOCUS),
+  widget("TextInput", "draw_cursor", "DrawQuad", HOVER_FOCUS),
+  widget("TextInput", "draw_selection", "DrawQuad", HOVER_FOCUS),
+  widget("Icon", "draw_bg", "DrawQuad", &[]),
+  widget("Icon", "draw_icon", "DrawIcon", &[]),
+  widget(
+    "Image",
+    "draw_bg",
+    "DrawQuad",
+    &[("image", "texture2d"), ("opacity", "float"), ("image_scale", "vec2"), ("image_pan", "vec2")],
+  ),
+  widget(
+    "Slider",
+    "draw_slider",
+    "DrawQuad",
+    &[("hover", "float"), ("focus", "float"), ("drag", "float"), ("slide_pos", "float")],
+  ),
+  widget("Slider", "draw_text", "DrawText", HOVER_FOCUS),
+];
+
+pub fn draw_shader(name: &str) -> Option<&'static DrawShader> {
+  DRAW_SHADERS.iter().find(|draw_shader| draw_shader.name == name)
+}
+
+pub fn widget_draw_shader(widget: &str, property: &str) -> Option<&'static WidgetDrawShader> {
+  WIDGET_DRAW_SHADERS
+    .iter()
+    .find(|entry| entry.widget == widget && entry.property == property)
+}
+
+/// `name` and the draw shaders it derives from, base first.
+pub fn draw_shader_chain(name: &str) -> Vec<&'static DrawShader> {
+  let mut chain = vec![];
+  let mut next = draw_shader(name);
+  while let Some(shader) = next {
+    if chain.iter().any(|known: &&DrawShader| known.name == shader.name) {
+      break;
+    }
+    chain.insert(0, shader);
+    next = shader.base.and_then(draw_shader);
+  }
+  chain
+}
@@ -0,0 +1,45 @@
+use std::ops::Range;
+
+use crate::builtins::{builtin, sdf2d_method};
+
+/// Describes the builtin or `Sdf2d` method named at `offset` of the shader `source`, with the
+/// range of its name.
+pub fn shader_hover(source: &str, offset: usize) -> Option<(Range<usize>, String)> {
+  let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+  let start = source.get(..offset)?.rfind(|c: char| !is_ident(c)).map_or(0, |index| index + 1);
+  let end = source[offset..]
+    .find(|c: char| !is_ident(c))
+    .map_or(source.len(), |index| offset + index);
+  let name = &source[start..end];
+  let before = source[..start].trim_end();
+  let function = if before.ends_with("self.") {
+    return None;
+  } else if before.ends_with('.') || before.ends_with("::") {
+    sdf2d_method(name)?
+  } else {
+    builtin(name)?
+  };
+  let markdown = format!("```makepad\n{}\n```\n{}", function.label(), function.doc);
+  Some((start..end, markdown))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_shader_hover() {
+    let source = concat!(
+      "{ let sdf = Sdf2d::viewport(self.pos); sdf.circle(1.0, 1.0, 2.0);",
+      " return mix(a, b, self.mix); }",
+    );
+    let (range, markdown) = shader_hover(source, source.find("mix(").unwrap() + 1).unwrap();
+    assert_eq!(&source[range], "mix");
+    assert!(markdown.starts_with("```makepad\nmix(x: T, y: T, a: float) -> T\n```\n"));
+
+    let (range, _) = shader_hover(source, source.find("circle").unwrap()).unwrap();
+    assert_eq!(&source[range], "circle");
+    assert!(shader_hover(source, source.find("viewport").unwrap()).is_some());
+    assert_eq!(shader_hover(source, source.find("self.mix").unwrap() + 6), None);
+  }
+}
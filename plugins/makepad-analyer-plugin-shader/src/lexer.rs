@@ -0,0 +1,91 @@
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShaderTokenKind {
+  Ident(String),
+  Number(String),
+  /// `#f00`, a `vec4` literal.
+  Color(String),
+  Punct(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderToken {
+  pub kind: ShaderTokenKind,
+  pub span: Range<usize>,
+}
+
+impl ShaderToken {
+  pub fn is_punct(&self, punct: &str) -> bool {
+    matches!(self.kind, ShaderTokenKind::Punct(p) if p == punct)
+  }
+
+  pub fn ident(&self) -> Option<&str> {
+    match &self.kind {
+      ShaderTokenKind::Ident(name) => Some(name),
+      _ => None,
+    }
+  }
+}
+
+/// Longest first, so `+=` wins over `+`.
+const PUNCTS: &[&str] = &[
+  "::", "->", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=", "*=", "/=", "..", "+", "-", "*",
+  "/", "%", "=", "<", ">", "!", "(", ")", "{", "}", "[", "]", ",", ";", ":", ".", "?",
+];
+
+/// Splits shader code into tokens, dropping whitespace and comments. Characters that start no
+/// token are skipped.
+pub fn tokenize_shader(source: &str) -> Vec<ShaderToken> {
+  let bytes = source.as_bytes();
+  let mut tokens = vec![];
+  let mut index = 0;
+  while index < bytes.len() {
+    let rest = &source[index..];
+    let c = rest.chars().next().unwrap_or_default();
+    let start = index;
+    if c.is_whitespace() {
+      index += c.len_utf8();
+    } else if rest.starts_with("//") {
+      index += rest.find('\n').unwrap_or(rest.len());
+    } else if let Some(comment) = rest.strip_prefix("/*") {
+      index += comment.find("*/").map_or(rest.len(), |end| end + 4);
+    } else if c.is_ascii_digit() {
+      index += number_len(rest);
+      tokens.push(token(ShaderTokenKind::Number(source[start..index].to_string()), start, index));
+    } else if c == '#' {
+      index += 1 + rest[1..].find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len() - 1);
+      tokens.push(token(ShaderTokenKind::Color(source[start + 1..index].to_string()), start, index));
+    } else if c.is_alphabetic() || c == '_' {
+      index += rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(rest.len());
+      tokens.push(token(ShaderTokenKind::Ident(source[start..index].to_string()), start, index));
+    } else if let Some(punct) = PUNCTS.iter().find(|punct| rest.starts_with(**punct)) {
+      index += punct.len();
+      tokens.push(token(ShaderTokenKind::Punct(punct), start, index));
+    } else {
+      index += c.len_utf8();
+    }
+  }
+  tokens
+}
+
+fn token(kind: ShaderTokenKind, start: usize, end: usize) -> ShaderToken {
+  ShaderToken { kind, span: start..end }
+}
+
+/// `1`, `1.5` or `1e-3`, leaving the `..` of `0..4` alone.
+fn number_len(rest: &str) -> usize {
+  let bytes = rest.as_bytes();
+  let digits = |from: usize| from + bytes[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+  let mut len = digits(0);
+  if bytes.get(len) == Some(&b'.') && bytes.get(len + 1) != Some(&b'.') {
+    len = digits(len + 1);
+  }
+  if matches!(bytes.get(len), Some(b'e' | b'E')) {
+    let sign = usize::from(matches!(bytes.get(len + 1), Some(b'+' | b'-')));
+    if bytes.get(len + 1 + sign).is_some_and(u8::is_ascii_digit) {
+      len = digits(len + 1 + sign);
+    }
+  }
+  len
+}
@@ -0,0 +1,21 @@
+mod builtins;
+mod checker;
+mod completion;
+mod draw_shaders;
+mod hover;
+mod lexer;
+mod plugin;
+mod scope;
+mod signature_help;
+mod types;
+
+pub use builtins::*;
+pub use checker::*;
+pub use completion::*;
+pub use draw_shaders::*;
+pub use hover::*;
+pub use lexer::*;
+pub use plugin::*;
+pub use scope::*;
+pub use signature_help::*;
+pub use types::*;
@@ -0,0 +1,102 @@
+use makepad_analyzer_plugin_types::{
+  CompletionItem, DocumentRegion, Hover, HoverContents, MakepadAnalyzerPlugin, MarkupContent,
+  MarkupKind, PluginCapability, PluginDocument, PluginInfo, Position, ProvidedCapabilities, Range,
+};
+
+use crate::{completion::shader_completion_items, hover::shader_hover};
+
+struct ShaderPluginCapability;
+
+impl PluginCapability for ShaderPluginCapability {
+  fn provided(&self) -> ProvidedCapabilities {
+    ProvidedCapabilities {
+      regions: vec![DocumentRegion::Shader],
+      completion: true,
+      completion_trigger_characters: vec![".".to_string(), ":".to_string()],
+      hover: true,
+      ..ProvidedCapabilities::default()
+    }
+  }
+
+  fn handle_completion(
+    &self,
+    document: &PluginDocument,
+    position: Position,
+    _trigger_char: Option<&str>,
+  ) -> Vec<CompletionItem> {
+    let offset = document.offset(position);
+    match shader_body_at(document, offset) {
+      Some((start, body)) => shader_completion_items(body, offset - start),
+      None => vec![],
+    }
+  }
+
+  fn handle_hover(&self, document: &PluginDocument, position: Position) -> Option<Hover> {
+    let offset = document.offset(position);
+    let (start, body) = shader_body_at(document, offset)?;
+    let (range, value) = shader_hover(body, offset - start)?;
+    let line_index = document.line_index();
+    Some(Hover {
+      contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }),
+      range: Some(Range::new(
+        line_index.position(start + range.start),
+        line_index.position(start + range.end),
+      )),
+    })
+  }
+}
+
+/// The body of the shader `fn` around `offset`, with where it starts.
+fn shader_body_at(document: &PluginDocument, offset: usize) -> Option<(usize, &str)> {
+  let mut found = None;
+  for widget in document.ast().widgets() {
+    widget.walk(&mut |node| {
+      let mut shader_fns = node.shader_fns.iter();
+      if let Some(shader_fn) = shader_fns.find(|shader_fn| shader_fn.body_span.contains(offset)) {
+        found = Some(shader_fn.body_span);
+      }
+    });
+  }
+  let body = found?;
+  Some((body.start, body.text(document.text())))
+}
+
+/// Completes and describes the builtins and `Sdf2d` methods in shader `fn` bodies.
+pub struct MakepadAnalyzerShaderPlugin {
+  plugin_info: PluginInfo,
+  capability: ShaderPluginCapability,
+}
+
+impl MakepadAnalyzerPlugin for MakepadAnalyzerShaderPlugin {
+  fn plugin_info(&self) -> PluginInfo {
+    self.plugin_info.clone()
+  }
+
+  fn capabilities(&self) -> &dyn PluginCapability {
+    &self.capability
+  }
+
+  fn api_version(&self) -> u32 {
+    1
+  }
+}
+
+impl MakepadAnalyzerShaderPlugin {
+  pub fn new() -> Self {
+    Self::default()
+  }
+}
+
+impl Default for MakepadAnalyzerShaderPlugin {
+  fn default() -> Self {
+    MakepadAnalyzerShaderPlugin {
+      plugin_info: PluginInfo {
+        name: "Makepad Analyzer Shader Plugin".to_string(),
+        description: "A plugin for the Makepad Analyzer that provides shader builtin completion"
+          .to_string(),
+        version: "0.0.1".to_string(),
+      },
+      capability: ShaderPluginCapability,
+    }
+  }
+}
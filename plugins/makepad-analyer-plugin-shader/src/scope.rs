@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use makepad_analyzer_plugin_types::{
+  CompletionItem, CompletionItemKind, Documentation, MarkupContent, MarkupKind,
+};
+
+use crate::{
+  draw_shaders::{draw_shader_chain, widget_draw_shader, DRAW_LIST_UNIFORMS},
+  types::ShaderType,
+};
+
+/// A field `self.` reaches in shader code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderField {
+  pub ty: ShaderType,
+  /// What declares the field, e.g. `DrawQuad` or the live definition `AppTab`.
+  pub origin: String,
+}
+
+/// What `self.` gives access to in a shader `fn`.
+#[derive(Debug, Clone, Default)]
+pub struct ShaderScope {
+  pub fields: HashMap<String, ShaderField>,
+  /// The `fn`s of the shader with their return types.
+  pub methods: HashMap<String, ShaderType>,
+  /// Whether `fields` holds every field, so that the others are reported as unknown.
+  pub is_complete: bool,
+}
+
+/// The declarations and `fn`s of one `draw_*: { ... }` object of the live code.
+#[derive(Debug, Clone, Default)]
+pub struct ShaderLayer {
+  /// The definition or instance the object belongs to, e.g. `AppTab`.
+  pub origin: String,
+  pub fields: Vec<(String, ShaderType)>,
+  pub methods: Vec<(String, ShaderType)>,
+}
+
+/// Builds the scope of a `draw_*` object from the draw shader of the framework widget it is
+/// a property of, when `framework` names a known `(widget, property)`, and then from the
+/// live `layers` overriding it, base first. Only scopes rooted in a known draw shader are
+/// complete.
+pub fn inherited_scope(framework: Option<(&str, &str)>, layers: &[ShaderLayer]) -> ShaderScope {
+  let mut scope = ShaderScope::default();
+  let shader_type = |name: &str| ShaderType::from_name(name).unwrap_or(ShaderType::Unknown);
+
+  let entry = framework.and_then(|(widget, property)| widget_draw_shader(widget, property));
+  if let Some(entry) = entry {
+    for draw_shader in draw_shader_chain(entry.draw_shader) {
+      for (name, ty) in draw_shader.fields {
+        add_field(&mut scope, name, shader_type(ty), draw_shader.name);
+      }
+      for (name, ty) in draw_shader.methods {
+        scope.methods.insert(name.to_string(), shader_type(ty));
+      }
+    }
+    for (name, ty) in DRAW_LIST_UNIFORMS {
+      add_field(&mut scope, name, shader_type(ty), "the draw list");
+    }
+    for (name, ty) in entry.instances {
+      add_field(&mut scope, name, shader_type(ty), entry.widget);
+    }
+    scope.is_complete = true;
+  }
+
+  for layer in layers {
+    for (name, ty) in &layer.fields {
+      add_field(&mut scope, name, *ty, &layer.origin);
+    }
+    for (name, ty) in &layer.methods {
+      scope.methods.insert(name.clone(), *ty);
+    }
+  }
+  scope
+}
+
+/// Adds a field unless an earlier layer declares it already.
+fn add_field(scope: &mut ShaderScope, name: &str, ty: ShaderType, origin: &str) {
+  let field = scope.fields.entry(name.to_string()).or_insert_with(|| ShaderField {
+    ty,
+    origin: origin.to_string(),
+  });
+  // A value such as `color: (THEME_COLOR)` doesn't say more than the declaration.
+  if field.ty == ShaderType::Unknown {
+    field.ty = ty;
+  }
+}
+
+/// The fields and `fn`s to offer after `self.`.
+pub fn field_completion_items(scope: &ShaderScope) -> Vec<CompletionItem> {
+  let mut items: Vec<CompletionItem> = scope
+    .fields
+    .iter()
+    .map(|(name, field)| CompletionItem {
+      label: name.clone(),
+      kind: Some(CompletionItemKind::FIELD),
+      detail: Some(field.ty.to_string()),
+      documentation: Some(markdown(format!("Declared by `{}`.", field.origin))),
+      ..Default::default()
+    })
+    .chain(scope.methods.iter().map(|(name, ty)| CompletionItem {
+      label: name.clone(),
+      kind: Some(CompletionItemKind::METHOD),
+      detail: Some(format!("fn {}(self) -> {}", name, ty)),
+      ..Default::default()
+    }))
+    .collect();
+  items.sort_by(|a, b| a.label.cmp(&b.label));
+  items
+}
+
+/// Describes `self.name`.
+pub fn field_hover(scope: &ShaderScope, name: &str) -> Option<String> {
+  if let Some(field) = scope.fields.get(name) {
+    return Some(format!(
+      "```makepad\nself.{}: {}\n```\nDeclared by `{}`.",
+      name, field.ty, field.origin
+    ));
+  }
+  let ty = scope.methods.get(name)?;
+  Some(format!("```makepad\nfn {}(self) -> {}\n```", name, ty))
+}
+
+fn markdown(value: String) -> Documentation {
+  Documentation::MarkupContent(MarkupContent { kind: MarkupKind::Markdown, value })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_inherited_scope() {
+    let layers = [
+      ShaderLayer {
+        origin: "AppTab".to_string(),
+        fields: vec![
+          ("color_selected".to_string(), ShaderType::Vec4),
+          ("color".to_string(), ShaderType::Unknown),
+        ],
+        methods: vec![("get_color".to_string(), ShaderType::Vec4)],
+      },
+      ShaderLayer {
+        origin: "chat_tab".to_string(),
+        fields: vec![("glow".to_string(), ShaderType::Float)],
+        methods: vec![],
+      },
+    ];
+    let scope = inherited_scope(Some(("RadioButton", "draw_icon")), &layers);
+    assert!(scope.is_complete);
+    let origin = |name: &str| scope.fields.get(name).map(|field| (field.ty, field.origin.as_str()));
+    assert_eq!(origin("pos"), Some((ShaderType::Vec2, "DrawQuad")));
+    assert_eq!(origin("color"), Some((ShaderType::Vec4, "DrawIcon")));
+    assert_eq!(origin("selected"), Some((ShaderType::Float, "RadioButton")));
+    assert_eq!(origin("color_selected"), Some((ShaderType::Vec4, "AppTab")));
+    assert_eq!(origin("glow"), Some((ShaderType::Float, "chat_tab")));
+    assert_eq!(origin("pressed"), None);
+    assert_eq!(
+      field_hover(&scope, "selected").unwrap(),
+      "```makepad\nself.selected: float\n```\nDeclared by `RadioButton`."
+    );
+
+    let labels: Vec<String> = field_completion_items(&scope)
+      .into_iter()
+      .map(|item| item.label)
+      .collect();
+    assert!(labels.contains(&"rect_size".to_string()));
+    assert!(labels.contains(&"get_color".to_string()));
+
+    let scope = inherited_scope(Some(("MyWidget", "draw_bg")), &layers);
+    assert!(!scope.is_complete);
+    assert_eq!(scope.fields.len(), 3);
+  }
+}
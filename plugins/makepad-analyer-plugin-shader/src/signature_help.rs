@@ -0,0 +1,138 @@
+use makepad_analyzer_plugin_types::{
+  Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, SignatureHelp,
+  SignatureInformation,
+};
+
+use crate::builtins::{builtin_overload, sdf2d_method, ShaderFunction};
+
+/// The signature of the call `offset` is in, for the shader `source`, e.g. the body of a
+/// `fn pixel(self) -> vec4 { ... }`.
+pub fn signature_help(source: &str, offset: usize) -> Option<SignatureHelp> {
+  let (function, active_parameter) = enclosing_call(source, offset)?;
+  let label = function.label();
+  let mut parameters = vec![];
+  let mut start = function.name.len() + 1;
+  for (name, ty) in function.params {
+    let len = name.len() + 2 + ty.len();
+    parameters.push(ParameterInformation {
+      label: ParameterLabel::LabelOffsets([start as u32, (start + len) as u32]),
+      documentation: None,
+    });
+    start += len + 2;
+  }
+
+  Some(SignatureHelp {
+    signatures: vec![SignatureInformation {
+      label,
+      documentation: Some(Documentation::MarkupContent(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: function.doc.to_string(),
+      })),
+      parameters: Some(parameters),
+      active_parameter: Some(active_parameter),
+    }],
+    active_signature: Some(0),
+    active_parameter: Some(active_parameter),
+  })
+}
+
+/// The function called by the innermost unclosed `(` before `offset` and the index of the
+/// argument `offset` is in. Calls after `.` or `::` are `Sdf2d` methods.
+fn enclosing_call(source: &str, offset: usize) -> Option<(&'static ShaderFunction, u32)> {
+  let source = without_comments(source.get(..offset)?);
+  let mut depth = 0;
+  let mut commas = 0;
+  let mut open = None;
+  for (index, c) in source.char_indices().rev() {
+    match c {
+      ')' | ']' => depth += 1,
+      '(' | '[' if depth > 0 => depth -= 1,
+      '(' => {
+        open = Some(index);
+        break;
+      }
+      ',' if depth == 0 => commas += 1,
+      '{' | '}' | ';' if depth == 0 => return None,
+      _ => {}
+    }
+  }
+
+  let callee = source[..open?].trim_end();
+  let name_start = callee
+    .rfind(|c: char| !c.is_alphanumeric() && c != '_')
+    .map_or(0, |index| index + 1);
+  let name = &callee[name_start..];
+  let qualifier = callee[..name_start].trim_end();
+  let function = match qualifier.ends_with('.') || qualifier.ends_with("::") {
+    true => sdf2d_method(name)?,
+    false => builtin_overload(name, commas as usize + 1)?,
+  };
+  Some((function, commas))
+}
+
+/// `source` with `//` comments blanked out, so the offsets stay the same.
+fn without_comments(source: &str) -> String {
+  source
+    .split('\n')
+    .map(|line| match line.find("//") {
+      Some(index) => format!("{}{}", &line[..index], " ".repeat(line.len() - index)),
+      None => line.to_string(),
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// The label and active parameter at the `|` in `source`.
+  fn help_at(source: &str) -> Option<(String, String)> {
+    let offset = source.find('|').unwrap();
+    let source = source.replacen('|', "", 1);
+    let help = signature_help(&source, offset)?;
+    let signature = &help.signatures[0];
+    let Some(ParameterLabel::LabelOffsets([start, end])) = signature
+      .parameters
+      .as_ref()
+      .and_then(|parameters| parameters.get(help.active_parameter? as usize))
+      .map(|parameter| parameter.label.clone())
+    else {
+      return Some((signature.label.clone(), String::new()));
+    };
+    let parameter = signature.label[start as usize..end as usize].to_string();
+    Some((signature.label.clone(), parameter))
+  }
+
+  #[test]
+  fn test_builtin_signature_help() {
+    let source = "return mix(#f00, vec4(1.0, 0.0, 0.0, 1.0), |self.hover);";
+    assert_eq!(
+      help_at(source),
+      Some(("mix(x: T, y: T, a: float) -> T".to_string(), "a: float".to_string()))
+    );
+    let source = "return mix(#f00, vec4(1.0, |";
+    assert_eq!(help_at(source).unwrap().1, "y: float");
+    let source = "let a = smoothstep(|";
+    assert_eq!(help_at(source).unwrap().1, "edge0: float");
+    assert_eq!(help_at("let a = atan(|").unwrap().1, "y_over_x: T");
+    assert_eq!(help_at("let a = atan(self.pos.y, |").unwrap().1, "x: T");
+    assert_eq!(help_at("let a = 1.0; |"), None);
+    assert_eq!(help_at("let a = unknown(|"), None);
+  }
+
+  #[test]
+  fn test_sdf2d_signature_help() {
+    let source = "let sdf = Sdf2d::viewport(|self.pos * self.rect_size);";
+    assert_eq!(
+      help_at(source),
+      Some(("viewport(pos: vec2) -> Sdf2d".to_string(), "pos: vec2".to_string()))
+    );
+    let source = "sdf.box(1.0, 1.0, // the corner (x, y)\n    self.rect_size.x, |";
+    assert_eq!(help_at(source).unwrap().1, "h: float");
+    let source = "sdf.stroke(mix(#f00, #0f0, self.hover), |1.0)";
+    assert_eq!(help_at(source).unwrap().1, "width: float");
+    // `fill` is a method, not a builtin.
+    assert_eq!(help_at("fill(|"), None);
+  }
+}
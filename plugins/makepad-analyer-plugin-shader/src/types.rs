@@ -0,0 +1,142 @@
+use std::fmt;
+
+/// The type of a shader value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShaderType {
+  Float,
+  Int,
+  UInt,
+  Bool,
+  Vec2,
+  Vec3,
+  Vec4,
+  IVec2,
+  IVec3,
+  IVec4,
+  BVec2,
+  BVec3,
+  BVec4,
+  Mat2,
+  Mat3,
+  Mat4,
+  Texture2D,
+  Sdf2d,
+  Void,
+  /// Anything the checker can't tell, which is never reported as a mismatch.
+  Unknown,
+}
+
+impl ShaderType {
+  pub fn from_name(name: &str) -> Option<ShaderType> {
+    Some(match name {
+      "float" => ShaderType::Float,
+      "int" => ShaderType::Int,
+      "uint" => ShaderType::UInt,
+      "bool" => ShaderType::Bool,
+      "vec2" => ShaderType::Vec2,
+      "vec3" => ShaderType::Vec3,
+      "vec4" => ShaderType::Vec4,
+      "ivec2" => ShaderType::IVec2,
+      "ivec3" => ShaderType::IVec3,
+      "ivec4" => ShaderType::IVec4,
+      "bvec2" => ShaderType::BVec2,
+      "bvec3" => ShaderType::BVec3,
+      "bvec4" => ShaderType::BVec4,
+      "mat2" => ShaderType::Mat2,
+      "mat3" => ShaderType::Mat3,
+      "mat4" => ShaderType::Mat4,
+      "texture2d" | "texture2D" => ShaderType::Texture2D,
+      "Sdf2d" => ShaderType::Sdf2d,
+      "void" => ShaderType::Void,
+      _ => return None,
+    })
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      ShaderType::Float => "float",
+      ShaderType::Int => "int",
+      ShaderType::UInt => "uint",
+      ShaderType::Bool => "bool",
+      ShaderType::Vec2 => "vec2",
+      ShaderType::Vec3 => "vec3",
+      ShaderType::Vec4 => "vec4",
+      ShaderType::IVec2 => "ivec2",
+      ShaderType::IVec3 => "ivec3",
+      ShaderType::IVec4 => "ivec4",
+      ShaderType::BVec2 => "bvec2",
+      ShaderType::BVec3 => "bvec3",
+      ShaderType::BVec4 => "bvec4",
+      ShaderType::Mat2 => "mat2",
+      ShaderType::Mat3 => "mat3",
+      ShaderType::Mat4 => "mat4",
+      ShaderType::Texture2D => "texture2d",
+      ShaderType::Sdf2d => "Sdf2d",
+      ShaderType::Void => "void",
+      ShaderType::Unknown => "{unknown}",
+    }
+  }
+
+  pub fn is_scalar(&self) -> bool {
+    matches!(self, ShaderType::Float | ShaderType::Int | ShaderType::UInt)
+  }
+
+  /// The number of components of a vector, 1 for scalars.
+  pub fn components(&self) -> Option<usize> {
+    match self {
+      ShaderType::Float | ShaderType::Int | ShaderType::UInt | ShaderType::Bool => Some(1),
+      ShaderType::Vec2 | ShaderType::IVec2 | ShaderType::BVec2 => Some(2),
+      ShaderType::Vec3 | ShaderType::IVec3 | ShaderType::BVec3 => Some(3),
+      ShaderType::Vec4 | ShaderType::IVec4 | ShaderType::BVec4 => Some(4),
+      _ => None,
+    }
+  }
+
+  /// Whether this is a vector of two to four components, of any element type.
+  pub fn is_vector(&self) -> bool {
+    self.components().is_some_and(|components| components > 1)
+  }
+
+  /// The type of one component of a vector, or the type itself for anything else.
+  pub fn element(&self) -> ShaderType {
+    match self {
+      ShaderType::Vec2 | ShaderType::Vec3 | ShaderType::Vec4 => ShaderType::Float,
+      ShaderType::IVec2 | ShaderType::IVec3 | ShaderType::IVec4 => ShaderType::Int,
+      ShaderType::BVec2 | ShaderType::BVec3 | ShaderType::BVec4 => ShaderType::Bool,
+      ty => *ty,
+    }
+  }
+
+  /// The vector of `components` values of type `element`, or `element` itself for one.
+  pub fn vector(element: ShaderType, components: usize) -> ShaderType {
+    use ShaderType::*;
+    match (element, components) {
+      (element, 1) => element,
+      (Float, 2) => Vec2,
+      (Float, 3) => Vec3,
+      (Float, 4) => Vec4,
+      (Int, 2) => IVec2,
+      (Int, 3) => IVec3,
+      (Int, 4) => IVec4,
+      (Bool, 2) => BVec2,
+      (Bool, 3) => BVec3,
+      (Bool, 4) => BVec4,
+      _ => Unknown,
+    }
+  }
+
+  /// Whether a `found` value can go where an `expected` one is. Integers pass for floats,
+  /// as the literals in live code are written either way.
+  pub fn accepts(&self, found: ShaderType) -> bool {
+    *self == found
+      || *self == ShaderType::Unknown
+      || found == ShaderType::Unknown
+      || (self.is_scalar() && found.is_scalar())
+  }
+}
+
+impl fmt::Display for ShaderType {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.name())
+  }
+}
@@ -1,65 +1,93 @@
-use makepad_analyzer_plugin_types::{CompletionItem, CompletionItemKind, Documentation, InsertTextFormat, MarkupContent, MarkupKind, Position, Url};
+use makepad_analyzer_plugin_types::{CompletionItem, CompletionItemKind, Documentation, MarkupContent, MarkupKind, PluginDocument, Position};
 
 enum LiveCompletionContext {
-  Link,     // link::
-  Crate,    // crate::
-  Use,      // use
-  None,     // pub and dep, etc. No need to dynamically generate completion items.
+  Link,         // link::
+  Crate,        // crate::
+  Declaration,  // use and pub, at the start of a top-level line.
+  None,
 }
 
+/// Completes the keywords of the `live_design!` block at `position` of `document`.
 pub fn handle_completion(
-  uri: &Url,
+  document: &PluginDocument,
   position: Position,
-  trigger_char: &str,
 ) -> Vec<CompletionItem> {
+  if document.ast().block_at(document.offset(position)).is_none() {
+    return vec![];
+  }
+
+  match match_keyword(document, position) {
+    LiveCompletionContext::Link => completion_items_for_link_keyword(),
+    LiveCompletionContext::Crate => completion_items_for_crate_keyword(),
+    LiveCompletionContext::Declaration => {
+      let mut items = completion_items_for_use_keyword();
+      items.extend(completion_items_for_pub_keyword());
+      items
+    }
+    LiveCompletionContext::None => vec![],
+  }
+}
 
-  let shifted_position = Position {
-    line: position.line,
-    character: position.character - trigger_char.len() as u32 - 1,
-  };
-
-  tracing::info!("completion_items: shifted_position: {:?}", shifted_position);
-
-  // I'm not sure how to get the document from the session.
-
-  // let document = match session.get_document(uri) {
-  //   Some(document) => document,
-  //   None => return None,
-  // };
-
-  // let line = match document.get_line(position.line as usize) {
-  //   Some(line) => line,
-  //   None => return None,
-  // };
-
-  // match match_keyword(line, position.character as usize, trigger_char) {
-  //   LiveCompletionContext::Link => completion_items_for_link_keyword(),
-  //   LiveCompletionContext::Crate => completion_items_for_crate_keyword(),
-  //   LiveCompletionContext::Use => completion_items_for_use_keyword(),
-  //   LiveCompletionContext::None => completion_items_for_static_keywords()
-  // };
-
-  let static_completion_itmes = completion_items_for_static_keywords();
-
-  let mut completion_items = vec![];
-
-  completion_items.extend(static_completion_itmes);
-
-  completion_items
+fn match_keyword(document: &PluginDocument, position: Position) -> LiveCompletionContext {
+  let prefix = document.line_prefix(position);
+  if prefix.ends_with("link::") {
+    LiveCompletionContext::Link
+  } else if prefix.ends_with("crate::") || prefix.ends_with("crate://") {
+    LiveCompletionContext::Crate
+  } else if is_declaration_start(document, position) {
+    LiveCompletionContext::Declaration
+  } else {
+    LiveCompletionContext::None
+  }
 }
 
-fn match_keyword(line: &str, character: usize, trigger_char: &str) -> LiveCompletionContext {
-  todo!()
+/// Whether `position` is at the start of a line outside of every widget body, where a `use`
+/// or a definition can start.
+fn is_declaration_start(document: &PluginDocument, position: Position) -> bool {
+  let word = document.line_prefix(position).trim_start();
+  if !word.chars().all(|c| c == '_' || c.is_alphanumeric()) {
+    return false;
+  }
+  let offset = document.offset(position);
+  !document.ast().widgets().any(|widget| {
+    widget.body_span.is_some_and(|body| body.start < offset && offset < body.end)
+  })
 }
 
 /// Returns completion items for the `link` keyword.
 fn completion_items_for_link_keyword() -> Vec<CompletionItem> {
-  todo!()
+  [
+    ("theme", "The colors, fonts and sizes of the default theme."),
+    ("shaders", "The shader helpers such as `Sdf2d`."),
+    ("widgets", "The framework widgets such as `View` and `Button`."),
+  ]
+  .iter()
+  .map(|(name, doc)| CompletionItem {
+    label: name.to_string(),
+    kind: Some(CompletionItemKind::MODULE),
+    documentation: Some(Documentation::MarkupContent(MarkupContent {
+      kind: MarkupKind::Markdown,
+      value: doc.to_string(),
+    })),
+    ..Default::default()
+  })
+  .collect()
 }
 
 /// Returns completion items for the `crate::` and `crate://` keywords.
+///
+/// Module paths and resource files depend on the package on disk and are offered by the
+/// session; this only offers the `crate://self/` root of dependencies.
 fn completion_items_for_crate_keyword() -> Vec<CompletionItem> {
-  todo!()
+  vec![
+    CompletionItem {
+      label: "self".to_string(),
+      kind: Some(CompletionItemKind::FOLDER),
+      detail: Some("crate://self/".to_string()),
+      insert_text: Some("self/".to_string()),
+      ..Default::default()
+    }
+  ]
 }
 
 /// Returns completion items for the `use` keyword.
@@ -90,8 +118,8 @@ fn completion_items_for_use_keyword() -> Vec<CompletionItem> {
   ]
 }
 
-/// Returns static completion items for keywords the `pub` and `dep("")`.
-fn completion_items_for_static_keywords() -> Vec<CompletionItem> {
+/// Returns completion items for the `pub` keyword.
+fn completion_items_for_pub_keyword() -> Vec<CompletionItem> {
   vec![
     CompletionItem {
       label: "pub".to_string(),
@@ -109,27 +137,32 @@ fn completion_items_for_static_keywords() -> Vec<CompletionItem> {
         }
       )),
       ..Default::default()
-    },
-    CompletionItem {
-      label: "dep".to_string(),
-      kind: Some(CompletionItemKind::KEYWORD),
-      documentation: Some(Documentation::MarkupContent(
-        MarkupContent {
-            kind: MarkupKind::Markdown,
-            value: [
-              "**dep**",
-              "The `dep` keyword is used to declare a dependency.",
-              "Example: ",
-              "```rust",
-              "ICON_ADD = dep(\"crate://self/resources/icon_add.svg\")",
-              "```",
-            ]
-            .join("\n"),
-        },
-      )),
-      insert_text: Some("dep(\"$1\")".to_string()),
-      insert_text_format: Some(InsertTextFormat::SNIPPET),
-      ..Default::default()
     }
   ]
 }
+
+#[cfg(test)]
+mod tests {
+  use makepad_analyzer_plugin_types::Url;
+
+  use super::*;
+
+  fn labels(text: &str, position: Position) -> Vec<String> {
+    let document = PluginDocument::new(Url::parse("file:///app/src/app.rs").unwrap(), text);
+    handle_completion(&document, position).into_iter().map(|item| item.label).collect()
+  }
+
+  #[test]
+  fn test_handle_completion() {
+    let text = concat!(
+      "live_design! {\n  use link::\n  us\n",
+      "  App = {{App}} {\n    u\n    body = <View> {}\n  }\n}\nfn main() {}\n",
+    );
+    assert_eq!(labels(text, Position::new(1, 12)), ["theme", "shaders", "widgets"]);
+    assert_eq!(labels(text, Position::new(2, 4)), ["use", "pub"]);
+    assert!(labels(text, Position::new(4, 5)).is_empty());
+    assert!(labels(text, Position::new(5, 18)).is_empty());
+    assert!(labels(text, Position::new(3, 8)).is_empty());
+    assert!(labels(text, Position::new(9, 3)).is_empty());
+  }
+}
@@ -2,18 +2,27 @@ mod capabilities;
 
 pub use capabilities::*;
 
-use makepad_analyzer_plugin_types::{CompletionItem, MakepadAnalyzerPlugin, PluginCapability, PluginInfo, Position, Url};
+use makepad_analyzer_plugin_types::{CompletionItem, DocumentRegion, MakepadAnalyzerPlugin, PluginCapability, PluginDocument, PluginInfo, Position, ProvidedCapabilities};
 
 struct LivePluginCapability;
 
 impl PluginCapability for LivePluginCapability {
+  fn provided(&self) -> ProvidedCapabilities {
+    ProvidedCapabilities {
+      regions: vec![DocumentRegion::LiveDesign],
+      completion: true,
+      completion_trigger_characters: vec![":".to_string(), "/".to_string()],
+      ..ProvidedCapabilities::default()
+    }
+  }
+
   fn handle_completion(
     &self,
-    uri: &Url,
+    document: &PluginDocument,
     position: Position,
-    trigger_char: &str,
+    _trigger_char: Option<&str>,
   ) -> Vec<CompletionItem> {
-    capabilities::handle_completion(uri, position, trigger_char)
+    capabilities::handle_completion(document, position)
   }
 }
 
@@ -30,6 +39,10 @@ impl MakepadAnalyzerPlugin for MakepadAnalyzerLivePlugin {
   fn capabilities(&self) -> &dyn PluginCapability {
     &self.capability
   }
+
+  fn api_version(&self) -> u32 {
+    1
+  }
 }
 
 impl MakepadAnalyzerLivePlugin {